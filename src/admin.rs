@@ -0,0 +1,293 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use goose::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::common::{self, ContentType};
+use crate::config;
+use crate::errors;
+use crate::metrics;
+use crate::random;
+
+/// The weighted_users_index of the admin user that invalidates caches, usize::MAX until
+/// one is elected.
+static INVALIDATOR: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// How the admin user invalidates caches.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Invalidation {
+    /// Clear all caches from the performance page.
+    ClearCaches,
+    /// Save a random node, invalidating its cache tags.
+    SaveNode,
+    /// Randomly clear all caches or save a node.
+    Both,
+}
+
+impl std::str::FromStr for Invalidation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clear" => Ok(Invalidation::ClearCaches),
+            "save" => Ok(Invalidation::SaveNode),
+            "both" => Ok(Invalidation::Both),
+            _ => Err(format!("unknown invalidation: {}", s)),
+        }
+    }
+}
+
+/// Returns the machine name Drupal uses for a content type, used in form IDs.
+fn machine_name(content_type: &ContentType) -> &'static str {
+    match content_type {
+        ContentType::Article => "article",
+        ContentType::BasicPage => "page",
+        ContentType::Recipe => "recipe",
+    }
+}
+
+/// Load the form at the path, then submit it as a browser would, replacing the named
+/// fields with new values. A successful submission redirects, either to another page or
+/// back to the form with a status message, so anything else is a failure.
+async fn submit_form(
+    user: &GooseUser,
+    path: &str,
+    name: &str,
+    form_id: &str,
+    values: &[(&str, &str)],
+) -> GooseTaskResult {
+    let mut goose = user.get_named(path, name).await?;
+
+    let mut submitted;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(html) => {
+                    if let Some(error) = errors::detect_drupal_error(&html) {
+                        return user.set_failure(
+                            &format!("{}: {}", goose.request.url, error),
+                            &mut goose.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+
+                    let mut fields = match common::get_form_fields(&html, form_id) {
+                        Some(fields) => fields,
+                        None => {
+                            return user.set_failure(
+                                &format!("{}: no {} form on page", goose.request.url, form_id),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            );
+                        }
+                    };
+                    for (field, value) in values {
+                        fields.retain(|(existing, _)| existing != field);
+                        fields.push((field.to_string(), value.to_string()));
+                    }
+
+                    let request_builder = user.goose_post(path).await?;
+                    submitted = user
+                        .goose_send(request_builder.form(&fields), Some(name))
+                        .await?;
+                }
+                Err(e) => {
+                    return user.set_failure(
+                        &format!("{}: failed to parse page: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    match submitted.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(html) => {
+                    if let Some(error) = errors::detect_drupal_error(&html) {
+                        return user.set_failure(
+                            &format!("{}: {}", submitted.request.url, error),
+                            &mut submitted.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+
+                    // Drupal redirects after a form is successfully submitted, and
+                    // displays the form again with an error message otherwise. Goose
+                    // only sees a redirect to another page, so a form that redirects to
+                    // itself is recognized by its status message.
+                    if !submitted.request.redirected
+                        && !html.contains(r#"aria-label="Status message""#)
+                    {
+                        return user.set_failure(
+                            &format!("{}: {} wasn't submitted", submitted.request.url, form_id),
+                            &mut submitted.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+                }
+                Err(e) => {
+                    return user.set_failure(
+                        &format!("{}: failed to parse page: {}", submitted.request.url, e),
+                        &mut submitted.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", submitted.request.url, e),
+                &mut submitted.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Log in as the admin user. Runs once when the admin user starts, the session cookie is
+/// then sent with every request.
+pub async fn log_in(user: &GooseUser) -> GooseTaskResult {
+    let configuration = config::get_configuration();
+    let values = [
+        (
+            "name",
+            configuration.admin_user.as_deref().unwrap_or("admin"),
+        ),
+        (
+            "pass",
+            configuration.admin_password.as_deref().unwrap_or_default(),
+        ),
+        ("op", "Log in"),
+    ];
+    submit_form(
+        user,
+        "/user/login",
+        "admin /user/login",
+        "user_login_form",
+        &values,
+    )
+    .await
+}
+
+/// Elect the first admin user to start as the one that invalidates caches, and log it in.
+/// Goose allocates an admin user every time it cycles through the task sets, so with many
+/// users there are several, and the others stay idle so caches are only invalidated once
+/// per interval.
+pub async fn elect_invalidator(user: &GooseUser) -> GooseTaskResult {
+    let elected = INVALIDATOR
+        .compare_exchange(
+            usize::MAX,
+            user.weighted_users_index,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        )
+        .is_ok();
+    if !elected {
+        return Ok(());
+    }
+    log_in(user).await
+}
+
+/// Forget the elected admin user, so the next load test run in the same process elects
+/// its own.
+pub fn reset_invalidator() {
+    INVALIDATOR.store(usize::MAX, Ordering::SeqCst);
+}
+
+/// Clear all caches from the performance page, as an admin would after a deploy.
+pub async fn clear_caches(user: &GooseUser) -> GooseTaskResult {
+    submit_form(
+        user,
+        "/admin/config/development/performance",
+        "admin clear caches",
+        "system_performance_settings",
+        &[("op", "Clear all caches")],
+    )
+    .await?;
+    metrics::record_invalidation("clear all caches");
+
+    Ok(())
+}
+
+/// Flush an image style, deleting all of its derivatives so Drupal generates each one again
+/// the next time it's requested.
+pub async fn flush_image_style(user: &GooseUser, style: &str) -> GooseTaskResult {
+    submit_form(
+        user,
+        &format!("/admin/config/media/image-styles/manage/{}/flush", style),
+        "admin /admin/config/media/image-styles/manage/%/flush",
+        "image_style_flush_form",
+        &[("op", "Flush")],
+    )
+    .await
+}
+
+/// Save a random node without changing it, invalidating the cache tags of the node and of
+/// the listings it appears in.
+pub async fn save_node(user: &GooseUser) -> GooseTaskResult {
+    let content_types = [
+        ContentType::Article,
+        ContentType::BasicPage,
+        ContentType::Recipe,
+    ];
+    let content_type = content_types.choose(&mut random::user_rng(user)).unwrap();
+    let nodes = common::get_nodes(content_type);
+    let node = nodes.choose(&mut random::user_rng(user)).unwrap();
+
+    let path = format!("/node/{}/edit", node.nid);
+    let form_id = format!("node_{}_edit_form", machine_name(content_type));
+    submit_form(
+        user,
+        &path,
+        "admin /node/%nid/edit",
+        &form_id,
+        &[("op", "Save")],
+    )
+    .await?;
+    metrics::record_invalidation(&format!("save /node/{}", node.nid));
+
+    Ok(())
+}
+
+/// Invalidate caches as configured with --invalidate, if the user was elected to.
+pub async fn invalidate_caches(user: &GooseUser) -> GooseTaskResult {
+    if INVALIDATOR.load(Ordering::SeqCst) != user.weighted_users_index {
+        return Ok(());
+    }
+
+    let save = match config::get_configuration().invalidate {
+        Some(Invalidation::SaveNode) => true,
+        Some(Invalidation::Both) => random::user_rng(user).gen(),
+        _ => false,
+    };
+    if save {
+        save_node(user).await
+    } else {
+        clear_caches(user).await
+    }
+}
@@ -0,0 +1,385 @@
+use std::fmt;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::header::HeaderMap;
+
+use crate::common::{self, PageType};
+
+lazy_static! {
+    /// Assertions loaded from the file passed with --assertions, added to the defaults.
+    static ref CUSTOM_ASSERTIONS: RwLock<Vec<(Option<PageType>, Assertion)>> =
+        RwLock::new(Vec::new());
+
+    /// Selectors are matched on every page load, so only compile the patterns once.
+    static ref SELECTOR_PART: Regex =
+        Regex::new(r#"([#.]?)([-\w]+)|\[([-\w]+)(?:="?([^"\]]*)"?)?\]"#).unwrap();
+    /// A start or end tag, capturing the slash of an end tag, the tag name and attributes.
+    static ref TAG: Regex = Regex::new(r"<(/?)([a-zA-Z][a-zA-Z0-9-]*)([^>]*)>").unwrap();
+}
+
+/// Elements that never have an end tag, so never contain other elements.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// A check performed on every page of a given type, in addition to validating the title.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Assertion {
+    /// The page must contain an element matching this CSS selector.
+    RequiredSelector(String),
+    /// The page must not contain this string.
+    ForbiddenString(String),
+    /// The response must include this header.
+    RequiredHeader(String),
+    /// The response must not include this header.
+    ForbiddenHeader(String),
+    /// The page must be no larger than this many bytes.
+    MaxBodySize(usize),
+}
+
+/// A named type of failure, returned when an assertion is not met.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssertionFailure {
+    MissingSelector(String),
+    ForbiddenString(String),
+    MissingHeader(String),
+    ForbiddenHeader(String),
+    BodyTooLarge { size: usize, max: usize },
+}
+
+impl fmt::Display for AssertionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssertionFailure::MissingSelector(selector) => {
+                write!(f, "missing selector: {}", selector)
+            }
+            AssertionFailure::ForbiddenString(string) => write!(f, "forbidden string: {}", string),
+            AssertionFailure::MissingHeader(header) => write!(f, "missing header: {}", header),
+            AssertionFailure::ForbiddenHeader(header) => write!(f, "forbidden header: {}", header),
+            AssertionFailure::BodyTooLarge { size, max } => {
+                write!(f, "body too large: {} bytes (max {})", size, max)
+            }
+        }
+    }
+}
+
+/// Returns all assertions for the specified page type: those that apply to every page,
+/// those specific to the page type, and any loaded with --assertions.
+pub fn get_assertions(page_type: &PageType) -> Vec<Assertion> {
+    // Every page on the site.
+    let mut assertions = vec![Assertion::MaxBodySize(1024 * 1024)];
+
+    // Every page that renders the language switcher block.
+    match page_type {
+        PageType::Search | PageType::NotFound | PageType::ViewsAjax => (),
+        _ => assertions.push(Assertion::RequiredSelector(
+            "#block-umami-languageswitcher".to_string(),
+        )),
+    }
+
+    match page_type {
+        PageType::Article | PageType::BasicPage => {
+            assertions.push(Assertion::RequiredSelector(
+                "article .field--name-body".to_string(),
+            ));
+        }
+        PageType::Recipe => {
+            assertions.push(Assertion::RequiredSelector(
+                "article .field--name-field-ingredients".to_string(),
+            ));
+        }
+        PageType::ArticleListing | PageType::RecipeListing | PageType::ViewsAjax => {
+            assertions.push(Assertion::RequiredSelector(".view-content".to_string()));
+        }
+        PageType::Contact => {
+            assertions.push(Assertion::RequiredSelector(
+                "form#contact-message-feedback-form".to_string(),
+            ));
+        }
+        PageType::Search => {
+            assertions.push(Assertion::RequiredSelector("form#search-form".to_string()));
+        }
+        PageType::Front | PageType::Term | PageType::NotFound => (),
+    }
+
+    for (custom_page_type, assertion) in CUSTOM_ASSERTIONS.read().unwrap().iter() {
+        if custom_page_type.is_none() || custom_page_type.as_ref() == Some(page_type) {
+            assertions.push(assertion.clone());
+        }
+    }
+
+    assertions
+}
+
+/// Evaluate assertions against a page, returning the first failure.
+pub fn evaluate(
+    assertions: &[Assertion],
+    html: &str,
+    headers: &HeaderMap,
+) -> Result<(), AssertionFailure> {
+    // Only parse the page once, and only if a selector has to be matched.
+    let mut document = None;
+    for assertion in assertions {
+        match assertion {
+            Assertion::RequiredSelector(selector) => {
+                let document = document.get_or_insert_with(|| Document::parse(html));
+                if !document.matches(selector) {
+                    return Err(AssertionFailure::MissingSelector(selector.clone()));
+                }
+            }
+            Assertion::ForbiddenString(string) => {
+                if html.contains(string.as_str()) {
+                    return Err(AssertionFailure::ForbiddenString(string.clone()));
+                }
+            }
+            Assertion::RequiredHeader(header) => {
+                if !headers.contains_key(header.as_str()) {
+                    return Err(AssertionFailure::MissingHeader(header.clone()));
+                }
+            }
+            Assertion::ForbiddenHeader(header) => {
+                if headers.contains_key(header.as_str()) {
+                    return Err(AssertionFailure::ForbiddenHeader(header.clone()));
+                }
+            }
+            Assertion::MaxBodySize(max) => {
+                if html.len() > *max {
+                    return Err(AssertionFailure::BodyTooLarge {
+                        size: html.len(),
+                        max: *max,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A compound selector, such as `div#main.container[role="main"]`.
+#[derive(Debug, Default)]
+struct CompoundSelector {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>,
+}
+
+impl CompoundSelector {
+    fn parse(selector: &str) -> CompoundSelector {
+        let mut compound = CompoundSelector::default();
+        for captures in SELECTOR_PART.captures_iter(selector) {
+            if let Some(attribute) = captures.get(3) {
+                compound.attributes.push((
+                    attribute.as_str().to_lowercase(),
+                    captures.get(4).map(|value| value.as_str().to_string()),
+                ));
+                continue;
+            }
+            let name = captures[2].to_string();
+            match &captures[1] {
+                "#" => compound.id = Some(name),
+                "." => compound.classes.push(name),
+                _ => compound.tag = Some(name.to_lowercase()),
+            }
+        }
+        compound
+    }
+
+    fn matches(&self, tag: &str, attributes: &[(String, String)]) -> bool {
+        let attribute = |name: &str| {
+            attributes
+                .iter()
+                .find(|(attribute, _)| attribute == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        if let Some(expected) = &self.tag {
+            if expected != tag {
+                return false;
+            }
+        }
+        if let Some(expected) = &self.id {
+            if attribute("id") != Some(expected.as_str()) {
+                return false;
+            }
+        }
+        let classes: Vec<&str> = attribute("class")
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        if !self
+            .classes
+            .iter()
+            .all(|class| classes.contains(&class.as_str()))
+        {
+            return false;
+        }
+        self.attributes
+            .iter()
+            .all(|(name, expected)| match (attribute(name), expected) {
+                (Some(value), Some(expected)) => value == expected,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+    }
+}
+
+/// An element of a page, with its lower-cased tag name and attributes.
+#[derive(Debug)]
+struct Element {
+    tag: String,
+    attributes: Vec<(String, String)>,
+    /// The index of the element containing this one, if any.
+    parent: Option<usize>,
+}
+
+/// The elements of a page and how they're nested, parsed once so every selector asserted
+/// on the page can be matched against it.
+#[derive(Debug)]
+pub struct Document {
+    elements: Vec<Element>,
+}
+
+impl Document {
+    /// Parse the elements of a page. This isn't a full HTML parser: end tags close the most
+    /// recent matching open element, and elements left open, such as `<p>` without `</p>`,
+    /// are closed along with the element containing them. This is enough for the well
+    /// formed markup Drupal renders.
+    pub fn parse(html: &str) -> Document {
+        let mut elements: Vec<Element> = Vec::new();
+        // The indexes of the elements that are currently open.
+        let mut open: Vec<usize> = Vec::new();
+        for tag in TAG.captures_iter(html) {
+            let name = tag[2].to_lowercase();
+            if &tag[1] == "/" {
+                if let Some(position) = open.iter().rposition(|&index| elements[index].tag == name)
+                {
+                    open.truncate(position);
+                }
+                continue;
+            }
+            let attributes = common::ATTRIBUTE
+                .captures_iter(&tag[3])
+                .map(|attribute| (attribute[1].to_lowercase(), attribute[2].to_string()))
+                .collect();
+            elements.push(Element {
+                tag: name,
+                attributes,
+                parent: open.last().copied(),
+            });
+            let element = elements.last().unwrap();
+            if !tag[3].ends_with('/') && !VOID_ELEMENTS.contains(&element.tag.as_str()) {
+                open.push(elements.len() - 1);
+            }
+        }
+        Document { elements }
+    }
+
+    /// Returns true if the page contains an element matching the selector. Only compound
+    /// selectors (tag, #id, .class and [attribute] or [attribute="value"]) are supported,
+    /// optionally separated by spaces to match descendants.
+    pub fn matches(&self, selector: &str) -> bool {
+        let compounds: Vec<CompoundSelector> = selector
+            .split_whitespace()
+            .map(CompoundSelector::parse)
+            .collect();
+        let (last, ancestors) = match compounds.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        self.elements.iter().any(|element| {
+            if !last.matches(&element.tag, &element.attributes) {
+                return false;
+            }
+            // Match the remaining compounds against the element's ancestors, innermost
+            // first. Matching the nearest ancestor never rules out a match further out.
+            let mut parent = element.parent;
+            for compound in ancestors.iter().rev() {
+                loop {
+                    match parent {
+                        Some(index) => {
+                            let ancestor = &self.elements[index];
+                            parent = ancestor.parent;
+                            if compound.matches(&ancestor.tag, &ancestor.attributes) {
+                                break;
+                            }
+                        }
+                        None => return false,
+                    }
+                }
+            }
+            true
+        })
+    }
+}
+
+/// Returns true if the page contains an element matching the selector. When matching
+/// several selectors against the same page, parse it once with `Document::parse` instead.
+pub fn matches_selector(html: &str, selector: &str) -> bool {
+    Document::parse(html).matches(selector)
+}
+
+/// Load additional assertions from a file. Each section names the page type the
+/// assertions that follow apply to, or "all" for every page:
+///
+/// ```text
+/// [recipe]
+/// selector = .field--name-field-recipe-instruction
+/// forbidden = Deprecated function
+/// header = Content-Language
+/// forbidden_header = X-Drupal-Debug
+/// max_body_size = 200000
+/// ```
+pub fn load_assertions_file(path: &str) -> Result<(), String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read assertions file {}: {}", path, e))?;
+    let assertions = parse_assertions(&contents)?;
+    *CUSTOM_ASSERTIONS.write().unwrap() = assertions;
+    Ok(())
+}
+
+/// Parse the contents of an assertions file.
+pub fn parse_assertions(contents: &str) -> Result<Vec<(Option<PageType>, Assertion)>, String> {
+    let mut assertions = Vec::new();
+    let mut page_type = None;
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            let name = &line[1..line.len() - 1];
+            page_type = match name {
+                "all" => None,
+                _ => Some(
+                    name.parse::<PageType>()
+                        .map_err(|e| format!("line {}: {}", number + 1, e))?,
+                ),
+            };
+            continue;
+        }
+        let (key, value) = match line.find('=') {
+            Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+            None => return Err(format!("line {}: expected key = value", number + 1)),
+        };
+        let assertion = match key {
+            "selector" => Assertion::RequiredSelector(value.to_string()),
+            "forbidden" => Assertion::ForbiddenString(value.to_string()),
+            "header" => Assertion::RequiredHeader(value.to_lowercase()),
+            "forbidden_header" => Assertion::ForbiddenHeader(value.to_lowercase()),
+            "max_body_size" => Assertion::MaxBodySize(
+                value
+                    .parse()
+                    .map_err(|_| format!("line {}: invalid size: {}", number + 1, value))?,
+            ),
+            _ => return Err(format!("line {}: unknown assertion: {}", number + 1, key)),
+        };
+        assertions.push((page_type, assertion));
+    }
+
+    Ok(assertions)
+}
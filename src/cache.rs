@@ -0,0 +1,130 @@
+use std::fmt;
+
+use reqwest::header::HeaderMap;
+
+/// Whether a cache layer served a response.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CacheResult {
+    Hit,
+    Miss,
+    Uncacheable,
+}
+
+impl fmt::Display for CacheResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CacheResult::Hit => "HIT",
+            CacheResult::Miss => "MISS",
+            CacheResult::Uncacheable => "UNCACHEABLE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl CacheResult {
+    /// Parse the value of a Drupal cache header, ie `X-Drupal-Cache: HIT`.
+    fn parse(value: &str) -> Option<CacheResult> {
+        match value.trim().to_uppercase().as_str() {
+            "HIT" => Some(CacheResult::Hit),
+            "MISS" => Some(CacheResult::Miss),
+            "UNCACHEABLE" => Some(CacheResult::Uncacheable),
+            _ => None,
+        }
+    }
+}
+
+/// The cache layers that served a response, as reported in the response headers. A layer
+/// is None if the response doesn't include its header.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CacheStatus {
+    /// Drupal's Internal Page Cache, from `X-Drupal-Cache`.
+    pub page_cache: Option<CacheResult>,
+    /// Drupal's Dynamic Page Cache, from `X-Drupal-Dynamic-Cache`.
+    pub dynamic_cache: Option<CacheResult>,
+    /// Varnish or another reverse proxy, from `X-Varnish`, `X-Cache` or `Age`.
+    pub varnish: Option<CacheResult>,
+    /// How many seconds the response was cached by a proxy, from `Age`.
+    pub age: Option<u64>,
+}
+
+impl CacheStatus {
+    /// Determine the cache status from the response headers.
+    pub fn from_headers(headers: &HeaderMap) -> CacheStatus {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+
+        let age = header("age").and_then(|age| age.trim().parse::<u64>().ok());
+        // Varnish includes the request's ID in X-Varnish, followed by the ID of the request
+        // that populated the cache if it was a hit.
+        let varnish = if let Some(ids) = header("x-varnish") {
+            if ids.split_whitespace().count() > 1 {
+                Some(CacheResult::Hit)
+            } else {
+                Some(CacheResult::Miss)
+            }
+        } else if let Some(x_cache) = header("x-cache") {
+            if x_cache.to_uppercase().contains("HIT") {
+                Some(CacheResult::Hit)
+            } else {
+                Some(CacheResult::Miss)
+            }
+        } else if age.unwrap_or_default() > 0 {
+            Some(CacheResult::Hit)
+        } else {
+            None
+        };
+
+        CacheStatus {
+            page_cache: header("x-drupal-cache").and_then(|value| CacheResult::parse(&value)),
+            dynamic_cache: header("x-drupal-dynamic-cache")
+                .and_then(|value| CacheResult::parse(&value)),
+            varnish,
+            age,
+        }
+    }
+
+    /// The overall result: a HIT if any layer served the response, a MISS if any layer
+    /// could have, and otherwise UNCACHEABLE, including when there are no cache headers.
+    pub fn result(&self) -> CacheResult {
+        let layers = [self.varnish, self.page_cache, self.dynamic_cache];
+        if layers.contains(&Some(CacheResult::Hit)) {
+            CacheResult::Hit
+        } else if layers.contains(&Some(CacheResult::Miss)) {
+            CacheResult::Miss
+        } else {
+            CacheResult::Uncacheable
+        }
+    }
+}
+
+/// How requests defeat caches, to measure the performance of Drupal itself.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum CacheBust {
+    /// Pages can be served from cache.
+    #[default]
+    None,
+    /// A unique query string is added to every page, so no cache has a copy.
+    Query,
+    /// A session cookie is sent, which reverse proxies configured for Drupal pass to Drupal.
+    Session,
+    /// `Cache-Control: no-cache` is sent, so reverse proxies that honor it revalidate.
+    NoCache,
+}
+
+impl std::str::FromStr for CacheBust {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(CacheBust::None),
+            "query" => Ok(CacheBust::Query),
+            "session" => Ok(CacheBust::Session),
+            "no-cache" => Ok(CacheBust::NoCache),
+            _ => Err(format!("unknown cache busting mode: {}", s)),
+        }
+    }
+}
@@ -1,10 +1,41 @@
-use goose::goose::GooseResponse;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use goose::goose::{GooseRawRequest, GooseResponse};
 use goose::prelude::*;
 
+use lazy_static::lazy_static;
 use regex::Regex;
-use log::info;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use log::{info, warn};
 use rand::seq::SliceRandom;
-use rand::prelude::IteratorRandom;
+use rand::Rng;
+
+use crate::assertions;
+use crate::cache;
+use crate::cache::CacheBust;
+use crate::config;
+use crate::errors;
+use crate::fake;
+use crate::metrics;
+use crate::random;
+use crate::search;
+
+lazy_static! {
+    /// An attribute of an HTML element with a double quoted value, capturing its name and
+    /// value.
+    pub(crate) static ref ATTRIBUTE: Regex = Regex::new(r#"\s([\w:.-]+)="([^"]*)""#).unwrap();
+    /// The start tag of a link.
+    static ref LINK: Regex = Regex::new(r"<a\b[^>]*>").unwrap();
+    /// The content of the page's <title> element.
+    static ref TITLE: Regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+    /// A named or numeric HTML entity, capturing its name or number.
+    static ref ENTITY: Regex = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    /// Expected titles compiled as regular expressions for --title-match regex, so each
+    /// title is only compiled once.
+    static ref TITLE_PATTERNS: RwLock<HashMap<String, Regex>> = RwLock::new(HashMap::new());
+}
 
 /// The Umami website defines three content types.
 pub enum ContentType {
@@ -13,6 +44,73 @@ pub enum ContentType {
     Recipe,
 }
 
+/// The types of pages on the Umami website, used to select which assertions apply.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PageType {
+    Front,
+    ArticleListing,
+    RecipeListing,
+    Article,
+    BasicPage,
+    Recipe,
+    Term,
+    Contact,
+    Search,
+    NotFound,
+    /// The listing Views' AJAX pager loads, without the rest of the page.
+    ViewsAjax,
+}
+
+impl From<&ContentType> for PageType {
+    fn from(content_type: &ContentType) -> Self {
+        match content_type {
+            ContentType::Article => PageType::Article,
+            ContentType::BasicPage => PageType::BasicPage,
+            ContentType::Recipe => PageType::Recipe,
+        }
+    }
+}
+
+impl std::str::FromStr for PageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "front" => Ok(PageType::Front),
+            "article_listing" => Ok(PageType::ArticleListing),
+            "recipe_listing" => Ok(PageType::RecipeListing),
+            "article" => Ok(PageType::Article),
+            "basic_page" => Ok(PageType::BasicPage),
+            "recipe" => Ok(PageType::Recipe),
+            "term" => Ok(PageType::Term),
+            "contact" => Ok(PageType::Contact),
+            "search" => Ok(PageType::Search),
+            "not_found" => Ok(PageType::NotFound),
+            "views_ajax" => Ok(PageType::ViewsAjax),
+            _ => Err(format!("unknown page type: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for PageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            PageType::Front => "front",
+            PageType::ArticleListing => "article_listing",
+            PageType::RecipeListing => "recipe_listing",
+            PageType::Article => "article",
+            PageType::BasicPage => "basic_page",
+            PageType::Recipe => "recipe",
+            PageType::Term => "term",
+            PageType::Contact => "contact",
+            PageType::Search => "search",
+            PageType::NotFound => "not_found",
+            PageType::ViewsAjax => "views_ajax",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Details tracked about individual nodes used to run load test and validate
 /// that pages are being correctly loaded.
 pub struct Node<'a> {
@@ -32,7 +130,7 @@ pub struct Term<'a> {
 }
 
 /// Returns a vector of all nodes of a specified content type.
-pub fn get_nodes(content_type: &ContentType) -> Vec<Node> {
+pub fn get_nodes(content_type: &ContentType) -> Vec<Node<'_>> {
     let mut nodes: Vec<Node> = Vec::new();
 
     match content_type {
@@ -69,7 +167,7 @@ pub fn get_nodes(content_type: &ContentType) -> Vec<Node> {
                 nid: 14,
                 url_en: "/en/articles/lets-hear-it-for-carrots",
                 url_es: "/es/articles/un-aplauso-para-las-zanahorias",
-                title_en: "Let&#039;s hear it for carrots",
+                title_en: "Let's hear it for carrots",
                 title_es: "Un aplauso para las zanahorias",
             });
             nodes.push(Node {
@@ -176,229 +274,351 @@ pub fn get_nodes(content_type: &ContentType) -> Vec<Node> {
 
 /// Returns a vector of all taxonomy terms.
 pub fn get_terms() -> Vec<Term<'static>> {
-    let mut terms: Vec<Term> = Vec::new();
-
-    terms.push(Term {
-        url_en: "/en/recipe-category/accompaniments",
-        url_es: "/es/recipe-category/acompañamientos",
-        title_en: "Accompaniments",
-        title_es: "Acompañamientos",
-    });
-    terms.push(Term {
-        url_en: "/en/recipe-category/desserts",
-        url_es: "/es/recipe-category/postres",
-        title_en: "Desserts",
-        title_es: "Postres",
-    });
-    terms.push(Term {
-        url_en: "/en/recipe-category/main-courses",
-        url_es: "/es/recipe-category/platos-principales",
-        title_en: "Main courses",
-        title_es: "Platos principales",
-    });
-    terms.push(Term {
-        url_en: "/en/recipe-category/snacks",
-        url_es: "/es/recipe-category/tentempiés",
-        title_en: "Snacks",
-        title_es: "Tentempiés",
-    });
-    terms.push(Term {
-        url_en: "/en/recipe-category/starters",
-        url_es: "/es/recipe-category/entrantes",
-        title_en: "Starters",
-        title_es: "Entrantes",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/alcohol-free",
-        url_es: "/es/tags/sin-alcohol",
-        title_en: "Alcohol free",
-        title_es: "Sin alcohol",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/baked",
-        url_es: "/es/tags/horneado",
-        title_en: "Baked",
-        title_es: "Horneado",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/baking",
-        url_es: "/es/tags/cocción",
-        title_en: "Baking",
-        title_es: "Cocción",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/breakfast",
-        url_es: "/es/tags/desayuno",
-        title_en: "Breakfast",
-        title_es: "Desayuno",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/cake",
-        url_es: "/es/tags/pastel",
-        title_en: "Cake",
-        title_es: "Pastel",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/carrots",
-        url_es: "/es/tags/zanahorias",
-        title_en: "Carrots",
-        title_es: "Zanahorias",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/chocolate",
-        url_es: "/es/tags/chocolate",
-        title_en: "Chocolate",
-        title_es: "Chocolate",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/cocktail-party",
-        url_es: "/es/tags/fiesta-de-coctel",
-        title_en: "Cocktail party",
-        title_es: "Fiesta de coctel",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/dairy-free",
-        url_es: "/es/tags/sin-Lactosa",
-        title_en: "Dairy-free",
-        title_es: "Sin Lactosa",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/dessert",
-        url_es: "/es/tags/postre",
-        title_en: "Dessert",
-        title_es: "Postre",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/dinner-party",
-        url_es: "/es/tags/fiesta-de-cena",
-        title_en: "Dinner party",
-        title_es: "Fiesta de cena",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/drinks",
-        url_es: "/es/tags/bebidas",
-        title_en: "Drinks",
-        title_es: "Bebidas",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/egg",
-        url_es: "/es/tags/huevo",
-        title_en: "Egg",
-        title_es: "Huevo",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/grow-your-own",
-        url_es: "/es/tags/cultiva-los-tuyos",
-        title_en: "Grow your own",
-        title_es: "Cultiva los tuyos",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/healthy",
-        url_es: "/es/tags/saludable",
-        title_en: "Healthy",
-        title_es: "Saludable",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/herbs",
-        url_es: "/es/tags/hierbas",
-        title_en: "Herbs",
-        title_es: "Hierbas",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/learn-to-cook",
-        url_es: "/es/tags/aprender-a-cocinar",
-        title_en: "Learn to cook",
-        title_es: "Aprender a cocinar",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/mushrooms",
-        url_es: "/es/tags/champiñones",
-        title_en: "Mushrooms",
-        title_es: "Champiñones",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/oats",
-        url_es: "/es/tags/avena",
-        title_en: "Oats",
-        title_es: "Avena",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/party",
-        url_es: "/es/tags/fiesta",
-        title_en: "Party",
-        title_es: "Fiesta",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/pasta",
-        url_es: "/es/tags/pastas",
-        title_en: "Pasta",
-        title_es: "Pastas",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/pastry",
-        url_es: "/es/tags/repostería",
-        title_en: "Pastry",
-        title_es: "Repostería",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/seasonal",
-        url_es: "/es/tags/estacional",
-        title_en: "Seasonal",
-        title_es: "Estacional",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/shopping",
-        url_es: "/es/tags/compras",
-        title_en: "Shopping",
-        title_es: "Compras",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/soup",
-        url_es: "/es/tags/sopa",
-        title_en: "Soup",
-        title_es: "Sopa",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/supermarkets",
-        url_es: "/es/tags/supermercados",
-        title_en: "Supermarkets",
-        title_es: "Supermercados",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/vegan",
-        url_es: "/es/tags/vegano",
-        title_en: "Vegan",
-        title_es: "Vegano",
-    });
-    terms.push(Term {
-        url_en: "/en/tags/vegetarian",
-        url_es: "/es/tags/vegetariano",
-        title_en: "Vegetarian",
-        title_es: "Vegetariano",
-    });
-
-    terms
-}
-
-/// A valid title on this website starts with "<title>foo", where "foo" is the expected
-/// title text. Returns true if the expected title is set, otherwise returns false.
+    vec![
+        Term {
+            url_en: "/en/recipe-category/accompaniments",
+            url_es: "/es/recipe-category/acompañamientos",
+            title_en: "Accompaniments",
+            title_es: "Acompañamientos",
+        },
+        Term {
+            url_en: "/en/recipe-category/desserts",
+            url_es: "/es/recipe-category/postres",
+            title_en: "Desserts",
+            title_es: "Postres",
+        },
+        Term {
+            url_en: "/en/recipe-category/main-courses",
+            url_es: "/es/recipe-category/platos-principales",
+            title_en: "Main courses",
+            title_es: "Platos principales",
+        },
+        Term {
+            url_en: "/en/recipe-category/snacks",
+            url_es: "/es/recipe-category/tentempiés",
+            title_en: "Snacks",
+            title_es: "Tentempiés",
+        },
+        Term {
+            url_en: "/en/recipe-category/starters",
+            url_es: "/es/recipe-category/entrantes",
+            title_en: "Starters",
+            title_es: "Entrantes",
+        },
+        Term {
+            url_en: "/en/tags/alcohol-free",
+            url_es: "/es/tags/sin-alcohol",
+            title_en: "Alcohol free",
+            title_es: "Sin alcohol",
+        },
+        Term {
+            url_en: "/en/tags/baked",
+            url_es: "/es/tags/horneado",
+            title_en: "Baked",
+            title_es: "Horneado",
+        },
+        Term {
+            url_en: "/en/tags/baking",
+            url_es: "/es/tags/cocción",
+            title_en: "Baking",
+            title_es: "Cocción",
+        },
+        Term {
+            url_en: "/en/tags/breakfast",
+            url_es: "/es/tags/desayuno",
+            title_en: "Breakfast",
+            title_es: "Desayuno",
+        },
+        Term {
+            url_en: "/en/tags/cake",
+            url_es: "/es/tags/pastel",
+            title_en: "Cake",
+            title_es: "Pastel",
+        },
+        Term {
+            url_en: "/en/tags/carrots",
+            url_es: "/es/tags/zanahorias",
+            title_en: "Carrots",
+            title_es: "Zanahorias",
+        },
+        Term {
+            url_en: "/en/tags/chocolate",
+            url_es: "/es/tags/chocolate",
+            title_en: "Chocolate",
+            title_es: "Chocolate",
+        },
+        Term {
+            url_en: "/en/tags/cocktail-party",
+            url_es: "/es/tags/fiesta-de-coctel",
+            title_en: "Cocktail party",
+            title_es: "Fiesta de coctel",
+        },
+        Term {
+            url_en: "/en/tags/dairy-free",
+            url_es: "/es/tags/sin-Lactosa",
+            title_en: "Dairy-free",
+            title_es: "Sin Lactosa",
+        },
+        Term {
+            url_en: "/en/tags/dessert",
+            url_es: "/es/tags/postre",
+            title_en: "Dessert",
+            title_es: "Postre",
+        },
+        Term {
+            url_en: "/en/tags/dinner-party",
+            url_es: "/es/tags/fiesta-de-cena",
+            title_en: "Dinner party",
+            title_es: "Fiesta de cena",
+        },
+        Term {
+            url_en: "/en/tags/drinks",
+            url_es: "/es/tags/bebidas",
+            title_en: "Drinks",
+            title_es: "Bebidas",
+        },
+        Term {
+            url_en: "/en/tags/egg",
+            url_es: "/es/tags/huevo",
+            title_en: "Egg",
+            title_es: "Huevo",
+        },
+        Term {
+            url_en: "/en/tags/grow-your-own",
+            url_es: "/es/tags/cultiva-los-tuyos",
+            title_en: "Grow your own",
+            title_es: "Cultiva los tuyos",
+        },
+        Term {
+            url_en: "/en/tags/healthy",
+            url_es: "/es/tags/saludable",
+            title_en: "Healthy",
+            title_es: "Saludable",
+        },
+        Term {
+            url_en: "/en/tags/herbs",
+            url_es: "/es/tags/hierbas",
+            title_en: "Herbs",
+            title_es: "Hierbas",
+        },
+        Term {
+            url_en: "/en/tags/learn-to-cook",
+            url_es: "/es/tags/aprender-a-cocinar",
+            title_en: "Learn to cook",
+            title_es: "Aprender a cocinar",
+        },
+        Term {
+            url_en: "/en/tags/mushrooms",
+            url_es: "/es/tags/champiñones",
+            title_en: "Mushrooms",
+            title_es: "Champiñones",
+        },
+        Term {
+            url_en: "/en/tags/oats",
+            url_es: "/es/tags/avena",
+            title_en: "Oats",
+            title_es: "Avena",
+        },
+        Term {
+            url_en: "/en/tags/party",
+            url_es: "/es/tags/fiesta",
+            title_en: "Party",
+            title_es: "Fiesta",
+        },
+        Term {
+            url_en: "/en/tags/pasta",
+            url_es: "/es/tags/pastas",
+            title_en: "Pasta",
+            title_es: "Pastas",
+        },
+        Term {
+            url_en: "/en/tags/pastry",
+            url_es: "/es/tags/repostería",
+            title_en: "Pastry",
+            title_es: "Repostería",
+        },
+        Term {
+            url_en: "/en/tags/seasonal",
+            url_es: "/es/tags/estacional",
+            title_en: "Seasonal",
+            title_es: "Estacional",
+        },
+        Term {
+            url_en: "/en/tags/shopping",
+            url_es: "/es/tags/compras",
+            title_en: "Shopping",
+            title_es: "Compras",
+        },
+        Term {
+            url_en: "/en/tags/soup",
+            url_es: "/es/tags/sopa",
+            title_en: "Soup",
+            title_es: "Sopa",
+        },
+        Term {
+            url_en: "/en/tags/supermarkets",
+            url_es: "/es/tags/supermercados",
+            title_en: "Supermarkets",
+            title_es: "Supermercados",
+        },
+        Term {
+            url_en: "/en/tags/vegan",
+            url_es: "/es/tags/vegano",
+            title_en: "Vegan",
+            title_es: "Vegano",
+        },
+        Term {
+            url_en: "/en/tags/vegetarian",
+            url_es: "/es/tags/vegetariano",
+            title_en: "Vegetarian",
+            title_es: "Vegetariano",
+        },
+    ]
+}
+
+/// How the title of a page is compared to the expected title.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum TitleMatch {
+    /// The page title starts with the expected title, ignoring the site name that follows.
+    #[default]
+    Prefix,
+    /// The page title, without the trailing " | site name", equals the expected title.
+    Exact,
+    /// The expected title is a regular expression that matches the page title.
+    Regex,
+}
+
+impl std::str::FromStr for TitleMatch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prefix" => Ok(TitleMatch::Prefix),
+            "exact" => Ok(TitleMatch::Exact),
+            "regex" => Ok(TitleMatch::Regex),
+            _ => Err(format!("unknown title match mode: {}", s)),
+        }
+    }
+}
+
+/// Decodes the HTML entities Drupal uses when escaping text, such as "&#039;" and "&amp;".
+/// Unknown entities are left as is.
+pub fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |captures: &regex::Captures| {
+            let name = &captures[1];
+            let decoded = if let Some(hex) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok().and_then(std::char::from_u32)
+            } else if let Some(decimal) = name.strip_prefix('#') {
+                decimal.parse().ok().and_then(std::char::from_u32)
+            } else {
+                match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some('\u{a0}'),
+                    _ => None,
+                }
+            };
+            match decoded {
+                Some(character) => character.to_string(),
+                None => captures[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+/// Returns the plain text content of the page's <title> element, with HTML entities
+/// decoded and whitespace normalized.
+pub fn get_title(html: &str) -> Option<String> {
+    let captures = TITLE.captures(html)?;
+    let decoded = decode_entities(&captures[1]);
+    Some(decoded.split_whitespace().collect::<Vec<&str>>().join(" "))
+}
+
+/// Compare a page title to the expected plain text title using the given match mode.
+pub fn title_matches(page_title: &str, title: &str, title_match: TitleMatch) -> bool {
+    match title_match {
+        TitleMatch::Prefix => page_title.starts_with(title),
+        TitleMatch::Exact => {
+            // Drupal appends the site name to the page title: "title | site name".
+            let page_title = match page_title.rfind(" | ") {
+                Some(index) => &page_title[..index],
+                None => page_title,
+            };
+            page_title == title
+        }
+        TitleMatch::Regex => match title_pattern(title) {
+            Ok(regex) => regex.is_match(page_title),
+            Err(e) => {
+                warn!("title is not a valid regular expression: {}: {}", title, e);
+                false
+            }
+        },
+    }
+}
+
+/// Returns the expected title compiled as a regular expression, compiling it the first
+/// time it's used.
+fn title_pattern(title: &str) -> Result<Regex, regex::Error> {
+    if let Some(regex) = TITLE_PATTERNS.read().unwrap().get(title) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(title)?;
+    TITLE_PATTERNS
+        .write()
+        .unwrap()
+        .insert(title.to_string(), regex.clone());
+    Ok(regex)
+}
+
+/// Compile every title in the catalog as a regular expression, as --title-match regex
+/// compares them, returning an error naming the first title that isn't valid.
+pub fn compile_title_patterns() -> Result<(), String> {
+    let mut titles = Vec::new();
+    for content_type in &[
+        ContentType::Article,
+        ContentType::BasicPage,
+        ContentType::Recipe,
+    ] {
+        for node in get_nodes(content_type) {
+            titles.push(node.title_en);
+            titles.push(node.title_es);
+        }
+    }
+    for term in get_terms() {
+        titles.push(term.title_en);
+        titles.push(term.title_es);
+    }
+
+    for title in titles {
+        title_pattern(title)
+            .map_err(|e| format!("title is not a valid regular expression: {}: {}", title, e))?;
+    }
+    Ok(())
+}
+
+/// A valid title on this website is "foo | Umami Food Magazine", where "foo" is the
+/// expected plain text title. Returns true if the expected title is set, using the
+/// configured match mode, otherwise returns false.
 pub fn valid_title(html: &str, title: &str) -> bool {
-    if html.contains(&("<title>".to_string() + title)) {
-        return true
-    } else {
-        return false;
+    match get_title(html) {
+        Some(page_title) => title_matches(&page_title, title, config::get_title_match()),
+        None => false,
     }
 }
 
-/// Finds all local static elements on the page and loads them asynchronously.
-/// This default profile only has local assets, so we can use simple patterns.
-pub async fn load_static_elements(user: &GooseUser, html: &str) {
+/// Finds all local static elements on the page, returning their URLs in the order they
+/// appear. This default profile only has local assets, so we can use simple patterns.
+pub fn get_static_elements(html: &str) -> Vec<String> {
     // Use a regular expression to find all src=<foo> in the HTML, where foo
     // is the URL to image and js assets.
     // @TODO: parse HTML5 srcset= also
     let image = Regex::new(r#"src="(.*?)""#).unwrap();
     let mut urls = Vec::new();
-    for url in image.captures_iter(&html) {
+    for url in image.captures_iter(html) {
         if url[1].starts_with("/sites") || url[1].starts_with("/core") {
             urls.push(url[1].to_string());
         }
@@ -407,35 +627,251 @@ pub async fn load_static_elements(user: &GooseUser, html: &str) {
     // Use a regular expression to find all href=<foo> in the HTML, where foo
     // is the URL to css assets.
     let css = Regex::new(r#"href="(/sites/default/files/css/.*?)""#).unwrap();
-    for url in css.captures_iter(&html) {
+    for url in css.captures_iter(html) {
         urls.push(url[1].to_string());
     }
 
+    urls
+}
+
+/// Finds all local static elements on the page and loads them asynchronously.
+pub async fn load_static_elements(user: &GooseUser, html: &str) {
     // Load all the static assets found on the page.
-    for asset in &urls {
+    for asset in &get_static_elements(html) {
         let _ = user.get_named(asset, "static asset").await;
     }
+}
 
+/// Returns the path of a URL, removing the scheme and host from absolute URLs.
+pub fn url_path(url: &str) -> &str {
+    match url.find("://") {
+        Some(index) => match url[index + 3..].find('/') {
+            Some(start) => &url[index + 3 + start..],
+            None => "/",
+        },
+        None => url,
+    }
 }
 
-/// Validate the HTML response, confirming the expected title was returned, then load
-/// all static assets found on the page.
+/// Decode a percent-encoded string, such as a path containing Spanish characters.
+pub fn percent_decode(encoded: &str) -> String {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Percent-encode a string for use in a query string, such as a search query.
+pub fn percent_encode(decoded: &str) -> String {
+    let mut encoded = String::new();
+    for byte in decoded.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Normalize an IRI, such as a Spanish alias containing raw UTF-8, to a URI: non-ASCII
+/// characters, spaces, stray `%` and other characters not allowed in URLs are
+/// percent-encoded, and existing escapes are upper-cased as Drupal generates them. The
+/// encoded and unencoded forms of a path are normalized to the same URI, so they're named
+/// alike in the metrics.
+pub fn iri_to_uri(iri: &str) -> String {
+    let bytes = iri.as_bytes();
+    let mut uri = String::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        if byte == b'%'
+            && index + 2 < bytes.len()
+            && bytes[index + 1].is_ascii_hexdigit()
+            && bytes[index + 2].is_ascii_hexdigit()
+        {
+            uri.push('%');
+            uri.push(bytes[index + 1].to_ascii_uppercase() as char);
+            uri.push(bytes[index + 2].to_ascii_uppercase() as char);
+            index += 3;
+            continue;
+        }
+        if byte.is_ascii_graphic() && !b"\"%<>\\^`{|}".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+        index += 1;
+    }
+    uri
+}
+
+/// Returns the language of a page from its URL: Spanish pages are prefixed with /es.
+pub fn url_language(url: &str) -> &'static str {
+    let path = url_path(url);
+    if path == "/es" || path.starts_with("/es/") || path.starts_with("/es?") {
+        "es"
+    } else {
+        "en"
+    }
+}
+
+/// Record which caches served a page, and how long it took to load.
+pub fn record_cache_status(request: &GooseRawRequest, headers: &HeaderMap, page_type: &PageType) {
+    metrics::record_cache_status(
+        &request.name,
+        page_type,
+        url_language(&request.final_url),
+        &cache::CacheStatus::from_headers(headers),
+        request.response_time,
+    );
+}
+
+/// Load a page. If cache busting is enabled for the user's task set, the request is
+/// modified so it can't be served from cache.
+pub async fn get_page(user: &GooseUser, path: &str) -> Result<GooseResponse, GooseTaskError> {
+    get_named_page(user, path, None).await
+}
+
+/// Load a page, as get_page does. The request is given the name in the metrics, if set.
+pub async fn get_named_page(
+    user: &GooseUser,
+    path: &str,
+    name: Option<&str>,
+) -> Result<GooseResponse, GooseTaskError> {
+    get_named_uri(user, &iri_to_uri(path), name).await
+}
+
+/// Load a page from a path that's already a URI, as get_named_page does, without
+/// normalizing it first.
+async fn get_named_uri(
+    user: &GooseUser,
+    path: &str,
+    name: Option<&str>,
+) -> Result<GooseResponse, GooseTaskError> {
+    let task_set = crate::task_set_name(user.task_sets_index);
+    let mut rng = random::user_rng(user);
+    let request_builder = match config::get_configuration().cache_bust(task_set) {
+        CacheBust::None => user.goose_get(path).await?,
+        CacheBust::Query => {
+            let separator = if path.contains('?') { '&' } else { '?' };
+            user.goose_get(&format!("{}{}nocache={}", path, separator, rng.gen::<u64>()))
+                .await?
+        }
+        CacheBust::Session => user.goose_get(path).await?.header(
+            "Cookie",
+            format!("SESS{:032x}={:032x}", rng.gen::<u128>(), rng.gen::<u128>()),
+        ),
+        CacheBust::NoCache => user
+            .goose_get(path)
+            .await?
+            .header("Cache-Control", "no-cache")
+            .header("Pragma", "no-cache"),
+    };
+
+    // Unless named, Goose names requests by their path, so the query string doesn't affect
+    // the metrics.
+    user.goose_send(request_builder, name).await
+}
+
+/// The User-Agent Goose's own client identifies itself with. Goose doesn't expose it, so
+/// clients built to replace Goose's send this instead. It must follow the version of Goose
+/// in Cargo.lock, the client IP tests fail when it doesn't.
+pub const GOOSE_USER_AGENT: &str = "goose/0.10.2";
+
+/// Returns a synthetic client IP address for the user, unique to each user, ie 10.0.0.1
+/// for the first user.
+pub fn client_ip(index: usize) -> String {
+    let index = index + 1;
+    format!("10.{}.{}.{}", (index >> 16) & 255, (index >> 8) & 255, index & 255)
+}
+
+/// With --client-ip, send the user's synthetic client IP address with every request, in
+/// the configured header. Drupal behind a trusted reverse proxy then sees each user as a
+/// different client, so flood control limits each user separately. Runs once when each
+/// user starts, replacing the user's client.
+pub async fn set_client_ip(user: &GooseUser) -> GooseTaskResult {
+    let header = match config::get_configuration().client_ip_header {
+        Some(header) => header,
+        None => return Ok(()),
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_bytes(header.as_bytes()).unwrap(),
+        HeaderValue::from_str(&client_ip(user.weighted_users_index)).unwrap(),
+    );
+    // Goose's own client also stores cookies, which Drupal's forms depend on, and
+    // identifies itself as Goose.
+    let builder = reqwest::Client::builder()
+        .default_headers(headers)
+        .user_agent(GOOSE_USER_AGENT)
+        .cookie_store(true);
+    user.set_client_builder(builder).await?;
+
+    Ok(())
+}
+
+/// Validate a page, confirming Drupal didn't return an error, the expected title was
+/// returned and all assertions for the page type are met. Returns a description of the
+/// first problem found, assertion failures are also counted in the metrics.
+pub fn validate_page(
+    html: &str,
+    headers: &HeaderMap,
+    page_type: &PageType,
+    title: &str,
+) -> Result<(), String> {
+    // Drupal can return an error with the expected title, so check for errors first.
+    if let Some(error) = errors::detect_drupal_error(html) {
+        return Err(error.to_string());
+    }
+
+    // The listing loaded by Views' AJAX pager has no title.
+    if *page_type != PageType::ViewsAjax && !valid_title(html, title) {
+        return Err(format!("title not found: {}", title));
+    }
+
+    match assertions::evaluate(&assertions::get_assertions(page_type), html, headers) {
+        Ok(()) => Ok(()),
+        Err(failure) => {
+            metrics::record_assertion_failure(page_type, &failure);
+            Err(failure.to_string())
+        }
+    }
+}
+
+/// Validate the HTML response, confirming the expected title was returned and the page
+/// passes all assertions, then load all static assets found on the page.
 pub async fn validate_and_load_static_assets(
     user: &GooseUser,
     mut goose: GooseResponse,
+    page_type: PageType,
     title: &str,
 ) -> GooseTaskResult {
     match goose.response {
         Ok(response) => {
             // Copy the headers so we have them for logging if there are errors.
             let headers = &response.headers().clone();
+            record_cache_status(&goose.request, headers, &page_type);
             match response.text().await {
                 Ok(html) => {
-                    if !valid_title(&html, &title) {
+                    if let Err(e) = validate_page(&html, headers, &page_type, title) {
                         return user.set_failure(
-                            &format!("{}: title not found: {}", goose.request.url, title),
+                            &format!("{}: {}", goose.request.url, e),
                             &mut goose.request,
-                            Some(&headers),
+                            Some(headers),
                             Some(&html),
                         );
                     }
@@ -446,7 +882,7 @@ pub async fn validate_and_load_static_assets(
                     return user.set_failure(
                         &format!("{}: failed to parse page: {}", goose.request.url, e),
                         &mut goose.request,
-                        Some(&headers),
+                        Some(headers),
                         None,
                     );
                 }
@@ -468,10 +904,195 @@ pub async fn validate_and_load_static_assets(
 /// Use regular expression to get the value of a named form element.
 pub fn get_form_value(html: &str, name: &str) -> Option<String> {
     let re = Regex::new(&format!(r#"name="{}" value=['"](.*?)['"]"#, name)).unwrap();
-    match re.captures(&html) {
-        Some(value) => Some(value[1].to_string()),
-        None => None,
+    re.captures(html).map(|value| value[1].to_string())
+}
+
+/// Use regular expression to get the links of all search results on the page.
+pub fn get_search_results(html: &str) -> Vec<String> {
+    let re = Regex::new(r#"class="search-result__title">\s*<a href="([^"]*)""#).unwrap();
+    re.captures_iter(html)
+        .map(|result| decode_entities(&result[1]))
+        .collect()
+}
+
+/// Use regular expression to get the link to the next page of a pager.
+pub fn get_next_page(html: &str) -> Option<String> {
+    let re = Regex::new(r#"<a href="([^"]*)"[^>]*rel="next""#).unwrap();
+    re.captures(html).map(|next| decode_entities(&next[1]))
+}
+
+/// Use regular expression to count the rows of a Views listing.
+pub fn get_listing_rows(html: &str) -> usize {
+    let re = Regex::new(r#"class="(?:[^"]* )?views-row[ "]"#).unwrap();
+    re.find_iter(html).count()
+}
+
+/// Returns the HTML inserted by the commands of a Drupal AJAX response, such as the
+/// response to a Views AJAX pager, or None if it isn't a valid AJAX response.
+pub fn get_ajax_content(json: &str) -> Option<String> {
+    let commands: Vec<serde_json::Value> = serde_json::from_str(json).ok()?;
+    let content: Vec<&str> = commands
+        .iter()
+        .filter(|command| command["command"] == "insert")
+        .filter_map(|command| command["data"].as_str())
+        .collect();
+    if content.is_empty() {
+        None
+    } else {
+        Some(content.join("\n"))
+    }
+}
+
+/// Returns the URL Views' AJAX pager loads the listing on the page from, with the view's
+/// name and arguments from drupalSettings, or None if the page has no AJAX-enabled view.
+/// The pager's query string, ie `page=1`, is appended to load a page of the listing.
+pub fn get_views_ajax_url(html: &str) -> Option<String> {
+    let re = Regex::new(
+        r#"<script type="application/json" data-drupal-selector="drupal-settings-json">(.*?)</script>"#,
+    )
+    .unwrap();
+    let settings: serde_json::Value = serde_json::from_str(&re.captures(html)?[1]).ok()?;
+    let ajax_path = settings["views"]["ajax_path"].as_str()?;
+    let view = settings["views"]["ajaxViews"].as_object()?.values().next()?;
+    let mut url = format!("{}?_wrapper_format=drupal_ajax", ajax_path);
+    for argument in &[
+        "view_name",
+        "view_display_id",
+        "view_args",
+        "view_path",
+        "view_base_path",
+        "view_dom_id",
+        "pager_element",
+    ] {
+        let value = match &view[argument] {
+            serde_json::Value::String(value) => value.clone(),
+            serde_json::Value::Null => String::new(),
+            value => value.to_string(),
+        };
+        url.push_str(&format!("&{}={}", argument, percent_encode(&value)));
+    }
+    Some(url)
+}
+
+/// Find the node at a path in either language, returning its page type and the title
+/// expected on the page.
+pub fn get_node_by_path(path: &str) -> Option<(PageType, String)> {
+    let path = percent_decode(url_path(path));
+    for content_type in &[ContentType::Article, ContentType::BasicPage, ContentType::Recipe] {
+        for node in get_nodes(content_type) {
+            if path == node.url_en {
+                return Some((PageType::from(content_type), node.title_en.to_string()));
+            } else if path == node.url_es {
+                return Some((PageType::from(content_type), node.title_es.to_string()));
+            }
+        }
     }
+    None
+}
+
+/// Use regular expression to get the value of an attribute of an HTML element.
+fn get_attribute(element: &str, name: &str) -> Option<String> {
+    ATTRIBUTE
+        .captures_iter(element)
+        .find(|attribute| &attribute[1] == name)
+        .map(|attribute| decode_entities(&attribute[2]))
+}
+
+/// Returns the language and URL of each element matched by the regular expression.
+fn get_hreflang_links(html: &str, re: &Regex) -> Vec<(String, String)> {
+    re.find_iter(html)
+        .filter_map(|link| {
+            let language = get_attribute(link.as_str(), "hreflang")?;
+            let url = get_attribute(link.as_str(), "href")?;
+            Some((language, url))
+        })
+        .collect()
+}
+
+/// Use regular expression to get the href of every link on the page, in the order they
+/// appear.
+pub fn get_links(html: &str) -> Vec<String> {
+    LINK.find_iter(html)
+        .filter_map(|link| get_attribute(link.as_str(), "href"))
+        .collect()
+}
+
+/// Use regular expression to get the links in the language switcher, as pairs of language
+/// code and URL.
+pub fn get_language_links(html: &str) -> Vec<(String, String)> {
+    let re = Regex::new(r#"<a\b[^>]*\bclass="(?:[^"]* )?language-link[ "][^>]*>"#).unwrap();
+    get_hreflang_links(html, &re)
+}
+
+/// Use regular expression to get the URL of the `<link rel="canonical">` link.
+pub fn get_canonical_link(html: &str) -> Option<String> {
+    let re = Regex::new(r#"<link\b[^>]*\brel="canonical"[^>]*>"#).unwrap();
+    re.find(html).and_then(|link| get_attribute(link.as_str(), "href"))
+}
+
+/// Use regular expression to get the `<link rel="alternate" hreflang>` links to the
+/// translations of a page, as pairs of language code and URL.
+pub fn get_alternate_links(html: &str) -> Vec<(String, String)> {
+    let re = Regex::new(r#"<link\b[^>]*\brel="alternate"[^>]*>"#).unwrap();
+    get_hreflang_links(html, &re)
+}
+
+/// Use regular expressions to get the value of every field in the form with the given
+/// Drupal form ID, as a browser would submit them. Buttons are not included, nor are
+/// checkboxes and radio buttons that aren't checked.
+pub fn get_form_fields(html: &str, form_id: &str) -> Option<Vec<(String, String)>> {
+    let form_re = Regex::new(r"(?s)<form\b.*?</form>").unwrap();
+    let form = form_re
+        .find_iter(html)
+        .map(|form| form.as_str())
+        .find(|form| get_form_value(form, "form_id").as_deref() == Some(form_id))?;
+
+    let checked = Regex::new(r"\s(checked|selected)\b").unwrap();
+
+    let mut fields = Vec::new();
+    let input_re = Regex::new(r"<input\b[^>]*>").unwrap();
+    for input in input_re.find_iter(form).map(|input| input.as_str()) {
+        let name = match get_attribute(input, "name") {
+            Some(name) => name,
+            None => continue,
+        };
+        match get_attribute(input, "type").unwrap_or_default().as_str() {
+            "submit" | "button" | "image" | "reset" | "file" => continue,
+            "checkbox" | "radio" if !checked.is_match(input) => continue,
+            _ => (),
+        }
+        fields.push((name, get_attribute(input, "value").unwrap_or_default()));
+    }
+
+    let textarea_re = Regex::new(r"(?s)<textarea\b([^>]*)>(.*?)</textarea>").unwrap();
+    for textarea in textarea_re.captures_iter(form) {
+        if let Some(name) = get_attribute(&textarea[1], "name") {
+            fields.push((name, decode_entities(&textarea[2])));
+        }
+    }
+
+    // Submit the selected option, or the first option if none are selected.
+    let select_re = Regex::new(r"(?s)<select\b([^>]*)>(.*?)</select>").unwrap();
+    let option_re = Regex::new(r"<option\b[^>]*>").unwrap();
+    for select in select_re.captures_iter(form) {
+        let name = match get_attribute(&select[1], "name") {
+            Some(name) => name,
+            None => continue,
+        };
+        let options: Vec<&str> = option_re
+            .find_iter(&select[2])
+            .map(|option| option.as_str())
+            .collect();
+        let option = options
+            .iter()
+            .find(|option| checked.is_match(option))
+            .or_else(|| options.first());
+        if let Some(option) = option {
+            fields.push((name, get_attribute(option, "value").unwrap_or_default()));
+        }
+    }
+
+    Some(fields)
 }
 
 /// Anonymously load the contact form and POST feedback. The english boolean flag indicates
@@ -482,17 +1103,18 @@ pub async fn anonymous_contact_form(user: &GooseUser, english: bool) -> GooseTas
     } else {
         "/es/contact"
     };
-    let mut goose = user.get(contact_form_url).await?;
+    let mut goose = get_page(user, contact_form_url).await?;
 
     // We can't invoke common::validate_and_load_static_assets as while it's important
     // to validate the page and load static elements, we then need to extra form elements
     // from the HTML of the page. So we duplicate some of the logic, enhancing it for form
     // processing.
-    let contact_form;
+    let mut contact_form;
     match goose.response {
         Ok(response) => {
             // Copy the headers so we have them for logging if there are errors.
             let headers = &response.headers().clone();
+            record_cache_status(&goose.request, headers, &PageType::Contact);
             match response.text().await {
                 Ok(html) => {
                     // Be sure we've properly loaded the Contact form.
@@ -501,11 +1123,11 @@ pub async fn anonymous_contact_form(user: &GooseUser, english: bool) -> GooseTas
                     } else {
                         "Comentarios sobre el sitio web"
                     };
-                    if !valid_title(&html, title) {
+                    if let Err(e) = validate_page(&html, headers, &PageType::Contact, title) {
                         return user.set_failure(
-                            &format!("{}: title not found: {}", goose.request.url, title),
+                            &format!("{}: {}", goose.request.url, e),
                             &mut goose.request,
-                            Some(&headers),
+                            Some(headers),
                             Some(&html),
                         );
                     }
@@ -520,22 +1142,32 @@ pub async fn anonymous_contact_form(user: &GooseUser, english: bool) -> GooseTas
                         return user.set_failure(
                             &format!("{}: no form_build_id on page", goose.request.url),
                             &mut goose.request,
-                            Some(&headers),
+                            Some(headers),
                             Some(&html),
                         );
                     }
 
-                    // Build contact form parameters.
-                    // @TODO: dynamically generate the content.
-                    let params = [
-                        ("name", "@TODO: name"),
-                        ("mail", "nobody@example.com"),
-                        ("subject[0][value]", "@TODO: subject"),
-                        ("message[0][value]", "@TODO: message"),
-                        ("form_build_id", &form_build_id.unwrap()),
+                    // Build contact form parameters from a generated message.
+                    let message = fake::contact_message(
+                        &mut random::user_rng(user),
+                        english,
+                        &fake::next_id(user.weighted_users_index),
+                    );
+                    let form_build_id = form_build_id.unwrap();
+                    let mut params = vec![
+                        ("name", message.name.as_str()),
+                        ("mail", message.mail.as_str()),
+                        ("subject[0][value]", message.subject.as_str()),
+                        ("message[0][value]", message.message.as_str()),
+                        ("form_build_id", form_build_id.as_str()),
                         ("form_id", "contact_message_feedback_form"),
                         ("op", "Send+message"),
                     ];
+                    // Drupal only honors the "Send yourself a copy" checkbox for users
+                    // allowed to see it, by default only logged in users.
+                    if config::get_configuration().contact_copy {
+                        params.push(("copy", "1"));
+                    }
                     let request_builder = user.goose_post(contact_form_url).await?;
                     contact_form = user.goose_send(request_builder.form(&params), None).await?;
                 }
@@ -543,7 +1175,7 @@ pub async fn anonymous_contact_form(user: &GooseUser, english: bool) -> GooseTas
                     return user.set_failure(
                         &format!("{}: failed to parse page: {}", goose.request.url, e),
                         &mut goose.request,
-                        Some(&headers),
+                        Some(headers),
                         None,
                     );
                 }
@@ -560,23 +1192,40 @@ pub async fn anonymous_contact_form(user: &GooseUser, english: bool) -> GooseTas
     }
 
     // Drupal 9 throttles how many times an IP address can submit the contact form, so we
-    // need special handling. We check the response, and issue an info! level message if
-    // the form is throttled. This is a valid event to load test.
+    // need special handling. We check the response, and record whether the form was
+    // throttled in the metrics. This is a valid event to load test.
     match contact_form.response {
         Ok(response) => {
             // Copy the headers so we have them for logging if there are errors.
             let headers = &response.headers().clone();
             match response.text().await {
                 Ok(html) => {
+                    if let Some(error) = errors::detect_drupal_error(&html) {
+                        return user.set_failure(
+                            &format!("{}: {}", contact_form.request.url, error),
+                            &mut contact_form.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+
                     // If the contact form succeeded, we were redirected to the home page.
                     let error_text = if english {
                         "You cannot send more than"
                     } else {
                         "No le está permitido enviar más"
                     };
-                    if html.contains(error_text) {
-                        info!("post to contact form was throttled: {}", contact_form.request.url);
+                    let throttled = html.contains(error_text);
+                    if !throttled && !contact_form.request.redirected {
+                        // Otherwise Drupal displays the form again with validation errors.
+                        return user.set_failure(
+                            &format!("{}: contact form wasn't submitted", contact_form.request.url),
+                            &mut contact_form.request,
+                            Some(headers),
+                            Some(&html),
+                        );
                     }
+                    metrics::record_contact(if english { "en" } else { "es" }, throttled);
 
                     // Either way, a "real" user would still load all static elements on
                     // the returned page.
@@ -586,7 +1235,253 @@ pub async fn anonymous_contact_form(user: &GooseUser, english: bool) -> GooseTas
                     return user.set_failure(
                         &format!("{}: failed to parse page: {}", goose.request.url, e),
                         &mut goose.request,
-                        Some(&headers),
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// How many pages of a listing users load and how often, unless configured with
+/// --pager-depth.
+pub const DEFAULT_PAGER_DEPTH: &[(usize, usize)] = &[(2, 4), (3, 2), (5, 1)];
+
+/// Load a random listing (recipes, articles or a recipe category) in the specified
+/// language, then follow its pager to deeper pages, which are more expensive for Drupal to
+/// build. Deeper pages are loaded either by following the pager's links or as Views' AJAX
+/// pager does, from /views/ajax with the view's arguments, and each must list results.
+pub async fn listing_pager(user: &GooseUser, english: bool) -> GooseTaskResult {
+    let terms = get_terms();
+    let term = terms.choose(&mut random::user_rng(user)).unwrap();
+    let listings = if english {
+        [
+            ("/en/recipes/", PageType::RecipeListing, "Recipes"),
+            ("/en/articles/", PageType::ArticleListing, "Articles"),
+            (term.url_en, PageType::Term, term.title_en),
+        ]
+    } else {
+        [
+            ("/es/recipes/", PageType::RecipeListing, "Recetas"),
+            ("/es/articles/", PageType::ArticleListing, "Artículos"),
+            (term.url_es, PageType::Term, term.title_es),
+        ]
+    };
+    let (path, page_type, title) = listings.choose(&mut random::user_rng(user)).unwrap();
+    let path = &iri_to_uri(path);
+
+    // Randomly select how many pages to load, from the configured distribution.
+    let configuration = config::get_configuration();
+    let depths = if configuration.pager_depth.is_empty() {
+        DEFAULT_PAGER_DEPTH
+    } else {
+        configuration.pager_depth.as_slice()
+    };
+    let depth = depths
+        .choose_weighted(&mut random::user_rng(user), |(_, weight)| *weight)
+        .map(|(depth, _)| *depth)
+        .unwrap_or(1);
+
+    let mut url = path.to_string();
+    let mut views_ajax_url: Option<String> = None;
+    for page in 0..depth {
+        // Half of the deeper pages are loaded with the AJAX pager, if the listing has one.
+        let views_ajax = views_ajax_url
+            .as_ref()
+            .filter(|_| random::user_rng(user).gen_bool(0.5));
+        let ajax = views_ajax.is_some();
+        // Deeper pages are named by page, so their response times can be compared.
+        let mut goose = if page == 0 {
+            get_page(user, path).await?
+        } else if let Some(views_ajax_url) = views_ajax {
+            // The AJAX pager sends the query string of the pager's link along with the
+            // view's arguments.
+            let name = format!("{}?page={} ajax", path, page);
+            let ajax_url = match url.split_once('?') {
+                Some((_, query)) => format!("{}&{}", views_ajax_url, query),
+                None => views_ajax_url.clone(),
+            };
+            get_named_page(user, &ajax_url, Some(&name)).await?
+        } else {
+            let name = format!("{}?page={}", path, page);
+            get_named_page(user, &url, Some(&name)).await?
+        };
+
+        let next_page;
+        match goose.response {
+            Ok(response) => {
+                // Copy the headers so we have them for logging if there are errors.
+                let headers = &response.headers().clone();
+                match response.text().await {
+                    Ok(text) => {
+                        // The AJAX pager only returns the listing, as JSON commands.
+                        let html = if ajax {
+                            match get_ajax_content(&text) {
+                                Some(html) => html,
+                                None => {
+                                    return user.set_failure(
+                                        &format!("{}: invalid ajax response", goose.request.url),
+                                        &mut goose.request,
+                                        Some(headers),
+                                        Some(&text),
+                                    );
+                                }
+                            }
+                        } else {
+                            text
+                        };
+
+                        let page_type = if ajax {
+                            &PageType::ViewsAjax
+                        } else {
+                            page_type
+                        };
+                        record_cache_status(&goose.request, headers, page_type);
+                        if let Err(e) = validate_page(&html, headers, page_type, title) {
+                            return user.set_failure(
+                                &format!("{}: {}", goose.request.url, e),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            );
+                        }
+
+                        if get_listing_rows(&html) == 0 {
+                            return user.set_failure(
+                                &format!("{}: no results on page {}", goose.request.url, page),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            );
+                        }
+
+                        if !ajax {
+                            load_static_elements(user, &html).await;
+                            views_ajax_url = get_views_ajax_url(&html).or(views_ajax_url);
+                        }
+                        next_page = get_next_page(&html);
+                    }
+                    Err(e) => {
+                        return user.set_failure(
+                            &format!("{}: failed to parse page: {}", goose.request.url, e),
+                            &mut goose.request,
+                            Some(headers),
+                            None,
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                return user.set_failure(
+                    &format!("{}: no response from server: {}", goose.request.url, e),
+                    &mut goose.request,
+                    None,
+                    None,
+                );
+            }
+        }
+
+        // Drupal's pager links only contain the query string.
+        url = match next_page {
+            Some(next) if next.starts_with('?') => format!("{}{}", path, next),
+            Some(next) => url_path(&next).to_string(),
+            None => break,
+        };
+    }
+
+    Ok(())
+}
+
+/// Load a random node in the specified language, then switch to its translation with the
+/// language switcher, as users landing on the wrong language do. The language switcher
+/// and the hreflang alternate link must both point to the translation in the catalog, and
+/// the translation must have the translated title.
+pub async fn switch_language(user: &GooseUser, english: bool) -> GooseTaskResult {
+    // Randomly select a content type.
+    let content_types = [
+        ContentType::Article,
+        ContentType::BasicPage,
+        ContentType::Recipe,
+    ];
+    let content_type = content_types.choose(&mut random::user_rng(user)).unwrap();
+    // Then randomly select a node of this content type.
+    let nodes = get_nodes(content_type);
+    let node = nodes.choose(&mut random::user_rng(user)).unwrap();
+    let page_type = PageType::from(content_type);
+    let (path, title, language, translation_path, translation_title) = if english {
+        (node.url_en, node.title_en, "es", node.url_es, node.title_es)
+    } else {
+        (node.url_es, node.title_es, "en", node.url_en, node.title_en)
+    };
+
+    let mut goose = get_page(user, path).await?;
+    let switcher_link;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            record_cache_status(&goose.request, headers, &page_type);
+            match response.text().await {
+                Ok(html) => {
+                    if let Err(e) = validate_page(&html, headers, &page_type, title) {
+                        return user.set_failure(
+                            &format!("{}: {}", goose.request.url, e),
+                            &mut goose.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+                    load_static_elements(user, &html).await;
+
+                    // Links can be absolute and percent-encoded, the catalog's paths aren't.
+                    let mut translation_links = Vec::new();
+                    let links = [
+                        ("language switcher", get_language_links(&html)),
+                        ("hreflang alternate", get_alternate_links(&html)),
+                    ];
+                    for (link_type, links) in &links {
+                        let link = match links.iter().find(|(hreflang, _)| hreflang == language) {
+                            Some((_, link)) => link,
+                            None => {
+                                return user.set_failure(
+                                    &format!("{}: no {} link to {}", goose.request.url, link_type, language),
+                                    &mut goose.request,
+                                    Some(headers),
+                                    Some(&html),
+                                );
+                            }
+                        };
+                        if percent_decode(url_path(link)) != translation_path {
+                            return user.set_failure(
+                                &format!(
+                                    "{}: {} link to {} is {}, expected {}",
+                                    goose.request.url, link_type, language, link, translation_path
+                                ),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            );
+                        }
+                        translation_links.push(url_path(link).to_string());
+                    }
+                    switcher_link = translation_links.remove(0);
+                }
+                Err(e) => {
+                    return user.set_failure(
+                        &format!("{}: failed to parse page: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
                         None,
                     );
                 }
@@ -602,42 +1497,303 @@ pub async fn anonymous_contact_form(user: &GooseUser, english: bool) -> GooseTas
         }
     }
 
+    // Then follow the language switcher to the translation.
+    let goose = get_page(user, &switcher_link).await?;
+    validate_and_load_static_assets(user, goose, page_type, translation_title).await?;
+
     Ok(())
 }
 
+/// Load a random node by its system path, /node/{nid} or /es/node/{nid}, in the specified
+/// language. Drupal either redirects to the node's alias, or serves the node directly with
+/// a canonical link to the alias. Either way the alias must match the catalog. Redirected
+/// and directly served requests are recorded separately in the metrics.
+pub async fn node_by_nid(user: &GooseUser, english: bool) -> GooseTaskResult {
+    // Randomly select a content type.
+    let content_types = [
+        ContentType::Article,
+        ContentType::BasicPage,
+        ContentType::Recipe,
+    ];
+    let content_type = content_types.choose(&mut random::user_rng(user)).unwrap();
+    // Then randomly select a node of this content type.
+    let nodes = get_nodes(content_type);
+    let node = nodes.choose(&mut random::user_rng(user)).unwrap();
+    let page_type = PageType::from(content_type);
+    let (path, alias, title, language) = if english {
+        (format!("/node/{}", node.nid), node.url_en, node.title_en, "en")
+    } else {
+        (format!("/es/node/{}", node.nid), node.url_es, node.title_es, "es")
+    };
+
+    // Load the page by nid instead of by URL.
+    let mut goose = get_page(user, &path).await?;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            record_cache_status(&goose.request, headers, &page_type);
+            metrics::record_node_path(
+                language,
+                goose.request.redirected,
+                goose.request.response_time,
+            );
+            match response.text().await {
+                Ok(html) => {
+                    if let Err(e) = validate_page(&html, headers, &page_type, title) {
+                        return user.set_failure(
+                            &format!("{}: {}", goose.request.url, e),
+                            &mut goose.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+
+                    // Links can be absolute and percent-encoded, the catalog's paths aren't.
+                    if goose.request.redirected
+                        && percent_decode(url_path(&goose.request.final_url)) != alias
+                    {
+                        return user.set_failure(
+                            &format!(
+                                "{}: redirected to {}, expected {}",
+                                goose.request.url, goose.request.final_url, alias
+                            ),
+                            &mut goose.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+                    match get_canonical_link(&html) {
+                        Some(canonical) if percent_decode(url_path(&canonical)) == alias => (),
+                        canonical => {
+                            return user.set_failure(
+                                &format!(
+                                    "{}: canonical link is {}, expected {}",
+                                    goose.request.url,
+                                    canonical.unwrap_or_else(|| "missing".to_string()),
+                                    alias
+                                ),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            );
+                        }
+                    }
+
+                    load_static_elements(user, &html).await;
+                }
+                Err(e) => {
+                    return user.set_failure(
+                        &format!("{}: failed to parse page: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Load a random Spanish node or term whose alias contains non-ASCII characters, once
+/// with upper-case escapes, as Drupal generates them, and once with lower-case escapes, as
+/// some clients percent-encode. Drupal must find the alias from either form. Both requests
+/// are named by the normalized alias, so they're aggregated in the metrics, and loaded as
+/// get_page loads pages, so the task set's cache busting applies.
+pub async fn alias_escapes(user: &GooseUser) -> GooseTaskResult {
+    let mut aliases = Vec::new();
+    for content_type in &[ContentType::Article, ContentType::BasicPage, ContentType::Recipe] {
+        for node in get_nodes(content_type) {
+            aliases.push((PageType::from(content_type), node.url_es, node.title_es));
+        }
+    }
+    for term in get_terms() {
+        aliases.push((PageType::Term, term.url_es, term.title_es));
+    }
+    aliases.retain(|(_, path, _)| !path.is_ascii());
+    let (page_type, path, title) = *aliases.choose(&mut random::user_rng(user)).unwrap();
+
+    // The raw UTF-8 in the catalog's alias can't be sent as is, the HTTP client would
+    // percent-encode it with upper-case escapes.
+    let upper_case = iri_to_uri(path);
+    let re = Regex::new("%[0-9A-F]{2}").unwrap();
+    let lower_case =
+        re.replace_all(&upper_case, |escape: &regex::Captures| escape[0].to_lowercase());
+    for form in &[&*upper_case, &*lower_case] {
+        let goose = get_named_uri(user, form, Some(&upper_case)).await?;
+        validate_and_load_static_assets(user, goose, page_type, title).await?;
+    }
+
+    Ok(())
+}
+
+/// Paths that scanners commonly probe for, none of which exist on a Drupal site.
+pub const DEFAULT_SCANNER_PATHS: &[&str] = &[
+    "/wp-login.php",
+    "/wp-admin/",
+    "/xmlrpc.php",
+    "/.env",
+    "/.git/config",
+    "/phpmyadmin/",
+    "/admin.php",
+    "/backup.zip",
+    "/config.php.bak",
+    "/vendor/phpunit/phpunit/src/Util/PHP/eval-stdin.php",
+];
+
+/// Request a nonexistent path in the specified language, as scanners probing for
+/// vulnerable software do: usually one of the configured paths, otherwise a random path.
+/// Drupal must return a 404 page with the localized title. The web server blocking the
+/// request with a 403, as Drupal's .htaccess does for hidden files like /.env, is also
+/// accepted.
+pub async fn scanner(user: &GooseUser, english: bool) -> GooseTaskResult {
+    let configuration = config::get_configuration();
+    let random = random::user_rng(user).gen_bool(0.25);
+    let probe = if random {
+        let mut rng = random::user_rng(user);
+        let length = rng.gen_range(6, 13);
+        let word: String = (0..length)
+            .map(|_| rng.gen_range(b'a', b'z' + 1) as char)
+            .collect();
+        let extension = ["", ".php", ".html", ".zip"].choose(&mut rng).unwrap();
+        format!("/{}{}", word, extension)
+    } else if configuration.scanner_paths.is_empty() {
+        DEFAULT_SCANNER_PATHS
+            .choose(&mut random::user_rng(user))
+            .unwrap()
+            .to_string()
+    } else {
+        configuration
+            .scanner_paths
+            .choose(&mut random::user_rng(user))
+            .unwrap()
+            .clone()
+    };
+    let (path, title, language) = if english {
+        (probe, "Page not found", "en")
+    } else {
+        (format!("/es{}", probe), "Página no encontrada", "es")
+    };
+
+    // Random paths are named alike, so they're aggregated in the metrics.
+    let name = match (random, english) {
+        (true, true) => "/%random".to_string(),
+        (true, false) => "/es/%random".to_string(),
+        (false, _) => iri_to_uri(&path),
+    };
+    // Scanners don't defeat caches, so pages are requested directly rather than with
+    // get_named_page.
+    let mut goose = user.get_named(&iri_to_uri(&path), &name).await?;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            // Each request gets a single outcome: Goose counts every 404 and 403 as a
+            // failure, so either is set as a success once it's validated.
+            match response.status() {
+                // Blocked by the web server, as scanners expect from a hardened site.
+                StatusCode::FORBIDDEN => {
+                    info!("{}: blocked by the web server", goose.request.url);
+                    user.set_success(&mut goose.request)?;
+                }
+                StatusCode::NOT_FOUND => {
+                    record_cache_status(&goose.request, headers, &PageType::NotFound);
+                    metrics::record_not_found(language, random, goose.request.response_time);
+                    match response.text().await {
+                        Ok(html) => {
+                            match validate_page(&html, headers, &PageType::NotFound, title) {
+                                Ok(()) => user.set_success(&mut goose.request)?,
+                                Err(e) => {
+                                    return user.set_failure(
+                                        &format!("{}: {}", goose.request.url, e),
+                                        &mut goose.request,
+                                        Some(headers),
+                                        Some(&html),
+                                    );
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            return user.set_failure(
+                                &format!("{}: failed to parse page: {}", goose.request.url, e),
+                                &mut goose.request,
+                                Some(headers),
+                                None,
+                            );
+                        }
+                    }
+                }
+                status => {
+                    return user.set_failure(
+                        &format!("{}: status {}, expected 404", goose.request.url, status),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The most pages of search results a user loads.
+const MAX_SEARCH_PAGES: usize = 3;
+
 /// Load the search page and perform a search using one word from one of the node titles
-/// on the site.
+/// on the site. Some users then page through the results, before loading a random result.
 pub async fn search(user: &GooseUser, english: bool) -> GooseTaskResult {
     let search_form_url = if english {
         "/en/search/node"
     } else {
         "/es/search/node"
     };
-    let mut goose = user.get(search_form_url).await?;
+    let mut goose = get_page(user, search_form_url).await?;
 
     // We can't invoke common::validate_and_load_static_assets as while it's important
     // to validate the page and load static elements, we then need to extra form elements
     // from the HTML of the page. So we duplicate some of the logic, enhancing it for form
     // processing.
+    let search_title = if english {
+        "Search"
+    } else {
+        "Buscar"
+    };
     let search_word;
+    let query_type;
     let mut search_form;
     match goose.response {
         Ok(response) => {
             // Copy the headers so we have them for logging if there are errors.
             let headers = &response.headers().clone();
+            record_cache_status(&goose.request, headers, &PageType::Search);
             match response.text().await {
                 Ok(html) => {
                     // Be sure we've properly loaded the Search page.
-                    let title = if english {
-                        "Search"
-                    } else {
-                        "Buscar"
-                    };
-                    if !valid_title(&html, title) {
+                    if let Err(e) = validate_page(&html, headers, &PageType::Search, search_title) {
                         return user.set_failure(
-                            &format!("{}: title not found: {}", goose.request.url, title),
+                            &format!("{}: {}", goose.request.url, e),
                             &mut goose.request,
-                            Some(&headers),
+                            Some(headers),
                             Some(&html),
                         );
                     }
@@ -652,37 +1808,22 @@ pub async fn search(user: &GooseUser, english: bool) -> GooseTaskResult {
                         return user.set_failure(
                             &format!("{}: no form_build_id on page", goose.request.url),
                             &mut goose.request,
-                            Some(&headers),
+                            Some(headers),
                             Some(&html),
                         );
                     }
 
-                    // Randomly select a content type, favoring articles and recipes.
-                    let content_types = vec![
-                        ContentType::Article,
-                        ContentType::Article,
-                        ContentType::Article,
-                        ContentType::BasicPage,
-                        ContentType::Recipe,
-                        ContentType::Recipe,
-                        ContentType::Recipe,
-                    ];
-                    let content_type = content_types.choose(&mut rand::thread_rng());
-                    // Then randomly select a node of this content type.
-                    let nodes = get_nodes(&content_type.unwrap());
-                    let page = nodes.choose(&mut rand::thread_rng());
-                    // Finally randomly select a word from the title to use in our search.
-                    let title = if english {
-                        page.unwrap().title_en
-                    } else {
-                        page.unwrap().title_es
-                    };
-                    let words = title.split_whitespace();
-                    let word = words.choose(&mut rand::thread_rng());
-                    // Save a copy of the word so we can validate the results later.
-                    search_word = word.unwrap().to_string();
+                    // Generate a search query, from the configured mix of query types.
+                    let query = search::generate_query(
+                        &mut random::user_rng(user),
+                        english,
+                        &config::get_configuration().search_mix,
+                    );
+                    query_type = query.query_type;
+                    // Save a copy of the query so we can validate the results later.
+                    search_word = query.keys;
 
-                    // Build search form with random word from title.
+                    // Build search form with the generated query.
                     let params = [
                         ("keys", search_word.as_str()),
                         ("form_build_id", &form_build_id.unwrap()),
@@ -697,7 +1838,7 @@ pub async fn search(user: &GooseUser, english: bool) -> GooseTaskResult {
                         return user.set_failure(
                             &format!("{}: search didn't redirect", search_form.request.final_url),
                             &mut search_form.request,
-                            Some(&headers),
+                            Some(headers),
                             None,
                         );
                     }
@@ -706,7 +1847,7 @@ pub async fn search(user: &GooseUser, english: bool) -> GooseTaskResult {
                     return user.set_failure(
                         &format!("{}: failed to parse page: {}", goose.request.url, e),
                         &mut goose.request,
-                        Some(&headers),
+                        Some(headers),
                         None,
                     );
                 }
@@ -722,29 +1863,42 @@ pub async fn search(user: &GooseUser, english: bool) -> GooseTaskResult {
         }
     }
 
+    let mut results;
+    let mut next_page;
     match search_form.response {
         Ok(response) => {
             // Copy the headers so we have them for logging if there are errors.
             let headers = &response.headers().clone();
             match response.text().await {
                 Ok(html) => {
-                    if !html.contains(&search_word) {
+                    if let Some(error) = errors::detect_drupal_error(&html) {
+                        return user.set_failure(
+                            &format!("{}: {}", search_form.request.final_url, error),
+                            &mut search_form.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+
+                    // Search results escape the search term, ie "Let's" becomes "Let&#039;s".
+                    if !decode_entities(&html).contains(&search_word) {
                         return user.set_failure(
                             &format!("{}: search term ({}) not on page", goose.request.url, &search_word),
                             &mut goose.request,
-                            Some(&headers),
+                            Some(headers),
                             Some(&html),
                         );
                     }
                     load_static_elements(user, &html).await;
 
-                    // @TODO: get all href="" inside class="search-result__title" and load random node
+                    results = get_search_results(&html);
+                    next_page = get_next_page(&html);
                 },
                 Err(e) => {
                     return user.set_failure(
                         &format!("{}: failed to parse page: {}", goose.request.url, e),
                         &mut goose.request,
-                        Some(&headers),
+                        Some(headers),
                         None,
                     );
                 }
@@ -760,5 +1914,80 @@ pub async fn search(user: &GooseUser, english: bool) -> GooseTaskResult {
         }
     }
 
+    // Some users page through the results before clicking one.
+    let mut result_count = results.len();
+    let mut pages = 1;
+    while let Some(next) = next_page.take() {
+        if pages >= MAX_SEARCH_PAGES || !random::user_rng(user).gen_bool(0.5) {
+            break;
+        }
+        // Drupal's pager links only contain the query string.
+        let path = if next.starts_with('?') {
+            format!("{}{}", search_form_url, next)
+        } else {
+            url_path(&next).to_string()
+        };
+        let mut goose = get_page(user, &path).await?;
+        match goose.response {
+            Ok(response) => {
+                // Copy the headers so we have them for logging if there are errors.
+                let headers = &response.headers().clone();
+                match response.text().await {
+                    Ok(html) => {
+                        if let Err(e) = validate_page(&html, headers, &PageType::Search, search_title) {
+                            return user.set_failure(
+                                &format!("{}: {}", goose.request.url, e),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            );
+                        }
+                        load_static_elements(user, &html).await;
+
+                        results = get_search_results(&html);
+                        next_page = get_next_page(&html);
+                        result_count += results.len();
+                        pages += 1;
+                    }
+                    Err(e) => {
+                        return user.set_failure(
+                            &format!("{}: failed to parse page: {}", goose.request.url, e),
+                            &mut goose.request,
+                            Some(headers),
+                            None,
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                return user.set_failure(
+                    &format!("{}: no response from server: {}", goose.request.url, e),
+                    &mut goose.request,
+                    None,
+                    None,
+                );
+            }
+        }
+    }
+
+    // Then load a random result from the last page of results.
+    let result = results.choose(&mut random::user_rng(user));
+    metrics::record_search(
+        if english { "en" } else { "es" },
+        query_type,
+        result_count,
+        pages,
+        result.is_some(),
+    );
+    if let Some(result) = result {
+        match get_node_by_path(result) {
+            Some((page_type, title)) => {
+                let goose = get_page(user, url_path(result)).await?;
+                validate_and_load_static_assets(user, goose, page_type, &title).await?;
+            }
+            None => info!("search result not in catalog: {}", result),
+        }
+    }
+
     Ok(())
 }
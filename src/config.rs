@@ -0,0 +1,433 @@
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::admin::Invalidation;
+use crate::cache::CacheBust;
+use crate::common::{self, TitleMatch};
+use crate::jsonapi::Auth;
+use crate::search::{self, QueryType};
+
+lazy_static! {
+    /// The load test configuration, set once in main() before the load test starts.
+    static ref CONFIGURATION: RwLock<UmamiConfiguration> =
+        RwLock::new(UmamiConfiguration::default());
+}
+
+/// Options specific to the Umami load test. These are parsed out of the command line
+/// before the remaining arguments are passed on to Goose.
+#[derive(Clone, Debug, Default)]
+pub struct UmamiConfiguration {
+    /// Seed from which each user's random number generator is derived. If not set, a
+    /// random seed is generated so the run can still be replayed.
+    pub seed: Option<u64>,
+    /// How page titles are compared to the expected titles.
+    pub title_match: TitleMatch,
+    /// File containing additional page assertions.
+    pub assertions_file: Option<String>,
+    /// How each task set defeats caches. A task set of None applies to all task sets.
+    pub cache_bust: Vec<(Option<String>, CacheBust)>,
+    /// Whether to load every page once before users start.
+    pub warm_up: bool,
+    /// How the admin user invalidates caches. If not set, there is no admin user.
+    pub invalidate: Option<Invalidation>,
+    /// How many seconds the admin user waits between invalidating caches.
+    pub invalidate_interval: Option<usize>,
+    /// The name the admin user logs in with, defaults to "admin".
+    pub admin_user: Option<String>,
+    /// The password the admin user logs in with.
+    pub admin_password: Option<String>,
+    /// How often each type of search query is generated. If empty, the default mix is used.
+    pub search_mix: Vec<(QueryType, usize)>,
+    /// Whether contact form submissions ask for a copy to be sent to the sender.
+    pub contact_copy: bool,
+    /// The header each anonymous user sends its own client IP address in, ie
+    /// X-Forwarded-For. If not set, all users share the load test's IP address.
+    pub client_ip_header: Option<String>,
+    /// How many pages of a listing users load and how often. If empty, the default
+    /// distribution is used.
+    pub pager_depth: Vec<(usize, usize)>,
+    /// Whether to add a scanner user requesting nonexistent paths.
+    pub scanner: bool,
+    /// Nonexistent paths the scanner requests. If empty, the default paths are used.
+    pub scanner_paths: Vec<String>,
+    /// Whether to add a crawler user, crawling the site as a search engine does.
+    pub crawler: bool,
+    /// The User-Agent the crawler sends, defaults to crawler::DEFAULT_USER_AGENT.
+    pub crawler_user_agent: Option<String>,
+    /// Whether to add a JSON:API client, as used by a decoupled frontend.
+    pub jsonapi: bool,
+    /// How the JSON:API writer authenticates. If not set, there is no JSON:API writer.
+    pub jsonapi_write: Option<Auth>,
+    /// Whether to add a feed reader, polling RSS feeds.
+    pub feeds: bool,
+    /// How many seconds the feed reader waits between polls.
+    pub feed_interval: Option<usize>,
+    /// Whether to add an image style user, requesting image style derivatives.
+    pub image_styles: bool,
+    /// Whether the image style user periodically flushes the image styles on a page before
+    /// requesting their derivatives, as the admin user.
+    pub image_flush: bool,
+    /// How many seconds pass between image style flushes.
+    pub image_flush_interval: Option<usize>,
+}
+
+/// Describes a command line option understood by the Umami load test.
+struct UmamiOption {
+    name: &'static str,
+    value: Option<&'static str>,
+    help: &'static str,
+}
+
+/// All command line options understood by the Umami load test.
+const OPTIONS: &[UmamiOption] = &[
+    UmamiOption {
+        name: "--seed",
+        value: Some("SEED"),
+        help: "Seeds each user's random choices, replaying the same pages",
+    },
+    UmamiOption {
+        name: "--title-match",
+        value: Some("MODE"),
+        help: "Compares page titles by prefix (default), exact or regex",
+    },
+    UmamiOption {
+        name: "--assertions",
+        value: Some("FILE"),
+        help: "Loads additional page assertions from FILE",
+    },
+    UmamiOption {
+        name: "--cache-bust",
+        value: Some("MODE"),
+        help: "Defeats caches with query, session or no-cache, ie english:query,spanish:none",
+    },
+    UmamiOption {
+        name: "--warm-up",
+        value: None,
+        help: "Loads every page once before users start, with separate metrics",
+    },
+    UmamiOption {
+        name: "--invalidate",
+        value: Some("MODE"),
+        help: "Adds an admin user who clears caches, saves nodes, or both",
+    },
+    UmamiOption {
+        name: "--invalidate-interval",
+        value: Some("SECONDS"),
+        help: "Sets how often the admin user invalidates caches (default 60)",
+    },
+    UmamiOption {
+        name: "--admin-user",
+        value: Some("NAME"),
+        help: "Sets the name the admin user logs in with (default admin)",
+    },
+    UmamiOption {
+        name: "--admin-password",
+        value: Some("PASSWORD"),
+        help: "Sets the password the admin user logs in with",
+    },
+    UmamiOption {
+        name: "--search-mix",
+        value: Some("MIX"),
+        help: "Weights search query types, ie word=4,phrase=2,typo=1,no_results=1",
+    },
+    UmamiOption {
+        name: "--contact-copy",
+        value: None,
+        help: "Checks \"Send yourself a copy\" when submitting the contact form",
+    },
+    UmamiOption {
+        name: "--client-ip",
+        value: Some("HEADER"),
+        help: "Sends a unique IP per user in HEADER, ie X-Forwarded-For or X-Real-IP",
+    },
+    UmamiOption {
+        name: "--pager-depth",
+        value: Some("DEPTHS"),
+        help: "Weights how many listing pages users load, ie 2=4,3=2,5=1",
+    },
+    UmamiOption {
+        name: "--scanner",
+        value: None,
+        help: "Adds a scanner user requesting nonexistent paths, validating 404 pages",
+    },
+    UmamiOption {
+        name: "--scanner-paths",
+        value: Some("PATHS"),
+        help: "Sets the paths the scanner requests, ie /wp-login.php,/.env",
+    },
+    UmamiOption {
+        name: "--crawler",
+        value: None,
+        help: "Adds a crawler user following robots.txt, sitemap.xml and links",
+    },
+    UmamiOption {
+        name: "--crawler-user-agent",
+        value: Some("AGENT"),
+        help: "Sets the User-Agent the crawler sends (default UmamiCrawler/1.0)",
+    },
+    UmamiOption {
+        name: "--jsonapi",
+        value: None,
+        help: "Adds a JSON:API client reading recipes, articles and terms",
+    },
+    UmamiOption {
+        name: "--jsonapi-write",
+        value: Some("AUTH"),
+        help: "Adds a JSON:API writer creating nodes, with basic or cookie auth",
+    },
+    UmamiOption {
+        name: "--feeds",
+        value: None,
+        help: "Adds a feed reader polling the front page and term RSS feeds",
+    },
+    UmamiOption {
+        name: "--feed-interval",
+        value: Some("SECONDS"),
+        help: "Sets how often the feed reader polls a feed (default 60)",
+    },
+    UmamiOption {
+        name: "--image-styles",
+        value: None,
+        help: "Adds an image style user requesting derivatives, cold then cached",
+    },
+    UmamiOption {
+        name: "--image-flush",
+        value: None,
+        help: "Flushes image styles before their derivatives are requested",
+    },
+    UmamiOption {
+        name: "--image-flush-interval",
+        value: Some("SECONDS"),
+        help: "Sets how often image styles are flushed (default 60)",
+    },
+];
+
+impl UmamiConfiguration {
+    /// Apply a single command line option to the configuration.
+    fn set(&mut self, name: &str, value: Option<String>) -> Result<(), String> {
+        let value = value.unwrap_or_default();
+        match name {
+            "--seed" => self.seed = Some(parse_value(name, &value)?),
+            "--title-match" => {
+                self.title_match = parse_value(name, &value)?;
+                // Expected titles are then compared as patterns, so check they all compile.
+                if self.title_match == TitleMatch::Regex {
+                    common::compile_title_patterns()
+                        .map_err(|e| format!("invalid value for {}: {}", name, e))?
+                }
+            }
+            "--assertions" => self.assertions_file = Some(value),
+            "--cache-bust" => self.cache_bust = parse_cache_bust(&value)?,
+            "--warm-up" => self.warm_up = true,
+            "--invalidate" => self.invalidate = Some(parse_value(name, &value)?),
+            "--invalidate-interval" => {
+                self.invalidate_interval = Some(parse_interval(name, &value)?)
+            }
+            "--admin-user" => self.admin_user = Some(value),
+            "--admin-password" => self.admin_password = Some(value),
+            "--search-mix" => {
+                self.search_mix = search::parse_mix(&value)
+                    .map_err(|e| format!("invalid value for {}: {}", name, e))?
+            }
+            "--contact-copy" => self.contact_copy = true,
+            "--client-ip" => {
+                if reqwest::header::HeaderName::from_bytes(value.as_bytes()).is_err() {
+                    return Err(format!("invalid value for {}: {}", name, value));
+                }
+                self.client_ip_header = Some(value)
+            }
+            "--pager-depth" => self.pager_depth = parse_pager_depth(&value)?,
+            "--scanner" => self.scanner = true,
+            "--scanner-paths" => self.scanner_paths = parse_scanner_paths(&value)?,
+            "--crawler" => self.crawler = true,
+            "--jsonapi" => self.jsonapi = true,
+            "--jsonapi-write" => self.jsonapi_write = Some(parse_value(name, &value)?),
+            "--feeds" => self.feeds = true,
+            "--feed-interval" => self.feed_interval = Some(parse_interval(name, &value)?),
+            "--image-styles" => self.image_styles = true,
+            "--image-flush" => self.image_flush = true,
+            "--image-flush-interval" => {
+                self.image_flush_interval = Some(parse_value(name, &value)?)
+            }
+            "--crawler-user-agent" => {
+                if value.is_empty() || reqwest::header::HeaderValue::from_str(&value).is_err() {
+                    return Err(format!("invalid value for {}: {}", name, value));
+                }
+                self.crawler_user_agent = Some(value)
+            }
+            _ => return Err(format!("unrecognized option: {}", name)),
+        }
+
+        Ok(())
+    }
+
+    /// Returns how the named task set defeats caches. A mode set for the task set takes
+    /// precedence over a mode set for all task sets.
+    pub fn cache_bust(&self, task_set: &str) -> CacheBust {
+        self.cache_bust
+            .iter()
+            .find(|(name, _)| name.as_deref() == Some(task_set))
+            .or_else(|| self.cache_bust.iter().find(|(name, _)| name.is_none()))
+            .map(|(_, mode)| *mode)
+            .unwrap_or_default()
+    }
+}
+
+/// Parse an option value, returning a helpful error if it's not valid.
+fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", name, value))
+}
+
+/// Parse an interval in seconds. Goose can't wait less than a second between tasks, and
+/// without waiting a task set would request pages as fast as it can.
+fn parse_interval(name: &str, value: &str) -> Result<usize, String> {
+    match parse_value(name, value)? {
+        0 => Err(format!("invalid value for {}: must be at least 1", name)),
+        interval => Ok(interval),
+    }
+}
+
+/// Parse a comma separated list of cache busting modes, each optionally prefixed with the
+/// task set it applies to, ie "query" or "english:query,spanish:no-cache".
+fn parse_cache_bust(value: &str) -> Result<Vec<(Option<String>, CacheBust)>, String> {
+    value
+        .split(',')
+        .map(|setting| match setting.find(':') {
+            Some(index) => {
+                let task_set = &setting[..index];
+                if !crate::CACHE_BUST_TASK_SETS.contains(&task_set) {
+                    return Err(format!("invalid task set for --cache-bust: {}", task_set));
+                }
+                let mode = parse_value("--cache-bust", &setting[index + 1..])?;
+                Ok((Some(task_set.to_string()), mode))
+            }
+            None => Ok((None, parse_value("--cache-bust", setting)?)),
+        })
+        .collect()
+}
+
+/// Parse a comma separated list of how many pages of a listing to load and how often, ie
+/// "2=4,3=2,5=1" loads two pages four times as often as five pages.
+fn parse_pager_depth(value: &str) -> Result<Vec<(usize, usize)>, String> {
+    let depths = value
+        .split(',')
+        .map(|setting| {
+            let mut parts = setting.splitn(2, '=');
+            let depth = parse_value("--pager-depth", parts.next().unwrap_or_default())?;
+            let weight = parse_value("--pager-depth", parts.next().unwrap_or_default())?;
+            if depth == 0 {
+                return Err("invalid value for --pager-depth: depth must be at least 1".to_string());
+            }
+            Ok((depth, weight))
+        })
+        .collect::<Result<Vec<(usize, usize)>, String>>()?;
+    if depths.iter().all(|(_, weight)| *weight == 0) {
+        return Err("invalid value for --pager-depth: no weights".to_string());
+    }
+    Ok(depths)
+}
+
+/// Parse a comma separated list of paths for the scanner to request, ie
+/// "/wp-login.php,/.env". Each path must start with a slash.
+fn parse_scanner_paths(value: &str) -> Result<Vec<String>, String> {
+    value
+        .split(',')
+        .map(|path| {
+            if path.starts_with('/') {
+                Ok(path.to_string())
+            } else {
+                Err(format!("invalid value for --scanner-paths: {}", path))
+            }
+        })
+        .collect()
+}
+
+/// Split the command line into Umami options and Goose options. Returns the parsed Umami
+/// configuration and all arguments that should be passed on to Goose.
+pub fn parse_args<I: IntoIterator<Item = String>>(
+    args: I,
+) -> Result<(UmamiConfiguration, Vec<String>), String> {
+    let mut configuration = UmamiConfiguration::default();
+    let mut goose_args = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Options can be passed as either "--name value" or "--name=value".
+        let (name, inline_value) = match arg.find('=') {
+            Some(index) if arg.starts_with("--") => {
+                (arg[..index].to_string(), Some(arg[index + 1..].to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let option = match OPTIONS.iter().find(|option| option.name == name) {
+            Some(option) => option,
+            None => {
+                goose_args.push(arg);
+                continue;
+            }
+        };
+        let value = if option.value.is_some() {
+            match inline_value {
+                Some(value) => Some(value),
+                None => match args.next() {
+                    Some(value) => Some(value),
+                    None => return Err(format!("missing value for {}", name)),
+                },
+            }
+        } else {
+            inline_value
+        };
+        configuration.set(&name, value)?;
+    }
+
+    if configuration.invalidate.is_some() && configuration.admin_password.is_none() {
+        return Err("--invalidate requires --admin-password".to_string());
+    }
+    if configuration.jsonapi_write.is_some() && configuration.admin_password.is_none() {
+        return Err("--jsonapi-write requires --admin-password".to_string());
+    }
+    if configuration.image_flush && !configuration.image_styles {
+        return Err("--image-flush requires --image-styles".to_string());
+    }
+    if configuration.image_flush && configuration.admin_password.is_none() {
+        return Err("--image-flush requires --admin-password".to_string());
+    }
+    if configuration.image_flush_interval.is_some() && !configuration.image_flush {
+        return Err("--image-flush-interval requires --image-flush".to_string());
+    }
+
+    Ok((configuration, goose_args))
+}
+
+/// Returns help text describing all Umami options, displayed after the Goose options.
+pub fn usage() -> String {
+    let mut usage = "Umami options:".to_string();
+    for option in OPTIONS {
+        let flag = match option.value {
+            Some(value) => format!("{} {}", option.name, value),
+            None => option.name.to_string(),
+        };
+        usage.push_str(&format!("\n  {:<30} {}", flag, option.help));
+    }
+    usage
+}
+
+/// Store the configuration so it can be accessed from task functions.
+pub fn set_configuration(configuration: UmamiConfiguration) {
+    *CONFIGURATION.write().unwrap() = configuration;
+}
+
+/// Returns a copy of the current configuration. The copy can safely be held across
+/// await points, which a lock guard can not.
+pub fn get_configuration() -> UmamiConfiguration {
+    CONFIGURATION.read().unwrap().clone()
+}
+
+/// Returns how page titles are compared. Titles are validated on every page, so this
+/// avoids copying the whole configuration.
+pub fn get_title_match() -> TitleMatch {
+    CONFIGURATION.read().unwrap().title_match
+}
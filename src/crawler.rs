@@ -0,0 +1,464 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use goose::prelude::*;
+use lazy_static::lazy_static;
+use log::info;
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use crate::cache::{CacheResult, CacheStatus};
+use crate::common;
+use crate::config;
+use crate::errors;
+use crate::metrics;
+
+/// The User-Agent the crawler identifies itself with, unless configured with
+/// --crawler-user-agent.
+pub const DEFAULT_USER_AGENT: &str = "UmamiCrawler/1.0";
+
+/// The most pages crawled before the crawler starts over from robots.txt.
+const MAX_CRAWL_PAGES: usize = 1000;
+
+lazy_static! {
+    /// Each crawler's progress through the site, indexed by weighted_users_index.
+    static ref CRAWLS: Mutex<HashMap<usize, Crawl>> = Mutex::new(HashMap::new());
+}
+
+/// The rules robots.txt sets for the crawler.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Robots {
+    /// Allow and Disallow patterns, with whether they allow matching paths.
+    pub rules: Vec<(String, bool)>,
+    /// How many seconds to wait between requests.
+    pub crawl_delay: Option<f64>,
+    /// The sitemaps listed in robots.txt.
+    pub sitemaps: Vec<String>,
+}
+
+impl Robots {
+    /// Whether the crawler may request the path. The longest matching pattern applies,
+    /// with Allow winning a tie, and paths no pattern matches are allowed.
+    pub fn allowed(&self, path: &str) -> bool {
+        let mut allowed = true;
+        let mut longest = 0;
+        for (pattern, allow) in &self.rules {
+            if pattern_matches(pattern, path)
+                && (pattern.len() > longest || (pattern.len() == longest && *allow))
+            {
+                allowed = *allow;
+                longest = pattern.len();
+            }
+        }
+        allowed
+    }
+}
+
+/// Whether a path matches a robots.txt pattern: a path prefix, in which `*` matches any
+/// characters and a trailing `$` matches the end of the path.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let prefix = parts.next().unwrap_or_default();
+    if !path.starts_with(prefix) {
+        return false;
+    }
+    let mut rest = &path[prefix.len()..];
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        if anchored && index == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(start) => rest = &rest[start + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+/// A group of robots.txt rules, and the user agents it applies to.
+#[derive(Default)]
+struct RobotsGroup {
+    user_agents: Vec<String>,
+    rules: Vec<(String, bool)>,
+    crawl_delay: Option<f64>,
+}
+
+/// Parse robots.txt, returning the rules for the crawler's User-Agent. A group naming
+/// part of the User-Agent applies, otherwise the group for all user agents (`*`).
+pub fn parse_robots(robots_txt: &str, user_agent: &str) -> Robots {
+    let mut groups: Vec<RobotsGroup> = Vec::new();
+    let mut sitemaps = Vec::new();
+    // Consecutive User-agent lines share the group that follows them.
+    let mut in_user_agents = false;
+    for line in robots_txt.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let (key, value) = match line.find(':') {
+            Some(index) => (
+                line[..index].trim().to_lowercase(),
+                line[index + 1..].trim(),
+            ),
+            None => continue,
+        };
+        if key == "user-agent" {
+            if !in_user_agents {
+                groups.push(RobotsGroup::default());
+            }
+            if let Some(group) = groups.last_mut() {
+                group.user_agents.push(value.to_lowercase());
+            }
+            in_user_agents = true;
+            continue;
+        }
+        in_user_agents = false;
+        match (key.as_str(), groups.last_mut()) {
+            ("sitemap", _) => sitemaps.push(value.to_string()),
+            // An empty Disallow allows everything.
+            ("allow", Some(group)) | ("disallow", Some(group)) if !value.is_empty() => {
+                group.rules.push((value.to_string(), key == "allow"))
+            }
+            ("crawl-delay", Some(group)) => {
+                group.crawl_delay = value
+                    .parse()
+                    .ok()
+                    .filter(|delay: &f64| delay.is_finite() && *delay >= 0.0)
+            }
+            _ => (),
+        }
+    }
+
+    let user_agent = user_agent.to_lowercase();
+    let group = groups
+        .iter()
+        .find(|group| {
+            group
+                .user_agents
+                .iter()
+                .any(|name| name != "*" && user_agent.contains(name.as_str()))
+        })
+        .or_else(|| {
+            groups
+                .iter()
+                .find(|group| group.user_agents.iter().any(|name| name == "*"))
+        });
+    Robots {
+        rules: group.map(|group| group.rules.clone()).unwrap_or_default(),
+        crawl_delay: group.and_then(|group| group.crawl_delay),
+        sitemaps,
+    }
+}
+
+/// Use regular expression to get the URLs listed in a sitemap, or the sitemaps listed in
+/// a sitemap index.
+pub fn get_sitemap_urls(xml: &str) -> Vec<String> {
+    let re = Regex::new(r"<loc>\s*([^<]*?)\s*</loc>").unwrap();
+    re.captures_iter(xml)
+        .map(|url| common::decode_entities(&url[1]))
+        .collect()
+}
+
+/// Returns the path of a link if it's to a page on the site, which the crawler follows,
+/// ignoring any fragment. Links to other sites, static files and other schemes are
+/// ignored.
+pub fn crawlable_path(link: &str, page_url: &str) -> Option<String> {
+    let link = link.split('#').next().unwrap_or_default();
+    let path = if link.starts_with('/') && !link.starts_with("//") {
+        link
+    } else if link.starts_with("http://") || link.starts_with("https://") {
+        let host = |url: &str| {
+            let url = &url[url.find("://").map(|index| index + 3).unwrap_or(0)..];
+            url[..url.find('/').unwrap_or(url.len())].to_string()
+        };
+        if host(link) != host(page_url) {
+            return None;
+        }
+        common::url_path(link)
+    } else {
+        return None;
+    };
+    if path.starts_with("/core/") || path.starts_with("/sites/") {
+        return None;
+    }
+    Some(common::iri_to_uri(path))
+}
+
+/// A crawler's progress through the site.
+#[derive(Default)]
+struct Crawl {
+    robots: Robots,
+    /// Paths waiting to be crawled in the order they were discovered, with whether they
+    /// were discovered from a sitemap.
+    queue: VecDeque<(String, bool)>,
+    /// Every path discovered, so each is only crawled once.
+    discovered: HashSet<String>,
+    pages: usize,
+    /// When the crawler's latest request was made, or will be after its crawl delay.
+    last_request: Option<Instant>,
+}
+
+impl Crawl {
+    /// Queue a path to be crawled, unless it's already been discovered. Paths robots.txt
+    /// disallows are counted, but not queued.
+    fn discover(&mut self, path: String, sitemap: bool) {
+        if !self.discovered.insert(path.clone()) {
+            return;
+        }
+        if self.robots.allowed(&path) {
+            self.queue.push_back((path, sitemap));
+        } else {
+            metrics::record_crawl_disallowed(common::url_language(&path), sitemap);
+        }
+    }
+
+    /// Returns the next path to crawl, and how long to wait before requesting it to honor
+    /// the crawl delay. Returns None when the crawl is finished.
+    fn next(&mut self) -> Option<(String, bool, Duration)> {
+        if self.pages >= MAX_CRAWL_PAGES {
+            return None;
+        }
+        let (path, sitemap) = self.queue.pop_front()?;
+        self.pages += 1;
+
+        let now = Instant::now();
+        let delay = Duration::from_secs_f64(self.robots.crawl_delay.unwrap_or(0.0));
+        let request_at = match self.last_request {
+            Some(last_request) if last_request + delay > now => last_request + delay,
+            _ => now,
+        };
+        self.last_request = Some(request_at);
+        Some((path, sitemap, request_at - now))
+    }
+}
+
+/// Identify the crawler with the configured User-Agent. Like search engine crawlers, it
+/// doesn't keep cookies. Runs once when each crawler starts, replacing its client.
+pub async fn set_user_agent(user: &GooseUser) -> GooseTaskResult {
+    let user_agent = config::get_configuration()
+        .crawler_user_agent
+        .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string());
+    let builder = reqwest::Client::builder().user_agent(user_agent);
+    user.set_client_builder(builder).await?;
+
+    Ok(())
+}
+
+/// Load a file the site may not have, such as robots.txt. A missing file isn't a
+/// failure, and None is returned.
+async fn get_optional(
+    user: &GooseUser,
+    path: &str,
+    name: &str,
+) -> Result<Option<String>, GooseTaskError> {
+    let request_builder = user.goose_get(path).await?;
+    let mut goose = user.goose_send(request_builder, Some(name)).await?;
+    match goose.response {
+        Ok(response) => {
+            let status = response.status();
+            if status == StatusCode::NOT_FOUND {
+                // Goose counts every 404 as a failure, but here it's expected.
+                user.set_success(&mut goose.request)?;
+                return Ok(None);
+            } else if !status.is_success() {
+                return Ok(None);
+            }
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(text) => Ok(Some(text)),
+                Err(e) => user
+                    .set_failure(
+                        &format!("{}: failed to parse response: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    )
+                    .map(|_| None),
+            }
+        }
+        Err(e) => user
+            .set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            )
+            .map(|_| None),
+    }
+}
+
+/// Start a new crawl: fetch robots.txt, then queue the URLs of the sitemaps it lists, or
+/// of /sitemap.xml. Without a sitemap, the crawl starts from the front pages and follows
+/// links.
+async fn start_crawl(user: &GooseUser) -> GooseTaskResult {
+    let user_agent = config::get_configuration()
+        .crawler_user_agent
+        .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string());
+    let robots = match get_optional(user, "/robots.txt", "crawler /robots.txt").await? {
+        Some(robots_txt) => parse_robots(&robots_txt, &user_agent),
+        None => Robots::default(),
+    };
+
+    let mut sitemaps: VecDeque<String> = if robots.sitemaps.is_empty() {
+        vec!["/sitemap.xml".to_string()].into()
+    } else {
+        robots
+            .sitemaps
+            .iter()
+            .map(|sitemap| common::url_path(sitemap).to_string())
+            .collect()
+    };
+    let mut crawl = Crawl {
+        robots,
+        ..Default::default()
+    };
+    // A sitemap index lists more sitemaps rather than pages.
+    let mut loaded = HashSet::new();
+    while let Some(sitemap) = sitemaps.pop_front() {
+        if !loaded.insert(sitemap.clone()) {
+            continue;
+        }
+        if let Some(xml) = get_optional(user, &sitemap, "crawler /sitemap.xml").await? {
+            let index = xml.contains("<sitemapindex");
+            for url in get_sitemap_urls(&xml) {
+                if index {
+                    sitemaps.push_back(common::url_path(&url).to_string());
+                } else {
+                    crawl.discover(common::iri_to_uri(common::url_path(&url)), true);
+                }
+            }
+        }
+    }
+    if crawl.discovered.is_empty() {
+        crawl.discover("/".to_string(), false);
+        crawl.discover("/es".to_string(), false);
+    }
+    info!(
+        "crawler {} starting crawl of {} pages",
+        user.weighted_users_index,
+        crawl.queue.len()
+    );
+
+    CRAWLS
+        .lock()
+        .unwrap()
+        .insert(user.weighted_users_index, crawl);
+
+    Ok(())
+}
+
+/// Returns the next path for the user's crawl, if it hasn't finished.
+fn next_path(user: &GooseUser) -> Option<(String, bool, Duration)> {
+    CRAWLS
+        .lock()
+        .unwrap()
+        .get_mut(&user.weighted_users_index)
+        .and_then(|crawl| crawl.next())
+}
+
+/// Crawl the next page breadth-first, as a polite search engine crawler does: honoring
+/// robots.txt Disallow rules and Crawl-delay, and following links when there's no
+/// sitemap. When the crawl finishes, a new one starts. Requests are named by language,
+/// so crawler traffic is kept apart from other users' in the metrics.
+pub async fn crawl(user: &GooseUser) -> GooseTaskResult {
+    let next = match next_path(user) {
+        Some(next) => next,
+        None => {
+            start_crawl(user).await?;
+            match next_path(user) {
+                Some(next) => next,
+                None => return Ok(()),
+            }
+        }
+    };
+    let (path, sitemap, delay) = next;
+    if delay > Duration::from_secs(0) {
+        tokio::time::delay_for(delay).await;
+    }
+
+    let language = common::url_language(&path);
+    let name = format!("crawler /{}", language);
+    let request_builder = user.goose_get(&path).await?;
+    let mut goose = user.goose_send(request_builder, Some(&name)).await?;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            let cache_hit = CacheStatus::from_headers(headers).result() == CacheResult::Hit;
+            metrics::record_crawl(language, sitemap, cache_hit, goose.request.response_time);
+            if !is_html(headers) {
+                return Ok(());
+            }
+            match response.text().await {
+                Ok(html) => {
+                    // Nodes in the catalog must have the expected title, and all pages
+                    // must be free of Drupal errors.
+                    let valid = match common::get_node_by_path(&path) {
+                        Some((page_type, title)) => {
+                            common::validate_page(&html, headers, &page_type, &title)
+                        }
+                        None => match errors::detect_drupal_error(&html) {
+                            Some(error) => Err(error.to_string()),
+                            None => Ok(()),
+                        },
+                    };
+                    if let Err(e) = valid {
+                        return user.set_failure(
+                            &format!("{}: {}", goose.request.url, e),
+                            &mut goose.request,
+                            Some(headers),
+                            Some(&html),
+                        );
+                    }
+
+                    if !sitemap {
+                        let base = &goose.request.final_url;
+                        let links: Vec<String> = common::get_links(&html)
+                            .iter()
+                            .filter_map(|link| crawlable_path(link, base))
+                            .collect();
+                        if let Some(crawl) =
+                            CRAWLS.lock().unwrap().get_mut(&user.weighted_users_index)
+                        {
+                            for link in links {
+                                crawl.discover(link, false);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    return user.set_failure(
+                        &format!("{}: failed to parse page: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the response is an HTML page, which the crawler validates and follows links
+/// from.
+fn is_html(headers: &HeaderMap) -> bool {
+    headers
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("text/html"))
+        .unwrap_or(false)
+}
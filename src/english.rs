@@ -1,21 +1,27 @@
 use goose::prelude::*;
 
 use crate::common;
+use crate::common::PageType;
+use crate::feeds;
+use crate::images;
+use crate::jsonapi;
+use crate::random;
 
 use rand::seq::SliceRandom;
 
 /// Load the front page in English and all static assets found on the page.
 pub async fn front_page_en(user: &GooseUser) -> GooseTaskResult {
-    let goose = user.get("/").await?;
-    common::validate_and_load_static_assets(user, goose, "Home").await?;
+    let goose = common::get_page(user, "/").await?;
+    common::validate_and_load_static_assets(user, goose, PageType::Front, "Home").await?;
 
     Ok(())
 }
 
 /// Load recipe listing in English and all static assets found on the page.
 pub async fn recipe_listing_en(user: &GooseUser) -> GooseTaskResult {
-    let goose = user.get("/en/recipes/").await?;
-    common::validate_and_load_static_assets(user, goose, "Recipes").await?;
+    let goose = common::get_page(user, "/en/recipes/").await?;
+    common::validate_and_load_static_assets(user, goose, PageType::RecipeListing, "Recipes")
+        .await?;
 
     Ok(())
 }
@@ -23,17 +29,24 @@ pub async fn recipe_listing_en(user: &GooseUser) -> GooseTaskResult {
 /// Load a random recipe in English and all static assets found on the page.
 pub async fn recipe_en(user: &GooseUser) -> GooseTaskResult {
     let nodes = common::get_nodes(&common::ContentType::Recipe);
-    let recipe = nodes.choose(&mut rand::thread_rng());
-    let goose = user.get(recipe.unwrap().url_en).await?;
-    common::validate_and_load_static_assets(user, goose, recipe.unwrap().title_en).await?;
+    let recipe = nodes.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, recipe.unwrap().url_en).await?;
+    common::validate_and_load_static_assets(
+        user,
+        goose,
+        PageType::Recipe,
+        recipe.unwrap().title_en,
+    )
+    .await?;
 
     Ok(())
 }
 
 /// Load article listing in English and all static assets found on the page.
 pub async fn article_listing_en(user: &GooseUser) -> GooseTaskResult {
-    let goose = user.get("/en/articles/").await?;
-    common::validate_and_load_static_assets(user, goose, "Articles").await?;
+    let goose = common::get_page(user, "/en/articles/").await?;
+    common::validate_and_load_static_assets(user, goose, PageType::ArticleListing, "Articles")
+        .await?;
 
     Ok(())
 }
@@ -41,9 +54,15 @@ pub async fn article_listing_en(user: &GooseUser) -> GooseTaskResult {
 /// Load a random article in English and all static assets found on the page.
 pub async fn article_en(user: &GooseUser) -> GooseTaskResult {
     let nodes = common::get_nodes(&common::ContentType::Article);
-    let article = nodes.choose(&mut rand::thread_rng());
-    let goose = user.get(article.unwrap().url_en).await?;
-    common::validate_and_load_static_assets(user, goose, article.unwrap().title_en).await?;
+    let article = nodes.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, article.unwrap().url_en).await?;
+    common::validate_and_load_static_assets(
+        user,
+        goose,
+        PageType::Article,
+        article.unwrap().title_en,
+    )
+    .await?;
 
     Ok(())
 }
@@ -51,30 +70,22 @@ pub async fn article_en(user: &GooseUser) -> GooseTaskResult {
 /// Load a random basic page in English and all static assets found on the page.
 pub async fn basic_page_en(user: &GooseUser) -> GooseTaskResult {
     let nodes = common::get_nodes(&common::ContentType::BasicPage);
-    let page = nodes.choose(&mut rand::thread_rng());
-    let goose = user.get(page.unwrap().url_en).await?;
-    common::validate_and_load_static_assets(user, goose, page.unwrap().title_en).await?;
+    let page = nodes.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, page.unwrap().url_en).await?;
+    common::validate_and_load_static_assets(
+        user,
+        goose,
+        PageType::BasicPage,
+        page.unwrap().title_en,
+    )
+    .await?;
 
     Ok(())
 }
 
 /// Load a random node by nid in English and all static assets found on the page.
 pub async fn page_by_nid(user: &GooseUser) -> GooseTaskResult {
-    // Randomly select a content type.
-    let content_types = vec![
-        common::ContentType::Article,
-        common::ContentType::BasicPage,
-        common::ContentType::Recipe,
-    ];
-    let content_type = content_types.choose(&mut rand::thread_rng());
-    // Then randomly select a node of this content type.
-    let nodes = common::get_nodes(&content_type.unwrap());
-    let page = nodes.choose(&mut rand::thread_rng());
-    // Load the page by nid instead of by URL.
-    let goose = user
-        .get(&("/node/".to_string() + &page.unwrap().nid.to_string()))
-        .await?;
-    common::validate_and_load_static_assets(user, goose, page.unwrap().title_en).await?;
+    common::node_by_nid(user, true).await?;
 
     Ok(())
 }
@@ -96,9 +107,66 @@ pub async fn search_en(user: &GooseUser) -> GooseTaskResult {
 /// Load category listing by a random term in English and all static assets found on the page.
 pub async fn term_listing_en(user: &GooseUser) -> GooseTaskResult {
     let terms = common::get_terms();
-    let term = terms.choose(&mut rand::thread_rng());
-    let goose = user.get(term.unwrap().url_en).await?;
-    common::validate_and_load_static_assets(user, goose, term.unwrap().title_en).await?;
+    let term = terms.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, term.unwrap().url_en).await?;
+    common::validate_and_load_static_assets(user, goose, PageType::Term, term.unwrap().title_en)
+        .await?;
+
+    Ok(())
+}
+
+/// Load a random listing in English and follow its pager to deeper pages.
+pub async fn listing_pager_en(user: &GooseUser) -> GooseTaskResult {
+    common::listing_pager(user, true).await?;
+
+    Ok(())
+}
+
+/// Load a random node in English, then switch to Spanish with the language switcher.
+pub async fn switch_language_en(user: &GooseUser) -> GooseTaskResult {
+    common::switch_language(user, true).await?;
+
+    Ok(())
+}
+
+/// Request a nonexistent path in English, as scanners do, and validate the 404 page.
+pub async fn scanner_en(user: &GooseUser) -> GooseTaskResult {
+    common::scanner(user, true).await?;
+
+    Ok(())
+}
+
+/// Request a random JSON:API collection in English, as a decoupled frontend does.
+pub async fn jsonapi_collection_en(user: &GooseUser) -> GooseTaskResult {
+    jsonapi::collection(user, true).await?;
+
+    Ok(())
+}
+
+/// Look up a random node in English through JSON:API, filtering by its title.
+pub async fn jsonapi_filter_en(user: &GooseUser) -> GooseTaskResult {
+    jsonapi::filter_by_title(user, true).await?;
+
+    Ok(())
+}
+
+/// Create, change and delete a recipe or article in English through JSON:API.
+pub async fn jsonapi_write_en(user: &GooseUser) -> GooseTaskResult {
+    jsonapi::write(user, true).await?;
+
+    Ok(())
+}
+
+/// Poll an RSS feed in English, as a feed reader does.
+pub async fn feeds_en(user: &GooseUser) -> GooseTaskResult {
+    feeds::poll(user, true).await?;
+
+    Ok(())
+}
+
+/// Request the image style derivatives on a random English recipe or article.
+pub async fn images_en(user: &GooseUser) -> GooseTaskResult {
+    images::derivatives(user, true).await?;
 
     Ok(())
 }
@@ -0,0 +1,147 @@
+use std::fmt;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// Database exceptions, capturing their message: as formatted by Drupal's error
+    /// handler, ie `<em class="placeholder">PDOException</em>: SQLSTATE...`, or uncaught
+    /// before Drupal's error handler is set, ie `<b>Fatal error</b>: Uncaught PDOException`.
+    /// Only the markup is matched, so pages merely mentioning an exception aren't errors.
+    static ref DATABASE_ERRORS: Vec<Regex> = vec![
+        Regex::new(
+            r#"<em class="placeholder">[\w\\]*(?:PDOException|DatabaseException\w*)</em>:\s*([^<\n]*)"#
+        )
+        .unwrap(),
+        Regex::new(r"<b>Fatal error</b>:\s*Uncaught [\w\\]*PDOException:?\s*([^<\n]*)").unwrap(),
+    ];
+    /// PHP errors as formatted by Drupal's error handler, ie
+    /// `<em class="placeholder">Warning</em>: Undefined variable...`
+    static ref DRUPAL_PHP_ERROR: Regex = Regex::new(
+        r#"<em class="placeholder">(Notice|User notice|Strict warning|Warning|User warning|Deprecated function|User deprecated function|Recoverable fatal error|Error)</em>:([^<\n]*)"#
+    )
+    .unwrap();
+    /// PHP errors displayed by PHP itself, ie `<b>Warning</b>:  Undefined variable...`
+    static ref RAW_PHP_ERROR: Regex = Regex::new(
+        r"<b>(Notice|Warning|Deprecated|Fatal error|Parse error)</b>:([^<\n]*)"
+    )
+    .unwrap();
+}
+
+/// The categories of errors Drupal can return, even with a 200 status code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorCategory {
+    /// Drupal's "The website encountered an unexpected error" page.
+    ErrorPage,
+    /// Drupal's "Site under maintenance" page.
+    MaintenancePage,
+    /// A database error, such as a PDOException.
+    DatabaseError,
+    /// A fatal PHP error, or an uncaught exception.
+    PhpError,
+    /// A PHP warning.
+    PhpWarning,
+    /// A PHP notice.
+    PhpNotice,
+    /// A PHP deprecation notice.
+    PhpDeprecated,
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ErrorCategory::ErrorPage => "error page",
+            ErrorCategory::MaintenancePage => "maintenance page",
+            ErrorCategory::DatabaseError => "database error",
+            ErrorCategory::PhpError => "php error",
+            ErrorCategory::PhpWarning => "php warning",
+            ErrorCategory::PhpNotice => "php notice",
+            ErrorCategory::PhpDeprecated => "php deprecated",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An error detected in a page returned by Drupal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrupalError {
+    pub category: ErrorCategory,
+    pub detail: String,
+}
+
+impl fmt::Display for DrupalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.detail.is_empty() {
+            write!(f, "{}", self.category)
+        } else {
+            write!(f, "{}: {}", self.category, self.detail)
+        }
+    }
+}
+
+/// Map the PHP error level to a category.
+fn php_error_category(level: &str) -> ErrorCategory {
+    match level {
+        "Notice" | "User notice" | "Strict warning" => ErrorCategory::PhpNotice,
+        "Warning" | "User warning" => ErrorCategory::PhpWarning,
+        "Deprecated" | "Deprecated function" | "User deprecated function" => {
+            ErrorCategory::PhpDeprecated
+        }
+        _ => ErrorCategory::PhpError,
+    }
+}
+
+/// Trim the detail of an error for including in a failure message.
+fn detail(text: &str) -> String {
+    let detail = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    match detail.char_indices().nth(120) {
+        Some((index, _)) => format!("{}...", &detail[..index]),
+        None => detail,
+    }
+}
+
+/// Detect Drupal error pages, maintenance mode, PHP errors and database errors in a page.
+/// The most specific error is returned, so a database error displayed on Drupal's error
+/// page is reported as a database error.
+pub fn detect_drupal_error(html: &str) -> Option<DrupalError> {
+    if let Some(captures) = DATABASE_ERRORS
+        .iter()
+        .find_map(|regex| regex.captures(html))
+    {
+        return Some(DrupalError {
+            category: ErrorCategory::DatabaseError,
+            detail: detail(&captures[1]),
+        });
+    }
+
+    if let Some(captures) = DRUPAL_PHP_ERROR
+        .captures(html)
+        .or_else(|| RAW_PHP_ERROR.captures(html))
+    {
+        return Some(DrupalError {
+            category: php_error_category(&captures[1]),
+            detail: detail(&captures[2]),
+        });
+    }
+
+    if html.contains("<title>Site under maintenance")
+        || html.contains("<title>Sitio en mantenimiento")
+        || html.contains("is currently under maintenance")
+    {
+        return Some(DrupalError {
+            category: ErrorCategory::MaintenancePage,
+            detail: String::new(),
+        });
+    }
+
+    if html.contains("The website encountered an unexpected error")
+        || html.contains("El sitio web encontró un error inesperado")
+    {
+        return Some(DrupalError {
+            category: ErrorCategory::ErrorPage,
+            detail: String::new(),
+        });
+    }
+
+    None
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::search;
+
+lazy_static! {
+    /// How many email addresses each GooseUser has generated, indexed by
+    /// weighted_users_index.
+    static ref MAIL_COUNTS: Mutex<HashMap<usize, usize>> = Mutex::new(HashMap::new());
+}
+
+const FIRST_NAMES_EN: &[&str] = &[
+    "Olivia",
+    "Liam",
+    "Emma",
+    "Noah",
+    "Amelia",
+    "Oliver",
+    "Ava",
+    "Elijah",
+    "Sophia",
+    "James",
+    "Isabella",
+    "William",
+    "Mia",
+    "Benjamin",
+    "Charlotte",
+    "Lucas",
+];
+const LAST_NAMES_EN: &[&str] = &[
+    "Smith", "Johnson", "Williams", "Brown", "Jones", "Miller", "Davis", "Wilson", "Anderson",
+    "Taylor", "Thomas", "Moore", "Jackson", "Martin", "Lee", "Thompson",
+];
+const FIRST_NAMES_ES: &[&str] = &[
+    "María",
+    "José",
+    "Lucía",
+    "Martín",
+    "Sofía",
+    "Hugo",
+    "Carmen",
+    "Alejandro",
+    "Paula",
+    "Daniel",
+    "Valentina",
+    "Pablo",
+    "Ana",
+    "Álvaro",
+    "Inés",
+    "Javier",
+];
+const LAST_NAMES_ES: &[&str] = &[
+    "García",
+    "Rodríguez",
+    "González",
+    "Fernández",
+    "López",
+    "Martínez",
+    "Sánchez",
+    "Pérez",
+    "Gómez",
+    "Martín",
+    "Jiménez",
+    "Ruiz",
+    "Hernández",
+    "Díaz",
+    "Moreno",
+    "Muñoz",
+];
+
+/// Subjects, where {} is replaced with the title of a random node.
+const SUBJECTS_EN: &[&str] = &[
+    "Question about {}",
+    "I loved {}",
+    "Problem with {}",
+    "Suggestion for {}",
+    "Feedback on your website",
+    "Recipe request",
+];
+const SUBJECTS_ES: &[&str] = &[
+    "Pregunta sobre {}",
+    "Me encantó {}",
+    "Problema con {}",
+    "Sugerencia para {}",
+    "Comentarios sobre su sitio web",
+    "Petición de receta",
+];
+
+const SENTENCES_EN: &[&str] = &[
+    "I tried this at home last weekend and my family loved it.",
+    "Could you tell me if the ingredients can be replaced with gluten free alternatives?",
+    "The page took a long time to load on my phone.",
+    "I think there's a mistake in the quantities listed.",
+    "Would you consider publishing more vegetarian recipes?",
+    "Thank you for all the great content, I visit your site every week.",
+    "How long can this be kept in the fridge?",
+    "I couldn't find the print button anywhere on the page.",
+    "My oven runs hot, so I had to lower the temperature a little.",
+    "Please let me know when the next issue of the magazine is available.",
+];
+const SENTENCES_ES: &[&str] = &[
+    "Lo preparé en casa el fin de semana pasado y a mi familia le encantó.",
+    "¿Podrían decirme si los ingredientes se pueden sustituir por alternativas sin gluten?",
+    "La página tardó mucho en cargar en mi teléfono.",
+    "Creo que hay un error en las cantidades indicadas.",
+    "¿Considerarían publicar más recetas vegetarianas?",
+    "Gracias por todo el contenido, visito su sitio cada semana.",
+    "¿Cuánto tiempo se puede conservar en la nevera?",
+    "No encontré el botón de imprimir en ninguna parte de la página.",
+    "Mi horno calienta mucho, así que tuve que bajar un poco la temperatura.",
+    "Avísenme cuando salga el próximo número de la revista, por favor.",
+];
+
+/// Titles of generated nodes, where {} is replaced with the title of a random node.
+const TITLES_EN: &[&str] = &[
+    "{} with a twist",
+    "{}, the easy way",
+    "{} for two",
+    "Weeknight special: {}",
+];
+const TITLES_ES: &[&str] = &[
+    "{} con un toque especial",
+    "{}, la forma fácil",
+    "{} para dos",
+    "Especial entre semana: {}",
+];
+
+/// Summaries of generated nodes, where {} is replaced with the title of a random node.
+const SUMMARIES_EN: &[&str] = &[
+    "A new take on {}.",
+    "Everything you need to know about {}.",
+    "Our readers' favorite version of {}.",
+];
+const SUMMARIES_ES: &[&str] = &[
+    "Una nueva versión de {}.",
+    "Todo lo que necesita saber sobre {}.",
+    "La versión favorita de nuestros lectores de {}.",
+];
+
+const STEPS_EN: &[&str] = &[
+    "Preheat the oven to 180°C.",
+    "Chop the onions and garlic finely.",
+    "Whisk the eggs with a pinch of salt.",
+    "Simmer gently for twenty minutes, stirring occasionally.",
+    "Fry the vegetables in olive oil until golden.",
+    "Season to taste with salt and pepper.",
+    "Leave to rest for ten minutes before serving.",
+    "Serve warm with a fresh green salad.",
+];
+const STEPS_ES: &[&str] = &[
+    "Precaliente el horno a 180 °C.",
+    "Pique finamente la cebolla y el ajo.",
+    "Bata los huevos con una pizca de sal.",
+    "Cueza a fuego lento durante veinte minutos, removiendo de vez en cuando.",
+    "Fría las verduras en aceite de oliva hasta que estén doradas.",
+    "Sazone al gusto con sal y pimienta.",
+    "Deje reposar diez minutos antes de servir.",
+    "Sirva caliente con una ensalada verde.",
+];
+
+const DIFFICULTIES: &[&str] = &["easy", "medium", "hard"];
+
+/// A generated submission of the contact form.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContactMessage {
+    pub name: String,
+    pub mail: String,
+    pub subject: String,
+    pub message: String,
+}
+
+/// A generated recipe or article, created through JSON:API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeDraft {
+    pub title: String,
+    pub summary: String,
+    /// The body of an article or the instructions of a recipe, as HTML paragraphs.
+    pub body: String,
+    /// The difficulty of a recipe: easy, medium or hard.
+    pub difficulty: &'static str,
+    /// Minutes needed to prepare a recipe.
+    pub preparation_time: usize,
+    /// Minutes needed to cook a recipe.
+    pub cooking_time: usize,
+    pub servings: usize,
+}
+
+/// Returns an id that the user with the given weighted_users_index hasn't used in an email
+/// address yet, ie "3.1" for the first address of the user 3. Ids are unique across users,
+/// and the same in every run, so the addresses are replayed with --seed.
+pub fn next_id(index: usize) -> String {
+    let mut counts = MAIL_COUNTS.lock().unwrap();
+    let count = counts.entry(index).or_insert(0);
+    *count += 1;
+    format!("{}.{}", index, count)
+}
+
+/// Convert a name into letters that are valid in the local part of an email address, ie
+/// "Muñoz" becomes "munoz".
+fn email_part(name: &str) -> String {
+    search::remove_accents(name)
+        .to_lowercase()
+        .chars()
+        .map(|c| if c == 'ñ' { 'n' } else { c })
+        .filter(|c| c.is_ascii_alphabetic())
+        .collect()
+}
+
+/// Generate one to three paragraphs, each of one to four of the sentences.
+fn paragraphs<R: Rng + ?Sized>(rng: &mut R, sentences: &[&str]) -> Vec<String> {
+    (0..rng.gen_range(1, 4))
+        .map(|_| {
+            (0..rng.gen_range(1, 5))
+                .map(|_| *sentences.choose(rng).unwrap())
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// Generate a message of one to three paragraphs, each of one to four sentences.
+fn message<R: Rng + ?Sized>(rng: &mut R, english: bool) -> String {
+    let sentences = if english { SENTENCES_EN } else { SENTENCES_ES };
+    paragraphs(rng, sentences).join("\n\n")
+}
+
+/// Generate a contact form submission in the given language. The id makes the email
+/// address unique, see next_id().
+pub fn contact_message<R: Rng + ?Sized>(rng: &mut R, english: bool, id: &str) -> ContactMessage {
+    let (first_names, last_names, subjects) = if english {
+        (FIRST_NAMES_EN, LAST_NAMES_EN, SUBJECTS_EN)
+    } else {
+        (FIRST_NAMES_ES, LAST_NAMES_ES, SUBJECTS_ES)
+    };
+    let first_name = first_names.choose(rng).unwrap();
+    let last_name = last_names.choose(rng).unwrap();
+    let subject = subjects
+        .choose(rng)
+        .unwrap()
+        .replace("{}", &search::random_title(rng, english));
+
+    ContactMessage {
+        name: format!("{} {}", first_name, last_name),
+        mail: format!(
+            "{}.{}.{}@example.com",
+            email_part(first_name),
+            email_part(last_name),
+            id
+        ),
+        subject,
+        message: message(rng, english),
+    }
+}
+
+/// Generate a recipe or article in the given language, named after a random node.
+pub fn node_draft<R: Rng + ?Sized>(rng: &mut R, english: bool) -> NodeDraft {
+    let (titles, summaries, steps) = if english {
+        (TITLES_EN, SUMMARIES_EN, STEPS_EN)
+    } else {
+        (TITLES_ES, SUMMARIES_ES, STEPS_ES)
+    };
+    let title = search::random_title(rng, english);
+    let body = paragraphs(rng, steps)
+        .iter()
+        .map(|paragraph| format!("<p>{}</p>", paragraph))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    NodeDraft {
+        title: titles.choose(rng).unwrap().replace("{}", &title),
+        summary: summaries.choose(rng).unwrap().replace("{}", &title),
+        body,
+        difficulty: DIFFICULTIES.choose(rng).unwrap(),
+        preparation_time: rng.gen_range(1, 13) * 5,
+        cooking_time: rng.gen_range(0, 25) * 5,
+        servings: rng.gen_range(1, 9),
+    }
+}
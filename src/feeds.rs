@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use goose::prelude::*;
+use lazy_static::lazy_static;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use regex::Regex;
+use reqwest::header::{HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+
+use crate::common;
+use crate::errors;
+use crate::metrics;
+use crate::random;
+
+lazy_static! {
+    /// Each feed reader's subscriptions, indexed by weighted_users_index.
+    static ref READERS: Mutex<HashMap<usize, Reader>> = Mutex::new(HashMap::new());
+    /// A predefined or numeric entity at the start of the text.
+    static ref ENTITY: Regex =
+        Regex::new(r"^&(amp|lt|gt|quot|apos|#[0-9]+|#x[0-9a-fA-F]+);").unwrap();
+    /// A start tag, capturing the element's name.
+    static ref START_TAG: Regex = Regex::new(r"<([\w:.-]+)[^>]*>").unwrap();
+    /// The prolog and start of an RSS 2.0 document.
+    static ref RSS: Regex =
+        Regex::new(r#"^(\s*<\?[^>]*\?>|\s*<!--.*?-->)*\s*<rss\b[^>]*\bversion=["']2\.0["']"#)
+            .unwrap();
+    /// An item of a feed, capturing its content.
+    static ref ITEM: Regex = Regex::new(r"(?s)<item\b[^>]*>(.*?)</item>").unwrap();
+}
+
+/// The feeds a reader subscribed to, and what it was told about each one.
+#[derive(Clone, Debug, Default)]
+struct Reader {
+    /// The feed found on each term page, by the page's path.
+    feeds: HashMap<String, String>,
+    /// The validators returned with each feed, by the feed's path.
+    validators: HashMap<String, Validators>,
+}
+
+/// The validators a feed was returned with, sent back in conditional requests so the
+/// server can answer 304 Not Modified if the feed hasn't changed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Whether there's anything to make a request conditional with.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Whether a name is a valid XML element or attribute name, ie "item" or "dc:creator".
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_alphabetic() || first == '_' || first == ':' => {
+            chars.all(|c| c.is_alphanumeric() || c == '_' || c == ':' || c == '-' || c == '.')
+        }
+        _ => false,
+    }
+}
+
+/// Check that text or an attribute value only uses predefined or numeric entities, and
+/// escapes every other `&`.
+fn check_text(text: &str) -> Result<(), String> {
+    if text.contains("]]>") {
+        return Err("]]> outside a CDATA section".to_string());
+    }
+    for (index, _) in text.match_indices('&') {
+        if !ENTITY.is_match(&text[index..]) {
+            let context: String = text[index..].chars().take(12).collect();
+            return Err(format!("undefined entity or unescaped &: {}", context));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a start tag, without the leading `<`, checking its name and attributes. Returns
+/// the element's name, whether it's an empty element, and the text after the tag.
+fn parse_start_tag(tag: &str) -> Result<(&str, bool, &str), String> {
+    let end = tag
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(tag.len());
+    let name = &tag[..end];
+    if !valid_name(name) {
+        return Err(format!("invalid element name <{}", name));
+    }
+
+    let mut rest = &tag[end..];
+    loop {
+        let trimmed = rest.trim_start();
+        if let Some(after) = trimmed.strip_prefix("/>") {
+            return Ok((name, true, after));
+        } else if let Some(after) = trimmed.strip_prefix('>') {
+            return Ok((name, false, after));
+        } else if trimmed.is_empty() {
+            return Err(format!("<{}> isn't closed", name));
+        } else if trimmed.len() == rest.len() {
+            return Err(format!(
+                "<{}> has attributes without whitespace between",
+                name
+            ));
+        }
+
+        let equals = trimmed
+            .find('=')
+            .ok_or_else(|| format!("<{}> has an attribute without a value", name))?;
+        let attribute = trimmed[..equals].trim_end();
+        if !valid_name(attribute) {
+            return Err(format!("<{}> has an invalid attribute {}", name, attribute));
+        }
+        let value = trimmed[equals + 1..].trim_start();
+        let quote = match value.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => return Err(format!("<{}> has an unquoted {}", name, attribute)),
+        };
+        let length = value[1..]
+            .find(quote)
+            .ok_or_else(|| format!("<{}> has an unterminated {}", name, attribute))?;
+        let text = &value[1..1 + length];
+        if text.contains('<') {
+            return Err(format!("<{}> has a < in {}", name, attribute));
+        }
+        check_text(text)?;
+        rest = &value[2 + length..];
+    }
+}
+
+/// Returns the text after the delimiter that ends a comment, CDATA section or processing
+/// instruction.
+fn after<'a>(markup: &'a str, end: &str, what: &str) -> Result<&'a str, String> {
+    match markup.find(end) {
+        Some(index) => Ok(&markup[index + end.len()..]),
+        None => Err(format!("unterminated {}", what)),
+    }
+}
+
+/// Check that a document is well-formed XML: a single root element, tags properly nested
+/// and closed, quoted attributes, and only predefined or numeric entities. Feeds don't
+/// have DTDs, so DTDs aren't supported. Returns a description of the first problem found.
+pub fn check_well_formed(xml: &str) -> Result<(), String> {
+    let mut open: Vec<&str> = Vec::new();
+    let mut root = false;
+    let mut rest = xml.trim_start_matches('\u{feff}');
+    loop {
+        let (text, markup) = rest.split_at(rest.find('<').unwrap_or(rest.len()));
+        if open.is_empty() && !text.trim().is_empty() {
+            return Err("text outside the root element".to_string());
+        }
+        check_text(text)?;
+        if markup.is_empty() {
+            break;
+        }
+
+        rest = if markup.starts_with("<?") {
+            after(markup, "?>", "processing instruction")?
+        } else if markup.starts_with("<!--") {
+            after(markup, "-->", "comment")?
+        } else if markup.starts_with("<![CDATA[") && !open.is_empty() {
+            after(markup, "]]>", "CDATA section")?
+        } else if markup.starts_with("<!") {
+            return Err("DTDs aren't supported".to_string());
+        } else if let Some(tag) = markup.strip_prefix("</") {
+            let end = tag
+                .find('>')
+                .ok_or_else(|| "unterminated end tag".to_string())?;
+            let name = tag[..end].trim_end();
+            match open.pop() {
+                Some(open) if open == name => (),
+                Some(open) => return Err(format!("</{}> closes <{}>", name, open)),
+                None => return Err(format!("</{}> closes nothing", name)),
+            }
+            &tag[end + 1..]
+        } else {
+            let (name, empty, after_tag) = parse_start_tag(&markup[1..])?;
+            if open.is_empty() && root {
+                return Err(format!("<{}> is a second root element", name));
+            }
+            root = true;
+            if !empty {
+                open.push(name);
+            }
+            after_tag
+        };
+    }
+
+    if let Some(name) = open.last() {
+        return Err(format!("<{}> isn't closed", name));
+    }
+    if !root {
+        return Err("no root element".to_string());
+    }
+    Ok(())
+}
+
+/// Returns the content of the first element with the name, up to its end tag.
+fn element_content<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let start = START_TAG
+        .captures_iter(xml)
+        .find(|tag| &tag[1] == name)?
+        .get(0)?
+        .end();
+    let length = xml[start..].find(&format!("</{}>", name))?;
+    Some(&xml[start..start + length])
+}
+
+/// Returns the decoded text of the first element with the name.
+fn element_text(xml: &str, name: &str) -> Option<String> {
+    element_content(xml, name).map(|text| {
+        let text = text.trim();
+        match text
+            .strip_prefix("<![CDATA[")
+            .and_then(|text| text.strip_suffix("]]>"))
+        {
+            Some(data) => data.to_string(),
+            None => common::decode_entities(text),
+        }
+    })
+}
+
+/// Validate an RSS feed in the language: it must be well-formed RSS 2.0 with a channel in
+/// the language, titled as expected if a title is given, and every item must link to a
+/// node in the catalog in the language, with the node's title. Returns how many items the
+/// feed has, or a description of the first problem found.
+pub fn validate_feed(xml: &str, langcode: &str, title: Option<&str>) -> Result<usize, String> {
+    check_well_formed(xml)?;
+    if !RSS.is_match(xml.trim_start_matches('\u{feff}')) {
+        return Err("not an RSS 2.0 feed".to_string());
+    }
+    let channel = element_content(xml, "channel").ok_or_else(|| "no channel".to_string())?;
+
+    // The channel's own elements come before its items.
+    let head = &channel[..channel.find("<item").unwrap_or(channel.len())];
+    let channel_title =
+        element_text(head, "title").ok_or_else(|| "no channel title".to_string())?;
+    if element_text(head, "link").is_none() {
+        return Err("no channel link".to_string());
+    }
+    match element_text(head, "language") {
+        Some(language) if language != langcode => {
+            return Err(format!(
+                "channel language {}, expected {}",
+                language, langcode
+            ));
+        }
+        _ => (),
+    }
+    match title {
+        Some(title) if channel_title != title => {
+            return Err(format!(
+                "channel title {}, expected {}",
+                channel_title, title
+            ));
+        }
+        _ => (),
+    }
+
+    let mut items = 0;
+    for item in ITEM.captures_iter(channel) {
+        let item_title = element_text(&item[1], "title")
+            .ok_or_else(|| format!("item {} has no title", items + 1))?;
+        let link =
+            element_text(&item[1], "link").ok_or_else(|| format!("{} has no link", item_title))?;
+        match common::get_node_by_path(&link) {
+            Some((_, expected)) if item_title != expected => {
+                return Err(format!(
+                    "{} is titled {}, expected {}",
+                    link, item_title, expected
+                ));
+            }
+            Some(_) if common::url_language(&link) != langcode => {
+                return Err(format!("{} isn't in {}", link, langcode));
+            }
+            Some(_) => (),
+            None => return Err(format!("{} isn't in the catalog", link)),
+        }
+        items += 1;
+    }
+
+    Ok(items)
+}
+
+/// Use regular expression to get the RSS feeds a page links to with `<link
+/// rel="alternate" type="application/rss+xml">`, as feed readers discover them.
+pub fn get_feed_links(html: &str) -> Vec<String> {
+    let link_re = Regex::new(r#"<link\b[^>]*\btype="application/rss\+xml"[^>]*>"#).unwrap();
+    let href_re = Regex::new(r#"\shref="([^"]*)""#).unwrap();
+    link_re
+        .find_iter(html)
+        .filter(|link| link.as_str().contains(r#"rel="alternate""#))
+        .filter_map(|link| href_re.captures(link.as_str()))
+        .map(|href| common::decode_entities(&href[1]))
+        .collect()
+}
+
+/// Load a term page, and subscribe the reader to the feed it links to. Returns the feed's
+/// path, or None if the request failed.
+async fn subscribe(
+    user: &GooseUser,
+    page: &str,
+    langcode: &str,
+) -> Result<Option<String>, GooseTaskError> {
+    let path = common::iri_to_uri(page);
+    let name = format!("feeds /{} subscribe", langcode);
+    let mut goose = user.get_named(&path, &name).await?;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(html) => {
+                    if let Some(error) = errors::detect_drupal_error(&html) {
+                        return user
+                            .set_failure(
+                                &format!("{}: {}", goose.request.url, error),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            )
+                            .map(|_| None);
+                    }
+                    match get_feed_links(&html).first() {
+                        Some(feed) => {
+                            let feed = common::url_path(feed).to_string();
+                            READERS
+                                .lock()
+                                .unwrap()
+                                .entry(user.weighted_users_index)
+                                .or_default()
+                                .feeds
+                                .insert(page.to_string(), feed.clone());
+                            Ok(Some(feed))
+                        }
+                        None => user
+                            .set_failure(
+                                &format!("{}: no RSS feed link", goose.request.url),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            )
+                            .map(|_| None),
+                    }
+                }
+                Err(e) => user
+                    .set_failure(
+                        &format!("{}: failed to parse page: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    )
+                    .map(|_| None),
+            }
+        }
+        Err(e) => user
+            .set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            )
+            .map(|_| None),
+    }
+}
+
+/// Poll a feed in the specified language, as a feed reader does: the front page feed, or
+/// the feed of a random term, discovered on the term's page the first time. After a feed
+/// is first loaded, polls send back the ETag and Last-Modified date it was returned with,
+/// so Drupal can answer 304 Not Modified if it hasn't changed.
+pub async fn poll(user: &GooseUser, english: bool) -> GooseTaskResult {
+    let langcode = if english { "en" } else { "es" };
+    let front = random::user_rng(user).gen_bool(0.5);
+    let (kind, path, name, title) = if front {
+        let path = if english { "/rss.xml" } else { "/es/rss.xml" };
+        ("front", path.to_string(), path.to_string(), None)
+    } else {
+        let terms = common::get_terms();
+        let term = terms.choose(&mut random::user_rng(user)).unwrap();
+        let (page, title) = if english {
+            (term.url_en, term.title_en)
+        } else {
+            (term.url_es, term.title_es)
+        };
+        let subscribed = READERS
+            .lock()
+            .unwrap()
+            .get(&user.weighted_users_index)
+            .and_then(|reader| reader.feeds.get(page).cloned());
+        let path = match subscribed {
+            Some(path) => path,
+            None => match subscribe(user, page, langcode).await? {
+                Some(path) => path,
+                None => return Ok(()),
+            },
+        };
+        let name = format!("/{}/taxonomy/term/%/feed", langcode);
+        ("term", path, name, Some(title))
+    };
+
+    let validators = READERS
+        .lock()
+        .unwrap()
+        .get(&user.weighted_users_index)
+        .and_then(|reader| reader.validators.get(&path).cloned())
+        .unwrap_or_default();
+    let mut request_builder = user.goose_get(&path).await?;
+    if let Some(etag) = &validators.etag {
+        request_builder = request_builder.header(IF_NONE_MATCH, etag.as_str());
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request_builder = request_builder.header(IF_MODIFIED_SINCE, last_modified.as_str());
+    }
+    let conditional = !validators.is_empty();
+
+    let mut goose = user.goose_send(request_builder, Some(&name)).await?;
+    match goose.response {
+        Ok(response) => {
+            let status = response.status();
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            if status == StatusCode::NOT_MODIFIED {
+                // Goose counts a 304 as a failure, but feed readers rely on it.
+                user.set_success(&mut goose.request)?;
+                if !conditional {
+                    return user.set_failure(
+                        &format!(
+                            "{}: 304 Not Modified to an unconditional request",
+                            goose.request.url
+                        ),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+                metrics::record_feed(langcode, kind, true, true, 0, goose.request.response_time);
+                return Ok(());
+            }
+            match response.text().await {
+                Ok(xml) => {
+                    let valid = if status.is_success() {
+                        validate_feed(&xml, langcode, title)
+                    } else {
+                        Err(status.to_string())
+                    };
+                    let items = match valid {
+                        Ok(items) => items,
+                        Err(e) => {
+                            return user.set_failure(
+                                &format!("{}: {}", goose.request.url, e),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&xml),
+                            );
+                        }
+                    };
+
+                    let header = |name: HeaderName| {
+                        headers
+                            .get(name)
+                            .and_then(|value| value.to_str().ok())
+                            .map(str::to_string)
+                    };
+                    let validators = Validators {
+                        etag: header(ETAG),
+                        last_modified: header(LAST_MODIFIED),
+                    };
+                    READERS
+                        .lock()
+                        .unwrap()
+                        .entry(user.weighted_users_index)
+                        .or_default()
+                        .validators
+                        .insert(path, validators);
+                    metrics::record_feed(
+                        langcode,
+                        kind,
+                        conditional,
+                        false,
+                        items,
+                        goose.request.response_time,
+                    );
+                }
+                Err(e) => {
+                    return user.set_failure(
+                        &format!("{}: failed to parse feed: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use goose::prelude::*;
+use lazy_static::lazy_static;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use regex::Regex;
+use reqwest::header::{HeaderMap, CACHE_CONTROL, CONTENT_TYPE};
+
+use crate::admin;
+use crate::common::{self, ContentType, PageType};
+use crate::config;
+use crate::metrics;
+use crate::random;
+
+lazy_static! {
+    /// When image styles were last flushed, with --image-flush.
+    static ref LAST_FLUSH: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+/// An image style derivative found on a page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Derivative {
+    /// The path of the derivative, including its itok token.
+    pub url: String,
+    /// The machine name of the image style, ie "large_3_2_768x512".
+    pub style: String,
+}
+
+/// Finds all image style derivatives on the page, in img src and picture source srcset
+/// attributes, returning each one once in the order they appear. Derivatives without an
+/// itok token are skipped, as Drupal refuses to generate them.
+pub fn get_derivatives(html: &str) -> Vec<Derivative> {
+    let attribute = Regex::new(r#"\s(?:src|srcset)="([^"]*)""#).unwrap();
+    let derivative = Regex::new(
+        r"^/(?:sites/[^/]+/files|system/files)/styles/(\w+)/\w+/[^?]+\?(?:.*&)?itok=[\w-]+",
+    )
+    .unwrap();
+    let mut derivatives: Vec<Derivative> = Vec::new();
+    for value in attribute.captures_iter(html) {
+        // A srcset lists candidates separated by commas, each followed by a descriptor.
+        for candidate in common::decode_entities(&value[1]).split(',') {
+            let url = common::url_path(candidate.split_whitespace().next().unwrap_or_default());
+            if let Some(captures) = derivative.captures(url) {
+                if derivatives.iter().all(|existing| existing.url != url) {
+                    derivatives.push(Derivative {
+                        url: url.to_string(),
+                        style: captures[1].to_string(),
+                    });
+                }
+            }
+        }
+    }
+    derivatives
+}
+
+/// Load a random recipe or article in the language, returning the image style
+/// derivatives on it, or None if the request failed.
+async fn load_page(
+    user: &GooseUser,
+    english: bool,
+) -> Result<Option<Vec<Derivative>>, GooseTaskError> {
+    let content_type = if random::user_rng(user).gen() {
+        ContentType::Recipe
+    } else {
+        ContentType::Article
+    };
+    let nodes = common::get_nodes(&content_type);
+    let node = nodes.choose(&mut random::user_rng(user)).unwrap();
+    let (path, title, name) = if english {
+        (node.url_en, node.title_en, "images /en page")
+    } else {
+        (node.url_es, node.title_es, "images /es page")
+    };
+
+    let mut goose = user.get_named(&common::iri_to_uri(path), name).await?;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(html) => {
+                    let page_type = PageType::from(&content_type);
+                    if let Err(e) = common::validate_page(&html, headers, &page_type, title) {
+                        return user
+                            .set_failure(
+                                &format!("{}: {}", goose.request.url, e),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            )
+                            .map(|_| None);
+                    }
+                    let derivatives = get_derivatives(&html);
+                    if derivatives.is_empty() {
+                        return user
+                            .set_failure(
+                                &format!("{}: no image style derivatives", goose.request.url),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&html),
+                            )
+                            .map(|_| None);
+                    }
+                    Ok(Some(derivatives))
+                }
+                Err(e) => user
+                    .set_failure(
+                        &format!("{}: failed to parse page: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    )
+                    .map(|_| None),
+            }
+        }
+        Err(e) => user
+            .set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            )
+            .map(|_| None),
+    }
+}
+
+/// Whether Drupal generated the derivative to answer the request. Drupal delivers the
+/// derivatives it generates itself, adding its X-Generator header and marking them
+/// private, while derivatives already generated are files the web server delivers.
+pub fn is_generated(headers: &HeaderMap) -> bool {
+    headers.contains_key("x-generator")
+        || headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| {
+                value
+                    .split(',')
+                    .any(|directive| directive.trim() == "private")
+            })
+}
+
+/// Request a derivative, confirming an image was returned, and record how long it took
+/// apart from other derivatives of the style depending on whether it was generated.
+/// Returns whether it was generated, or None if the request failed.
+async fn request(
+    user: &GooseUser,
+    derivative: &Derivative,
+) -> Result<Option<bool>, GooseTaskError> {
+    let name = format!("image style {}", derivative.style);
+    let mut goose = user.get_named(&derivative.url, &name).await?;
+    match goose.response {
+        Ok(response) => {
+            let status = response.status();
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            let content_type = headers
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            match response.bytes().await {
+                Ok(image) => {
+                    // Drupal answers 503 while another request is generating the same
+                    // derivative, and an HTML page if the itok token is wrong.
+                    let error = if !status.is_success() {
+                        Some(status.to_string())
+                    } else if !content_type.starts_with("image/") {
+                        Some(format!("not an image: {}", content_type))
+                    } else if image.is_empty() {
+                        Some("empty image".to_string())
+                    } else {
+                        None
+                    };
+                    if let Some(error) = error {
+                        return user
+                            .set_failure(
+                                &format!("{}: {}", goose.request.url, error),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&String::from_utf8_lossy(&image)),
+                            )
+                            .map(|_| None);
+                    }
+                    let generated = is_generated(headers);
+                    metrics::record_image(
+                        &derivative.style,
+                        generated,
+                        goose.request.response_time,
+                    );
+                    Ok(Some(generated))
+                }
+                Err(e) => user
+                    .set_failure(
+                        &format!("{}: failed to read image: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    )
+                    .map(|_| None),
+            }
+        }
+        Err(e) => user
+            .set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            )
+            .map(|_| None),
+    }
+}
+
+/// Whether image styles are due to be flushed, in which case the flush is claimed for
+/// this user, so image styles are only flushed once per --image-flush-interval.
+fn flush_due() -> bool {
+    let interval = config::get_configuration()
+        .image_flush_interval
+        .unwrap_or(60);
+    let mut last_flush = LAST_FLUSH.lock().unwrap();
+    let due = match *last_flush {
+        Some(flushed) => flushed.elapsed() >= Duration::from_secs(interval as u64),
+        None => true,
+    };
+    if due {
+        *last_flush = Some(Instant::now());
+    }
+    due
+}
+
+/// Load a random recipe or article in the language, then request the image style
+/// derivatives on it as a browser with a cold cache does. A derivative Drupal generated
+/// is requested again to measure delivering the generated image. With --image-flush, the
+/// admin user first flushes each image style on the page once per interval, so their
+/// derivatives have to be generated again.
+pub async fn derivatives(user: &GooseUser, english: bool) -> GooseTaskResult {
+    let derivatives = match load_page(user, english).await? {
+        Some(derivatives) => derivatives,
+        None => return Ok(()),
+    };
+
+    if config::get_configuration().image_flush && flush_due() {
+        let mut styles: Vec<&str> = derivatives
+            .iter()
+            .map(|derivative| derivative.style.as_str())
+            .collect();
+        styles.sort_unstable();
+        styles.dedup();
+        for style in styles {
+            admin::flush_image_style(user, style).await?;
+            metrics::record_image_flush();
+        }
+    }
+
+    for derivative in &derivatives {
+        if request(user, derivative).await? == Some(true) {
+            request(user, derivative).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,762 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use goose::prelude::*;
+use lazy_static::lazy_static;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+
+use crate::admin;
+use crate::common::{self, ContentType};
+use crate::config;
+use crate::fake::{self, NodeDraft};
+use crate::random;
+
+/// The JSON:API media type, sent in the Accept header and returned by Drupal.
+pub const MEDIA_TYPE: &str = "application/vnd.api+json";
+
+/// The most pages of a collection a client follows with the next link.
+const MAX_PAGES: usize = 3;
+
+lazy_static! {
+    /// The CSRF token of each writer logged in with a session cookie, indexed by
+    /// weighted_users_index.
+    static ref CSRF_TOKENS: Mutex<HashMap<usize, String>> = Mutex::new(HashMap::new());
+}
+
+/// How the JSON:API writer authenticates as the admin user.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Auth {
+    /// Send the name and password with every request, with Drupal's basic_auth module.
+    Basic,
+    /// Log in with the login form, then send the session's CSRF token with every write.
+    Cookie,
+}
+
+impl std::str::FromStr for Auth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "basic" => Ok(Auth::Basic),
+            "cookie" => Ok(Auth::Cookie),
+            _ => Err(format!("unknown authentication: {}", s)),
+        }
+    }
+}
+
+/// The Umami resources requested through JSON:API.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resource {
+    Recipe,
+    Article,
+    Tags,
+    RecipeCategory,
+}
+
+impl Resource {
+    /// The path of the resource's collection, without a language prefix.
+    pub fn path(self) -> &'static str {
+        match self {
+            Resource::Recipe => "/jsonapi/node/recipe",
+            Resource::Article => "/jsonapi/node/article",
+            Resource::Tags => "/jsonapi/taxonomy_term/tags",
+            Resource::RecipeCategory => "/jsonapi/taxonomy_term/recipe_category",
+        }
+    }
+
+    /// The resource type, as used in resource objects and sparse fieldsets.
+    pub fn resource_type(self) -> &'static str {
+        match self {
+            Resource::Recipe => "node--recipe",
+            Resource::Article => "node--article",
+            Resource::Tags => "taxonomy_term--tags",
+            Resource::RecipeCategory => "taxonomy_term--recipe_category",
+        }
+    }
+
+    /// The attribute holding the title of a resource: the title of nodes, the name of
+    /// terms.
+    pub fn title_field(self) -> &'static str {
+        match self {
+            Resource::Recipe | Resource::Article => "title",
+            Resource::Tags | Resource::RecipeCategory => "name",
+        }
+    }
+
+    /// Sorts a client could request.
+    fn sorts(self) -> &'static [&'static str] {
+        match self {
+            Resource::Recipe | Resource::Article => &["title", "-title", "-created", "created"],
+            Resource::Tags | Resource::RecipeCategory => &["name", "-name", "weight"],
+        }
+    }
+
+    /// Attributes, besides the title, path and langcode, a sparse fieldset could request.
+    fn fields(self) -> &'static [&'static str] {
+        match self {
+            Resource::Recipe => &["created", "field_difficulty", "field_preparation_time"],
+            Resource::Article => &["created", "body"],
+            Resource::Tags | Resource::RecipeCategory => &["description", "weight"],
+        }
+    }
+
+    /// Relationships a client could include.
+    fn includes(self) -> &'static [&'static str] {
+        match self {
+            Resource::Recipe => &[
+                "field_recipe_category",
+                "field_tags",
+                "field_recipe_category,field_tags",
+            ],
+            Resource::Article => &["field_tags"],
+            Resource::Tags | Resource::RecipeCategory => &[],
+        }
+    }
+
+    /// Returns the title the catalog expects of a resource in the language, found by the
+    /// resource's path alias, which doesn't include the language prefix.
+    pub fn catalog_title(self, langcode: &str, alias: &str) -> Option<String> {
+        let path = format!("/{}{}", langcode, alias);
+        match self {
+            Resource::Recipe | Resource::Article => {
+                common::get_node_by_path(&path).map(|(_, title)| title)
+            }
+            Resource::Tags | Resource::RecipeCategory => {
+                let path = common::percent_decode(&path);
+                common::get_terms().iter().find_map(|term| {
+                    if path == term.url_en {
+                        Some(term.title_en.to_string())
+                    } else if path == term.url_es {
+                        Some(term.title_es.to_string())
+                    } else {
+                        None
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// The query parameters of a request for a JSON:API collection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CollectionQuery {
+    /// A filter on the title of the resources.
+    pub title: Option<String>,
+    pub sort: Option<&'static str>,
+    /// The attributes of a sparse fieldset. If empty, all fields are returned.
+    pub fields: Vec<&'static str>,
+    /// The relationships to include, separated by commas.
+    pub include: Option<&'static str>,
+    /// How many resources to return per page.
+    pub limit: Option<usize>,
+}
+
+impl CollectionQuery {
+    /// Returns the query string requesting a collection of the resource, without the
+    /// leading `?`.
+    pub fn query_string(&self, resource: Resource) -> String {
+        let mut parameters = Vec::new();
+        if let Some(title) = &self.title {
+            parameters.push(format!(
+                "filter[{}]={}",
+                resource.title_field(),
+                common::percent_encode(title)
+            ));
+        }
+        if let Some(sort) = self.sort {
+            parameters.push(format!("sort={}", sort));
+        }
+        if !self.fields.is_empty() {
+            // Relationships must be in the sparse fieldset to be included.
+            let mut fields = self.fields.clone();
+            if let Some(include) = self.include {
+                fields.extend(include.split(','));
+            }
+            parameters.push(format!(
+                "fields[{}]={}",
+                resource.resource_type(),
+                fields.join(",")
+            ));
+        }
+        if let Some(include) = self.include {
+            parameters.push(format!("include={}", include));
+        }
+        if let Some(limit) = self.limit {
+            parameters.push(format!("page[limit]={}", limit));
+        }
+        parameters.join("&")
+    }
+}
+
+/// A validated page of a JSON:API collection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collection {
+    /// How many resources were on the page.
+    pub resources: usize,
+    /// The link to the next page, if there is one.
+    pub next: Option<String>,
+}
+
+/// Returns the type and id identifying a resource.
+fn identifier(resource: &Value) -> Option<(String, String)> {
+    Some((
+        resource["type"].as_str()?.to_string(),
+        resource["id"].as_str()?.to_string(),
+    ))
+}
+
+/// Returns the resources a relationship links to, whether to one or many.
+fn linkage(relationship: &Value) -> Vec<(String, String)> {
+    match &relationship["data"] {
+        Value::Array(resources) => resources.iter().filter_map(identifier).collect(),
+        resource => identifier(resource).into_iter().collect(),
+    }
+}
+
+/// Returns the details of the error objects in a JSON:API document, or None if it has no
+/// errors.
+fn error_message(document: &Value) -> Option<String> {
+    let errors = document["errors"].as_array()?;
+    if errors.is_empty() {
+        return None;
+    }
+    let details: Vec<&str> = errors
+        .iter()
+        .map(|error| {
+            error["detail"]
+                .as_str()
+                .or_else(|| error["title"].as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    Some(format!("JSON:API error: {}", details.join("; ")))
+}
+
+/// Validate a page of a JSON:API collection of the resource in the language: it must be
+/// a JSON:API document without errors, every resource must be of the right type and in the
+/// language, sparse fieldsets and filters must be applied, every included relationship
+/// must be in the document, and resources in the catalog must have the expected title.
+/// Returns a description of the first problem found.
+pub fn validate_collection(
+    body: &str,
+    resource: Resource,
+    langcode: &str,
+    query: &CollectionQuery,
+) -> Result<Collection, String> {
+    let document: Value = serde_json::from_str(body).map_err(|e| format!("invalid JSON: {}", e))?;
+    if let Some(error) = error_message(&document) {
+        return Err(error);
+    }
+    let data = document["data"]
+        .as_array()
+        .ok_or_else(|| "data is not an array of resources".to_string())?;
+    match query.limit {
+        Some(limit) if data.len() > limit => {
+            return Err(format!(
+                "{} resources, expected at most {}",
+                data.len(),
+                limit
+            ));
+        }
+        _ => (),
+    }
+    if query.title.is_some() && data.is_empty() {
+        return Err("no resources match the filter".to_string());
+    }
+
+    let included: HashSet<(String, String)> = document["included"]
+        .as_array()
+        .map(|included| included.iter().filter_map(identifier).collect())
+        .unwrap_or_default();
+    let title_field = resource.title_field();
+    for object in data {
+        let id = object["id"]
+            .as_str()
+            .ok_or_else(|| "resource without an id".to_string())?;
+        let resource_type = object["type"].as_str().unwrap_or_default();
+        if resource_type != resource.resource_type() {
+            return Err(format!(
+                "{}: type is {}, expected {}",
+                id,
+                resource_type,
+                resource.resource_type()
+            ));
+        }
+        let attributes = object["attributes"]
+            .as_object()
+            .ok_or_else(|| format!("{}: no attributes", id))?;
+        if let Some(field) = attributes
+            .keys()
+            .find(|field| !query.fields.is_empty() && !query.fields.contains(&field.as_str()))
+        {
+            return Err(format!("{}: {} is not in the sparse fieldset", id, field));
+        }
+        let title = attributes
+            .get(title_field)
+            .and_then(|title| title.as_str())
+            .ok_or_else(|| format!("{}: no {}", id, title_field))?;
+        match attributes
+            .get("langcode")
+            .and_then(|langcode| langcode.as_str())
+        {
+            Some(language) if language != langcode => {
+                return Err(format!(
+                    "{}: langcode is {}, expected {}",
+                    id, language, langcode
+                ));
+            }
+            _ => (),
+        }
+        match &query.title {
+            Some(filter) if title != filter => {
+                return Err(format!(
+                    "{}: {} doesn't match the filter {}",
+                    id, title, filter
+                ));
+            }
+            _ => (),
+        }
+        let alias = attributes
+            .get("path")
+            .and_then(|path| path["alias"].as_str());
+        match alias.and_then(|alias| resource.catalog_title(langcode, alias)) {
+            Some(expected) if title != expected => {
+                return Err(format!(
+                    "{}: {} is {}, expected {}",
+                    id, title_field, title, expected
+                ));
+            }
+            _ => (),
+        }
+
+        for relationship in query.include.unwrap_or_default().split(',') {
+            if relationship.is_empty() {
+                continue;
+            }
+            let identifiers = linkage(&object["relationships"][relationship]);
+            if let Some((related_type, related_id)) = identifiers
+                .into_iter()
+                .find(|identifier| !included.contains(identifier))
+            {
+                return Err(format!(
+                    "{}: {} {} {} is not included",
+                    id, relationship, related_type, related_id
+                ));
+            }
+        }
+    }
+
+    Ok(Collection {
+        resources: data.len(),
+        next: document["links"]["next"]["href"]
+            .as_str()
+            .map(|href| href.to_string()),
+    })
+}
+
+/// Returns the document creating a recipe or article from the draft, in the language.
+/// Nodes are created unpublished, so visitors never see them.
+pub fn create_document(resource: Resource, langcode: &str, draft: &NodeDraft) -> Value {
+    let mut attributes = json!({
+        "title": draft.title,
+        "langcode": langcode,
+        "status": false,
+    });
+    if resource == Resource::Recipe {
+        attributes["field_summary"] = json!({ "value": draft.summary, "format": "basic_html" });
+        attributes["field_recipe_instruction"] =
+            json!({ "value": draft.body, "format": "basic_html" });
+        attributes["field_difficulty"] = draft.difficulty.into();
+        attributes["field_preparation_time"] = draft.preparation_time.into();
+        attributes["field_cooking_time"] = draft.cooking_time.into();
+        attributes["field_number_of_servings"] = draft.servings.into();
+    } else {
+        attributes["body"] = json!({
+            "value": draft.body,
+            "summary": draft.summary,
+            "format": "basic_html",
+        });
+    }
+    json!({
+        "data": {
+            "type": resource.resource_type(),
+            "attributes": attributes,
+        },
+    })
+}
+
+/// Returns the document changing the title of a resource.
+pub fn update_document(resource: Resource, id: &str, title: &str) -> Value {
+    json!({
+        "data": {
+            "type": resource.resource_type(),
+            "id": id,
+            "attributes": { resource.title_field(): title },
+        },
+    })
+}
+
+/// Validate the document returned after a resource was created or updated: it must be a
+/// JSON:API document without errors, holding a resource of the right type with the title.
+/// Returns the id of the resource, or a description of the first problem found.
+pub fn validate_resource(body: &str, resource: Resource, title: &str) -> Result<String, String> {
+    let document: Value = serde_json::from_str(body).map_err(|e| format!("invalid JSON: {}", e))?;
+    if let Some(error) = error_message(&document) {
+        return Err(error);
+    }
+    let data = &document["data"];
+    let (resource_type, id) =
+        identifier(data).ok_or_else(|| "data is not a resource object".to_string())?;
+    if resource_type != resource.resource_type() {
+        return Err(format!(
+            "{} is a {}, expected {}",
+            id,
+            resource_type,
+            resource.resource_type()
+        ));
+    }
+    match data["attributes"][resource.title_field()].as_str() {
+        Some(written) if written == title => Ok(id),
+        Some(written) => Err(format!(
+            "{}: {} is {}, expected {}",
+            id,
+            resource.title_field(),
+            written,
+            title
+        )),
+        None => Err(format!("{}: no {}", id, resource.title_field())),
+    }
+}
+
+/// Request a page of a JSON:API collection and validate it. Returns None if the request
+/// failed.
+async fn get_collection(
+    user: &GooseUser,
+    path: &str,
+    name: &str,
+    resource: Resource,
+    langcode: &str,
+    query: &CollectionQuery,
+) -> Result<Option<Collection>, GooseTaskError> {
+    let request_builder = user.goose_get(path).await?.header("Accept", MEDIA_TYPE);
+    let mut goose = user.goose_send(request_builder, Some(name)).await?;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(body) => match validate_collection(&body, resource, langcode, query) {
+                    Ok(collection) => Ok(Some(collection)),
+                    Err(e) => user
+                        .set_failure(
+                            &format!("{}: {}", goose.request.url, e),
+                            &mut goose.request,
+                            Some(headers),
+                            Some(&body),
+                        )
+                        .map(|_| None),
+                },
+                Err(e) => user
+                    .set_failure(
+                        &format!("{}: failed to parse response: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    )
+                    .map(|_| None),
+            }
+        }
+        Err(e) => user
+            .set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            )
+            .map(|_| None),
+    }
+}
+
+/// Returns the path of a collection in the language. English is Drupal's default
+/// language, so English collections have no language prefix.
+fn collection_path(resource: Resource, english: bool) -> String {
+    if english {
+        resource.path().to_string()
+    } else {
+        format!("/es{}", resource.path())
+    }
+}
+
+/// Request a random collection in the specified language, as a decoupled frontend does:
+/// with a random sort, and often a sparse fieldset, included relationships and a page
+/// size. Some clients follow the next link to later pages.
+pub async fn collection(user: &GooseUser, english: bool) -> GooseTaskResult {
+    let resources = [
+        Resource::Recipe,
+        Resource::Article,
+        Resource::Tags,
+        Resource::RecipeCategory,
+    ];
+    let resource = *resources.choose(&mut random::user_rng(user)).unwrap();
+    let mut rng = random::user_rng(user);
+    let mut query = CollectionQuery {
+        sort: resource.sorts().choose(&mut rng).copied(),
+        include: resource.includes().choose(&mut rng).copied(),
+        ..Default::default()
+    };
+    if rng.gen_bool(0.5) {
+        query.fields = vec![resource.title_field(), "path", "langcode"];
+        if let Some(field) = resource.fields().choose(&mut rng) {
+            query.fields.push(*field);
+        }
+    }
+    if rng.gen_bool(0.5) {
+        query.limit = Some(rng.gen_range(2, 11));
+    }
+    let pages = rng.gen_range(1, MAX_PAGES + 1);
+
+    let langcode = if english { "en" } else { "es" };
+    let path = collection_path(resource, english);
+    let mut url = format!("{}?{}", path, query.query_string(resource));
+    for page in 0..pages {
+        // Later pages are named apart, as their offset changes what Drupal loads.
+        let name = if page == 0 {
+            path.clone()
+        } else {
+            format!("{} next", path)
+        };
+        let collection = get_collection(user, &url, &name, resource, langcode, &query).await?;
+        match collection.and_then(|collection| collection.next) {
+            Some(next) => url = common::url_path(&next).to_string(),
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Request recipes or articles in the specified language, filtered by the title of a
+/// random node in the catalog, as a decoupled frontend looking up a node does. The node
+/// must be found.
+pub async fn filter_by_title(user: &GooseUser, english: bool) -> GooseTaskResult {
+    let resource = *[Resource::Recipe, Resource::Article]
+        .choose(&mut random::user_rng(user))
+        .unwrap();
+    let content_type = if resource == Resource::Recipe {
+        ContentType::Recipe
+    } else {
+        ContentType::Article
+    };
+    let nodes = common::get_nodes(&content_type);
+    let node = nodes.choose(&mut random::user_rng(user)).unwrap();
+    let (title, langcode) = if english {
+        (node.title_en, "en")
+    } else {
+        (node.title_es, "es")
+    };
+    let query = CollectionQuery {
+        title: Some(title.to_string()),
+        fields: vec![resource.title_field(), "path", "langcode"],
+        ..Default::default()
+    };
+
+    let path = collection_path(resource, english);
+    let url = format!("{}?{}", path, query.query_string(resource));
+    let name = format!("{} filter", path);
+    get_collection(user, &url, &name, resource, langcode, &query).await?;
+
+    Ok(())
+}
+
+/// Log in the JSON:API writer. Runs once when the writer starts: with cookie
+/// authentication, the writer logs in as the admin user, then requests the CSRF token it
+/// must send with every write. Basic Auth needs no session.
+pub async fn log_in(user: &GooseUser) -> GooseTaskResult {
+    if config::get_configuration().jsonapi_write != Some(Auth::Cookie) {
+        return Ok(());
+    }
+    admin::log_in(user).await?;
+
+    let mut goose = user
+        .get_named("/session/token", "jsonapi /session/token")
+        .await?;
+    match goose.response {
+        Ok(response) => {
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(token) => {
+                    if token.trim().is_empty() {
+                        return user.set_failure(
+                            &format!("{}: no CSRF token", goose.request.url),
+                            &mut goose.request,
+                            Some(headers),
+                            Some(&token),
+                        );
+                    }
+                    CSRF_TOKENS
+                        .lock()
+                        .unwrap()
+                        .insert(user.weighted_users_index, token.trim().to_string());
+                }
+                Err(e) => {
+                    return user.set_failure(
+                        &format!("{}: failed to parse response: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            return user.set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Authenticate a write request as configured with --jsonapi-write.
+fn authenticate(user: &GooseUser, request_builder: RequestBuilder) -> RequestBuilder {
+    let configuration = config::get_configuration();
+    match configuration.jsonapi_write {
+        Some(Auth::Basic) => request_builder.basic_auth(
+            configuration.admin_user.as_deref().unwrap_or("admin"),
+            configuration.admin_password.as_deref(),
+        ),
+        Some(Auth::Cookie) => match CSRF_TOKENS.lock().unwrap().get(&user.weighted_users_index) {
+            Some(token) => request_builder.header("X-CSRF-Token", token.as_str()),
+            None => request_builder,
+        },
+        None => request_builder,
+    }
+}
+
+/// Send an authenticated write request, and validate the response if it has the expected
+/// status. Otherwise the failure includes the JSON:API errors in the response. Returns None
+/// if the request failed.
+async fn send_write<T>(
+    user: &GooseUser,
+    request_builder: RequestBuilder,
+    name: &str,
+    expected: StatusCode,
+    validate: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<Option<T>, GooseTaskError> {
+    let request_builder = authenticate(user, request_builder).header("Accept", MEDIA_TYPE);
+    let mut goose = user.goose_send(request_builder, Some(name)).await?;
+    match goose.response {
+        Ok(response) => {
+            let status = response.status();
+            // Copy the headers so we have them for logging if there are errors.
+            let headers = &response.headers().clone();
+            match response.text().await {
+                Ok(body) => {
+                    let result = if status == expected {
+                        validate(&body)
+                    } else {
+                        let error = serde_json::from_str(&body)
+                            .ok()
+                            .and_then(|document| error_message(&document))
+                            .unwrap_or_else(|| format!("expected {}", expected));
+                        Err(format!("{}: {}", status, error))
+                    };
+                    match result {
+                        Ok(value) => Ok(Some(value)),
+                        Err(e) => user
+                            .set_failure(
+                                &format!("{}: {}", goose.request.url, e),
+                                &mut goose.request,
+                                Some(headers),
+                                Some(&body),
+                            )
+                            .map(|_| None),
+                    }
+                }
+                Err(e) => user
+                    .set_failure(
+                        &format!("{}: failed to parse response: {}", goose.request.url, e),
+                        &mut goose.request,
+                        Some(headers),
+                        None,
+                    )
+                    .map(|_| None),
+            }
+        }
+        Err(e) => user
+            .set_failure(
+                &format!("{}: no response from server: {}", goose.request.url, e),
+                &mut goose.request,
+                None,
+                None,
+            )
+            .map(|_| None),
+    }
+}
+
+/// Create a recipe or article in the specified language, change its title, then delete
+/// it, as a headless publishing workflow does. The node is deleted even if changing it
+/// fails, so the load test leaves no content behind.
+pub async fn write(user: &GooseUser, english: bool) -> GooseTaskResult {
+    let resource = *[Resource::Recipe, Resource::Article]
+        .choose(&mut random::user_rng(user))
+        .unwrap();
+    let draft = fake::node_draft(&mut random::user_rng(user), english);
+    let langcode = if english { "en" } else { "es" };
+    let path = collection_path(resource, english);
+
+    let document = create_document(resource, langcode, &draft);
+    let request_builder = user
+        .goose_post(&path)
+        .await?
+        .header("Content-Type", MEDIA_TYPE)
+        .body(document.to_string());
+    let created = send_write(
+        user,
+        request_builder,
+        &format!("{} create", path),
+        StatusCode::CREATED,
+        |body| validate_resource(body, resource, &draft.title),
+    )
+    .await?;
+    let id = match created {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let resource_path = format!("{}/{}", path, id);
+    let title = fake::node_draft(&mut random::user_rng(user), english).title;
+    let document = update_document(resource, &id, &title);
+    let request_builder = user
+        .goose_patch(&resource_path)
+        .await?
+        .header("Content-Type", MEDIA_TYPE)
+        .body(document.to_string());
+    let updated = send_write(
+        user,
+        request_builder,
+        &format!("{} update", path),
+        StatusCode::OK,
+        |body| validate_resource(body, resource, &title),
+    )
+    .await;
+
+    let request_builder = user.goose_delete(&resource_path).await?;
+    send_write(
+        user,
+        request_builder,
+        &format!("{} delete", path),
+        StatusCode::NO_CONTENT,
+        |_| Ok(()),
+    )
+    .await?;
+    updated.map(|_| ())
+}
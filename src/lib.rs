@@ -0,0 +1,349 @@
+pub mod admin;
+pub mod assertions;
+pub mod cache;
+pub mod common;
+pub mod config;
+pub mod crawler;
+pub mod english;
+pub mod errors;
+pub mod fake;
+pub mod feeds;
+pub mod images;
+pub mod jsonapi;
+pub mod metrics;
+pub mod random;
+pub mod search;
+pub mod spanish;
+pub mod warm_up;
+
+use std::sync::RwLock;
+
+use goose::prelude::*;
+use lazy_static::lazy_static;
+
+use crate::admin::{elect_invalidator, invalidate_caches, log_in as admin_log_in};
+use crate::common::set_client_ip;
+use crate::crawler::{crawl, set_user_agent};
+use crate::english::*;
+use crate::jsonapi::log_in as jsonapi_log_in;
+use crate::spanish::*;
+use crate::warm_up::warm_up;
+
+/// Short names of the task sets that load pages with common::get_page, for which cache
+/// busting can be configured from the command line.
+pub const CACHE_BUST_TASK_SETS: &[&str] = &["english", "spanish"];
+
+lazy_static! {
+    /// Short names of the registered task sets, by task_sets_index.
+    static ref TASK_SETS: RwLock<Vec<&'static str>> = RwLock::new(Vec::new());
+}
+
+/// Returns the short name of the task set with the index, ie "english", or an empty string
+/// if no such task set was registered.
+pub fn task_set_name(task_sets_index: usize) -> &'static str {
+    TASK_SETS
+        .read()
+        .unwrap()
+        .get(task_sets_index)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Register the task set under its short name. Goose indexes task sets in the order
+/// they're registered.
+fn register_taskset(
+    goose_attack: GooseAttack,
+    name: &'static str,
+    taskset: GooseTaskSet,
+) -> GooseAttack {
+    TASK_SETS.write().unwrap().push(name);
+    goose_attack.register_taskset(taskset)
+}
+
+/// Returns the minimum and maximum wait times that make a task set wait the interval in
+/// seconds between tasks. Goose picks a wait time up to but excluding the maximum, then
+/// sleeps one second longer, and can't pick from an empty range.
+fn wait_time(interval: usize) -> (usize, usize) {
+    (interval.saturating_sub(1), interval)
+}
+
+/// Like Goose's task! macro, but first gives the user a new random number generator for
+/// the task, so the task makes the same random choices with the same --seed whatever order
+/// Goose runs the user's tasks in.
+macro_rules! seeded_task {
+    ($task_func:ident) => {
+        GooseTask::new(std::sync::Arc::new(move |user| {
+            random::start_task(user, stringify!($task_func));
+            std::boxed::Box::pin($task_func(user))
+        }))
+    };
+}
+
+/// Registers the task sets that define the actual load test. Each task set simulates a
+/// type of user.
+///  - Admin user: with --invalidate, logs in and periodically invalidates caches. However
+///    many admin users Goose allocates, only one invalidates caches
+///  - Anonymous English user: loads the English version of all pages
+///  - Anonymous Spanish user: loads the Spanish version of all pages
+///  - Scanner: with --scanner, requests nonexistent paths in both languages
+///  - Crawler: with --crawler, crawls the site as a polite search engine crawler does
+///  - JSON:API client: with --jsonapi, reads content through JSON:API in both languages
+///  - JSON:API writer: with --jsonapi-write, creates, changes and deletes nodes through
+///    JSON:API in both languages
+///  - Feed reader: with --feeds, polls the RSS feeds of the front page and terms
+///  - Image style user: with --image-styles, requests image style derivatives in both
+///    languages, first as Drupal generates them then once generated
+///
+/// Goose allocates users to task sets in the order they're registered, as many as each
+/// weight, then starts over, so the optional task sets only get users with enough users.
+///
+/// With --warm-up, every page is loaded once before the users start. With --client-ip,
+/// each anonymous user sends its own client IP address.
+pub fn register_tasksets(goose_attack: GooseAttack) -> Result<GooseAttack, GooseError> {
+    let configuration = config::get_configuration();
+    TASK_SETS.write().unwrap().clear();
+    admin::reset_invalidator();
+    let goose_attack = if configuration.warm_up {
+        goose_attack.test_start(task!(warm_up).set_name("warm up"))
+    } else {
+        goose_attack
+    };
+
+    // With --invalidate, an admin user invalidates caches. It's registered first, so Goose
+    // allocates the first user to it however few users there are.
+    let goose_attack = if configuration.invalidate.is_some() {
+        let (min_wait, max_wait) = wait_time(configuration.invalidate_interval.unwrap_or(60));
+        register_taskset(
+            goose_attack,
+            "admin",
+            taskset!("Admin user")
+                .set_weight(1)?
+                .set_wait_time(min_wait, max_wait)?
+                .register_task(
+                    seeded_task!(elect_invalidator)
+                        .set_name("admin login")
+                        .set_on_start(),
+                )
+                .register_task(seeded_task!(invalidate_caches).set_name("admin invalidate caches")),
+        )
+    } else {
+        goose_attack
+    };
+
+    // With --client-ip, each anonymous user first sets the client IP address it sends.
+    let anonymous_user = |name: &str| {
+        let taskset = taskset!(name);
+        if configuration.client_ip_header.is_some() {
+            taskset.register_task(
+                seeded_task!(set_client_ip)
+                    .set_name("set client ip")
+                    .set_on_start(),
+            )
+        } else {
+            taskset
+        }
+    };
+
+    let goose_attack = register_taskset(
+        goose_attack,
+        "english",
+        anonymous_user("Anonymous English user")
+            .set_weight(6)?
+            .register_task(
+                seeded_task!(front_page_en)
+                    .set_name("anon /")
+                    .set_weight(2)?,
+            )
+            .register_task(seeded_task!(basic_page_en).set_name("anon /en/basicpage"))
+            .register_task(seeded_task!(article_listing_en).set_name("anon /en/articles/"))
+            .register_task(
+                seeded_task!(article_en)
+                    .set_name("anon /en/articles/%")
+                    .set_weight(2)?,
+            )
+            .register_task(seeded_task!(recipe_listing_en).set_name("anon /en/recipes/"))
+            .register_task(
+                seeded_task!(recipe_en)
+                    .set_name("anon /en/recipes/%")
+                    .set_weight(4)?,
+            )
+            .register_task(seeded_task!(page_by_nid).set_name("anon /node/%nid"))
+            .register_task(
+                seeded_task!(term_listing_en)
+                    .set_name("anon /en term")
+                    .set_weight(2)?,
+            )
+            .register_task(seeded_task!(listing_pager_en).set_name("anon /en listing pager"))
+            .register_task(seeded_task!(switch_language_en).set_name("anon /en switch language"))
+            .register_task(seeded_task!(search_en).set_name("anon /en/search"))
+            .register_task(seeded_task!(anonymous_contact_form_en).set_name("anon /en/contact")),
+    );
+    let goose_attack = register_taskset(
+        goose_attack,
+        "spanish",
+        anonymous_user("Anonymous Spanish user")
+            .set_weight(2)?
+            .register_task(
+                seeded_task!(front_page_es)
+                    .set_name("anon /es/")
+                    .set_weight(2)?,
+            )
+            .register_task(seeded_task!(basic_page_es).set_name("anon /es/basicpage"))
+            .register_task(seeded_task!(article_listing_es).set_name("anon /es/articles/"))
+            .register_task(
+                seeded_task!(article_es)
+                    .set_name("anon /es/articles/%")
+                    .set_weight(2)?,
+            )
+            .register_task(seeded_task!(recipe_listing_es).set_name("anon /es/recipes/"))
+            .register_task(
+                seeded_task!(recipe_es)
+                    .set_name("anon /es/recipes/%")
+                    .set_weight(4)?,
+            )
+            .register_task(seeded_task!(page_by_nid_es).set_name("anon /es/node/%nid"))
+            .register_task(seeded_task!(alias_escapes_es).set_name("anon /es alias escapes"))
+            .register_task(
+                seeded_task!(term_listing_es)
+                    .set_name("anon /es term")
+                    .set_weight(2)?,
+            )
+            .register_task(seeded_task!(listing_pager_es).set_name("anon /es listing pager"))
+            .register_task(seeded_task!(switch_language_es).set_name("anon /es switch language"))
+            .register_task(seeded_task!(search_es).set_name("anon /es/search"))
+            .register_task(seeded_task!(anonymous_contact_form_es).set_name("anon /es/contact")),
+    );
+
+    // With --scanner, a scanner probes for nonexistent paths.
+    let goose_attack = if configuration.scanner {
+        register_taskset(
+            goose_attack,
+            "scanner",
+            taskset!("Scanner")
+                .set_weight(1)?
+                .register_task(
+                    seeded_task!(scanner_en)
+                        .set_name("scanner /en 404")
+                        .set_weight(2)?,
+                )
+                .register_task(seeded_task!(scanner_es).set_name("scanner /es 404")),
+        )
+    } else {
+        goose_attack
+    };
+
+    // With --crawler, a crawler crawls the site with its own User-Agent.
+    let goose_attack = if configuration.crawler {
+        register_taskset(
+            goose_attack,
+            "crawler",
+            taskset!("Crawler")
+                .set_weight(1)?
+                .register_task(
+                    seeded_task!(set_user_agent)
+                        .set_name("crawler user agent")
+                        .set_on_start(),
+                )
+                .register_task(seeded_task!(crawl).set_name("crawler crawl")),
+        )
+    } else {
+        goose_attack
+    };
+
+    // With --jsonapi, a decoupled frontend reads content through JSON:API.
+    let goose_attack = if configuration.jsonapi {
+        register_taskset(
+            goose_attack,
+            "jsonapi",
+            taskset!("JSON:API client")
+                .set_weight(1)?
+                .register_task(
+                    seeded_task!(jsonapi_collection_en)
+                        .set_name("jsonapi /jsonapi collection")
+                        .set_weight(2)?,
+                )
+                .register_task(seeded_task!(jsonapi_filter_en).set_name("jsonapi /jsonapi filter"))
+                .register_task(
+                    seeded_task!(jsonapi_collection_es)
+                        .set_name("jsonapi /es/jsonapi collection")
+                        .set_weight(2)?,
+                )
+                .register_task(
+                    seeded_task!(jsonapi_filter_es).set_name("jsonapi /es/jsonapi filter"),
+                ),
+        )
+    } else {
+        goose_attack
+    };
+
+    // With --jsonapi-write, a headless publisher writes content through JSON:API.
+    let goose_attack = if configuration.jsonapi_write.is_some() {
+        register_taskset(
+            goose_attack,
+            "jsonapi-write",
+            taskset!("JSON:API writer")
+                .set_weight(1)?
+                .register_task(
+                    seeded_task!(jsonapi_log_in)
+                        .set_name("jsonapi writer login")
+                        .set_on_start(),
+                )
+                .register_task(
+                    seeded_task!(jsonapi_write_en)
+                        .set_name("jsonapi /jsonapi write")
+                        .set_weight(2)?,
+                )
+                .register_task(
+                    seeded_task!(jsonapi_write_es).set_name("jsonapi /es/jsonapi write"),
+                ),
+        )
+    } else {
+        goose_attack
+    };
+
+    // With --feeds, a feed reader polls RSS feeds on a schedule.
+    let goose_attack = if configuration.feeds {
+        let (min_wait, max_wait) = wait_time(configuration.feed_interval.unwrap_or(60));
+        register_taskset(
+            goose_attack,
+            "feeds",
+            taskset!("Feed reader")
+                .set_weight(1)?
+                .set_wait_time(min_wait, max_wait)?
+                .register_task(seeded_task!(feeds_en).set_name("feeds /en").set_weight(2)?)
+                .register_task(seeded_task!(feeds_es).set_name("feeds /es")),
+        )
+    } else {
+        goose_attack
+    };
+
+    // With --image-styles, a browser with a cold cache requests image style derivatives,
+    // after the admin user flushes their styles with --image-flush.
+    let goose_attack = if configuration.image_styles {
+        let taskset = taskset!("Image style user").set_weight(1)?;
+        let taskset = if configuration.image_flush {
+            taskset.register_task(
+                seeded_task!(admin_log_in)
+                    .set_name("images admin login")
+                    .set_on_start(),
+            )
+        } else {
+            taskset
+        };
+        register_taskset(
+            goose_attack,
+            "images",
+            taskset
+                .register_task(
+                    seeded_task!(images_en)
+                        .set_name("images /en")
+                        .set_weight(2)?,
+                )
+                .register_task(seeded_task!(images_es).set_name("images /es")),
+        )
+    } else {
+        goose_attack
+    };
+
+    Ok(goose_attack)
+}
@@ -1,63 +1,50 @@
-mod common;
-mod english;
-mod spanish;
-
 use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
 
-use crate::english::*;
-use crate::spanish::*;
+use umami_loadtest::{assertions, config, metrics};
 
-/// Defines the actual load test. Each task set simulates a type of user.
-///  - Anonymous English user: loads the English version of all pages
-///  - Anonymous Spanish user: loads the Spanish version of all pages
+/// Parses the command line and runs the load test defined in lib.rs.
 fn main() -> Result<(), GooseError> {
-    let _goose_metrics = GooseAttack::initialize()?
-        .register_taskset(
-            taskset!("Anonymous English user")
-                .set_weight(6)?
-                .register_task(task!(front_page_en).set_name("anon /").set_weight(2)?)
-                .register_task(task!(basic_page_en).set_name("anon /en/basicpage"))
-                .register_task(task!(article_listing_en).set_name("anon /en/articles/"))
-                .register_task(
-                    task!(article_en)
-                        .set_name("anon /en/articles/%")
-                        .set_weight(2)?,
-                )
-                .register_task(task!(recipe_listing_en).set_name("anon /en/recipes/"))
-                .register_task(
-                    task!(recipe_en)
-                        .set_name("anon /en/recipes/%")
-                        .set_weight(4)?,
-                )
-                .register_task(task!(page_by_nid).set_name("anon /node/%nid"))
-                .register_task(task!(term_listing_en).set_name("anon /en term").set_weight(2)?)
-                .register_task(task!(search_en).set_name("anon /en/search"))
-                .register_task(task!(anonymous_contact_form_en).set_name("anon /en/contact")),
-        )
-        .register_taskset(
-            taskset!("Anonymous Spanish user")
-                .set_weight(2)?
-                .register_task(task!(front_page_es).set_name("anon /es/").set_weight(2)?)
-                .register_task(task!(basic_page_es).set_name("anon /es/basicpage"))
-                .register_task(task!(article_listing_es).set_name("anon /es/articles/"))
-                .register_task(
-                    task!(article_es)
-                        .set_name("anon /es/articles/%")
-                        .set_weight(2)?,
-                )
-                .register_task(task!(recipe_listing_es).set_name("anon /es/recipes/"))
-                .register_task(
-                    task!(recipe_es)
-                        .set_name("anon /es/recipes/%")
-                        .set_weight(4)?,
-                )
-                .register_task(task!(term_listing_es).set_name("anon /es term").set_weight(2)?)
-                .register_task(task!(search_es).set_name("anon /es/search"))
-                .register_task(task!(anonymous_contact_form_es).set_name("anon /es/contact")),
-        )
+    // Separate the Umami options from the Goose options.
+    let (mut configuration, goose_args) = match config::parse_args(std::env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let goose_configuration = match GooseConfiguration::parse_args_default(&goose_args) {
+        Ok(goose_configuration) => goose_configuration,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    if goose_configuration.help {
+        println!("{}\n\n{}", GooseConfiguration::usage(), config::usage());
+        std::process::exit(0);
+    }
+
+    if let Some(assertions_file) = &configuration.assertions_file {
+        if let Err(e) = assertions::load_assertions_file(assertions_file) {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+
+    // Always run with a seed, so any run can be replayed.
+    let seed = configuration.seed.unwrap_or_else(rand::random);
+    println!("Random seed: {} (replay with --seed {})", seed, seed);
+    configuration.seed = Some(seed);
+    config::set_configuration(configuration);
+
+    let goose_attack = GooseAttack::initialize_with_config(goose_configuration)?;
+    umami_loadtest::register_tasksets(goose_attack)?
         .set_default(GooseDefault::Host, "https://drupal-9.0.7.ddev.site/")?
         .execute()?
         .print();
+    print!("{}", metrics::get_metrics());
 
     Ok(())
 }
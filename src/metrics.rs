@@ -0,0 +1,759 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use lazy_static::lazy_static;
+
+use crate::assertions::AssertionFailure;
+use crate::cache::{CacheResult, CacheStatus};
+use crate::common::PageType;
+use crate::search::QueryType;
+
+lazy_static! {
+    /// Metrics collected by the load test that Goose doesn't track itself.
+    static ref METRICS: Mutex<UmamiMetrics> = Mutex::new(UmamiMetrics::default());
+}
+
+/// After caches are invalidated, page loads are grouped by how many seconds have passed,
+/// to show how quickly response times recover.
+const RECOVERY_WINDOWS: &[u64] = &[1, 2, 5, 10, 30, 60];
+
+const SEPARATOR: &str =
+    " ------------------------------------------------------------------------------";
+
+/// Response times in milliseconds.
+#[derive(Clone, Debug, Default)]
+pub struct ResponseTimes {
+    pub count: usize,
+    pub total: u64,
+    pub min: u64,
+    pub max: u64,
+}
+
+impl ResponseTimes {
+    fn record(&mut self, response_time: u64) {
+        if self.count == 0 || response_time < self.min {
+            self.min = response_time;
+        }
+        if response_time > self.max {
+            self.max = response_time;
+        }
+        self.count += 1;
+        self.total += response_time;
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total as f64 / self.count as f64
+        }
+    }
+}
+
+/// How many requests were served by each cache layer.
+#[derive(Clone, Debug, Default)]
+pub struct CacheHits {
+    pub requests: usize,
+    pub hits: usize,
+    pub page_cache_hits: usize,
+    pub dynamic_cache_hits: usize,
+    pub varnish_hits: usize,
+}
+
+/// Returns part of a total as a percentage.
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Metrics that Goose doesn't track, printed after the Goose metrics.
+#[derive(Clone, Debug, Default)]
+pub struct UmamiMetrics {
+    /// Response times per request name, split by whether the response was cached.
+    pub cache_response_times: BTreeMap<(String, CacheResult), ResponseTimes>,
+    /// Cache hits per page type and language.
+    pub cache_hits: BTreeMap<(String, String), CacheHits>,
+    /// Metrics collected during the warm-up, if there was one.
+    pub warm_up: Option<Box<WarmUpMetrics>>,
+    /// When the first page was loaded or caches were invalidated.
+    pub started: Option<Instant>,
+    /// Caches invalidated by the admin user.
+    pub invalidations: Vec<InvalidationEvent>,
+    /// Page loads after caches were invalidated, indexed by RECOVERY_WINDOWS.
+    pub recovery: BTreeMap<usize, RecoveryWindow>,
+    /// Searches per language and type of query.
+    pub searches: BTreeMap<(String, QueryType), SearchStats>,
+    /// Contact form submissions per language.
+    pub contact: BTreeMap<String, ContactStats>,
+    /// Response times of nodes loaded by nid per language, split by whether Drupal
+    /// redirected to the alias or served the node directly.
+    pub node_paths: BTreeMap<(String, String), ResponseTimes>,
+    /// Response times of 404 pages served to the scanner per language, split by whether
+    /// the path was from the list of probes or random.
+    pub not_found: BTreeMap<(String, String), ResponseTimes>,
+    /// Pages loaded by the crawler per language, split by whether they were discovered
+    /// from a sitemap or by following links.
+    pub crawler: BTreeMap<(String, String), CrawlerStats>,
+    /// Feeds polled by feed readers per language, split between the front page feed and
+    /// term feeds.
+    pub feeds: BTreeMap<(String, String), FeedStats>,
+    /// Response times of image style derivatives per style, split by whether Drupal
+    /// generated the derivative or it had already been generated.
+    pub images: BTreeMap<(String, String), ResponseTimes>,
+    /// How many times image styles were flushed before their derivatives were requested.
+    pub image_flushes: usize,
+    /// How many pages failed each assertion per page type.
+    pub assertion_failures: BTreeMap<(String, String), usize>,
+}
+
+/// Pages crawled, and discovered pages robots.txt disallowed crawling.
+#[derive(Clone, Debug, Default)]
+pub struct CrawlerStats {
+    pub pages: usize,
+    pub cache_hits: usize,
+    pub disallowed: usize,
+    pub response_times: ResponseTimes,
+}
+
+/// Feeds polled, and how many conditional polls were answered with 304 Not Modified.
+#[derive(Clone, Debug, Default)]
+pub struct FeedStats {
+    pub polls: usize,
+    /// Polls sending the ETag or Last-Modified date of an earlier response.
+    pub conditional: usize,
+    pub not_modified: usize,
+    /// Items in the feeds returned in full.
+    pub items: usize,
+    pub response_times: ResponseTimes,
+}
+
+/// Contact form submissions, and how many Drupal's flood control throttled.
+#[derive(Clone, Debug, Default)]
+pub struct ContactStats {
+    pub submissions: usize,
+    pub throttled: usize,
+}
+
+/// Searches performed, and the results they found.
+#[derive(Clone, Debug, Default)]
+pub struct SearchStats {
+    pub searches: usize,
+    /// Searches that found nothing.
+    pub no_results: usize,
+    /// Results listed on all pages of results loaded.
+    pub results: usize,
+    /// Pages of results loaded, including the first.
+    pub pages: usize,
+    /// Searches where a result was clicked.
+    pub clicks: usize,
+}
+
+/// Caches invalidated by the admin user.
+#[derive(Clone, Debug)]
+pub struct InvalidationEvent {
+    pub at: Instant,
+    /// How the caches were invalidated, ie "clear all caches".
+    pub description: String,
+}
+
+/// Page loads within a window of time after caches were invalidated.
+#[derive(Clone, Debug, Default)]
+pub struct RecoveryWindow {
+    pub response_times: ResponseTimes,
+    pub hits: usize,
+}
+
+/// Metrics collected while warming up caches, kept separate from the measured run.
+#[derive(Clone, Debug, Default)]
+pub struct WarmUpMetrics {
+    pub pages: usize,
+    pub static_assets: usize,
+    pub failures: usize,
+    /// How long the warm-up took, in milliseconds.
+    pub elapsed: u64,
+    /// The cache metrics of the pages loaded during the warm-up.
+    pub metrics: UmamiMetrics,
+}
+
+/// Record the cache status of a page and how long it took to load.
+pub fn record_cache_status(
+    name: &str,
+    page_type: &PageType,
+    language: &str,
+    status: &CacheStatus,
+    response_time: u64,
+) {
+    let result = status.result();
+    let mut metrics = METRICS.lock().unwrap();
+    let now = Instant::now();
+    metrics.started.get_or_insert(now);
+
+    if let Some(invalidation) = metrics.invalidations.last() {
+        let seconds = now.duration_since(invalidation.at).as_secs();
+        let window = RECOVERY_WINDOWS
+            .iter()
+            .position(|window| seconds < *window)
+            .unwrap_or_else(|| RECOVERY_WINDOWS.len());
+        let recovery = metrics.recovery.entry(window).or_default();
+        recovery.response_times.record(response_time);
+        if result == CacheResult::Hit {
+            recovery.hits += 1;
+        }
+    }
+
+    metrics
+        .cache_response_times
+        .entry((name.to_string(), result))
+        .or_default()
+        .record(response_time);
+
+    let hits = metrics
+        .cache_hits
+        .entry((page_type.to_string(), language.to_string()))
+        .or_default();
+    hits.requests += 1;
+    if result == CacheResult::Hit {
+        hits.hits += 1;
+    }
+    if status.page_cache == Some(CacheResult::Hit) {
+        hits.page_cache_hits += 1;
+    }
+    if status.dynamic_cache == Some(CacheResult::Hit) {
+        hits.dynamic_cache_hits += 1;
+    }
+    if status.varnish == Some(CacheResult::Hit) {
+        hits.varnish_hits += 1;
+    }
+}
+
+/// Record that caches were invalidated, so the page loads that follow are grouped by how
+/// long after the invalidation they happened.
+pub fn record_invalidation(description: &str) {
+    let mut metrics = METRICS.lock().unwrap();
+    let now = Instant::now();
+    metrics.started.get_or_insert(now);
+    metrics.invalidations.push(InvalidationEvent {
+        at: now,
+        description: description.to_string(),
+    });
+}
+
+/// Record a search, with the number of results listed on the pages of results loaded.
+pub fn record_search(
+    language: &str,
+    query_type: QueryType,
+    results: usize,
+    pages: usize,
+    clicked: bool,
+) {
+    let mut metrics = METRICS.lock().unwrap();
+    let search = metrics
+        .searches
+        .entry((language.to_string(), query_type))
+        .or_default();
+    search.searches += 1;
+    if results == 0 {
+        search.no_results += 1;
+    }
+    search.results += results;
+    search.pages += pages;
+    if clicked {
+        search.clicks += 1;
+    }
+}
+
+/// Record a contact form submission, and whether it was throttled.
+pub fn record_contact(language: &str, throttled: bool) {
+    let mut metrics = METRICS.lock().unwrap();
+    let contact = metrics.contact.entry(language.to_string()).or_default();
+    contact.submissions += 1;
+    if throttled {
+        contact.throttled += 1;
+    }
+}
+
+/// Record a 404 page served to the scanner, and whether the path probed was random.
+pub fn record_not_found(language: &str, random: bool, response_time: u64) {
+    let probe = if random { "random" } else { "listed" };
+    METRICS
+        .lock()
+        .unwrap()
+        .not_found
+        .entry((language.to_string(), probe.to_string()))
+        .or_default()
+        .record(response_time);
+}
+
+/// Returns the crawler metrics of pages in the language, discovered from a sitemap or
+/// by following links.
+fn crawler_stats<'a>(
+    metrics: &'a mut UmamiMetrics,
+    language: &str,
+    sitemap: bool,
+) -> &'a mut CrawlerStats {
+    let source = if sitemap { "sitemap" } else { "links" };
+    metrics
+        .crawler
+        .entry((language.to_string(), source.to_string()))
+        .or_default()
+}
+
+/// Record a page loaded by the crawler, and whether it was served from a cache.
+pub fn record_crawl(language: &str, sitemap: bool, cache_hit: bool, response_time: u64) {
+    let mut metrics = METRICS.lock().unwrap();
+    let crawler = crawler_stats(&mut metrics, language, sitemap);
+    crawler.pages += 1;
+    if cache_hit {
+        crawler.cache_hits += 1;
+    }
+    crawler.response_times.record(response_time);
+}
+
+/// Record a page the crawler discovered but didn't load, because robots.txt disallows it.
+pub fn record_crawl_disallowed(language: &str, sitemap: bool) {
+    let mut metrics = METRICS.lock().unwrap();
+    crawler_stats(&mut metrics, language, sitemap).disallowed += 1;
+}
+
+/// Record a feed polled by a feed reader, whether the poll was conditional, and whether
+/// the feed was returned in full or was not modified.
+pub fn record_feed(
+    language: &str,
+    feed: &str,
+    conditional: bool,
+    not_modified: bool,
+    items: usize,
+    response_time: u64,
+) {
+    let mut metrics = METRICS.lock().unwrap();
+    let feeds = metrics
+        .feeds
+        .entry((language.to_string(), feed.to_string()))
+        .or_default();
+    feeds.polls += 1;
+    if conditional {
+        feeds.conditional += 1;
+    }
+    if not_modified {
+        feeds.not_modified += 1;
+    }
+    feeds.items += items;
+    feeds.response_times.record(response_time);
+}
+
+/// Record an image style derivative, and whether it was generated by the request.
+pub fn record_image(style: &str, generated: bool, response_time: u64) {
+    let derivative = if generated { "generated" } else { "cached" };
+    METRICS
+        .lock()
+        .unwrap()
+        .images
+        .entry((style.to_string(), derivative.to_string()))
+        .or_default()
+        .record(response_time);
+}
+
+/// Record that an image style was flushed.
+pub fn record_image_flush() {
+    METRICS.lock().unwrap().image_flushes += 1;
+}
+
+/// Record a page of the given type that failed an assertion.
+pub fn record_assertion_failure(page_type: &PageType, failure: &AssertionFailure) {
+    *METRICS
+        .lock()
+        .unwrap()
+        .assertion_failures
+        .entry((page_type.to_string(), failure.to_string()))
+        .or_default() += 1;
+}
+
+/// Record a node loaded by nid, and whether Drupal redirected to its alias.
+pub fn record_node_path(language: &str, redirected: bool, response_time: u64) {
+    let resolution = if redirected { "redirect" } else { "direct" };
+    METRICS
+        .lock()
+        .unwrap()
+        .node_paths
+        .entry((language.to_string(), resolution.to_string()))
+        .or_default()
+        .record(response_time);
+}
+
+/// Returns a copy of the metrics collected so far.
+pub fn get_metrics() -> UmamiMetrics {
+    METRICS.lock().unwrap().clone()
+}
+
+/// Set aside the metrics collected so far as the warm-up metrics, so the measured run
+/// starts from zero.
+pub fn end_warm_up(mut warm_up: WarmUpMetrics) {
+    let mut metrics = METRICS.lock().unwrap();
+    warm_up.metrics = std::mem::take(&mut *metrics);
+    metrics.warm_up = Some(Box::new(warm_up));
+}
+
+impl UmamiMetrics {
+    /// Write the cache response times and hit ratios, if any pages were loaded.
+    fn write_cache_metrics(&self, f: &mut fmt::Formatter, title: &str) -> fmt::Result {
+        if self.cache_response_times.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === {} ===", title)?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<24} | {:<11} | {:>7} | {:>9} | {:>7} | {:>7}",
+            "Name", "Cache", "# reqs", "Average", "Min", "Max"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((name, result), times) in &self.cache_response_times {
+            writeln!(
+                f,
+                " {:<24} | {:<11} | {:>7} | {:>9.2} | {:>7} | {:>7}",
+                name,
+                result.to_string(),
+                times.count,
+                times.average(),
+                times.min,
+                times.max
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<16} | {:<4} | {:>7} | {:>7} | {:>10} | {:>9} | {:>7}",
+            "Page type", "Lang", "# reqs", "Hit %", "Page cache", "Dynamic", "Varnish"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((page_type, language), hits) in &self.cache_hits {
+            writeln!(
+                f,
+                " {:<16} | {:<4} | {:>7} | {:>7.2} | {:>10.2} | {:>9.2} | {:>7.2}",
+                page_type,
+                language,
+                hits.requests,
+                percentage(hits.hits, hits.requests),
+                percentage(hits.page_cache_hits, hits.requests),
+                percentage(hits.dynamic_cache_hits, hits.requests),
+                percentage(hits.varnish_hits, hits.requests)
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write when caches were invalidated, and how page loads recovered afterwards.
+    fn write_invalidation_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let started = match (self.started, self.invalidations.is_empty()) {
+            (Some(started), false) => started,
+            _ => return Ok(()),
+        };
+
+        writeln!(f, "\n === CACHE INVALIDATIONS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(f, " {:>10} | Invalidation", "Time")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for invalidation in &self.invalidations {
+            writeln!(
+                f,
+                " {:>9.2}s | {}",
+                invalidation.at.duration_since(started).as_secs_f64(),
+                invalidation.description
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<18} | {:>7} | {:>9} | {:>7} | {:>7}",
+            "Since invalidation", "# reqs", "Average", "Max", "Hit %"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for (window, recovery) in &self.recovery {
+            let label = match (window.checked_sub(1), RECOVERY_WINDOWS.get(*window)) {
+                (None, Some(end)) => format!("0-{}s", end),
+                (Some(start), Some(end)) => format!("{}-{}s", RECOVERY_WINDOWS[start], end),
+                _ => format!("{}s+", RECOVERY_WINDOWS[RECOVERY_WINDOWS.len() - 1]),
+            };
+            writeln!(
+                f,
+                " {:<18} | {:>7} | {:>9.2} | {:>7} | {:>7.2}",
+                label,
+                recovery.response_times.count,
+                recovery.response_times.average(),
+                recovery.response_times.max,
+                percentage(recovery.hits, recovery.response_times.count)
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write how many searches found results, and how many results they found.
+    fn write_search_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.searches.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === SEARCH METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<4} | {:<10} | {:>8} | {:>12} | {:>7} | {:>5} | {:>8}",
+            "Lang", "Query", "Searches", "No results %", "Results", "Pages", "Clicks %"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((language, query_type), search) in &self.searches {
+            writeln!(
+                f,
+                " {:<4} | {:<10} | {:>8} | {:>12.2} | {:>7.2} | {:>5.2} | {:>8.2}",
+                language,
+                query_type.to_string(),
+                search.searches,
+                percentage(search.no_results, search.searches),
+                search.results as f64 / search.searches as f64,
+                search.pages as f64 / search.searches as f64,
+                percentage(search.clicks, search.searches)
+            )?;
+        }
+        writeln!(f, " Results and pages are averages per search.")?;
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write the response times of nodes loaded by nid, redirected or served directly.
+    fn write_node_path_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.node_paths.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === NODE PATH METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<4} | {:<10} | {:>7} | {:>9} | {:>7} | {:>7}",
+            "Lang", "Resolution", "# reqs", "Average", "Min", "Max"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((language, resolution), times) in &self.node_paths {
+            writeln!(
+                f,
+                " {:<4} | {:<10} | {:>7} | {:>9.2} | {:>7} | {:>7}",
+                language,
+                resolution,
+                times.count,
+                times.average(),
+                times.min,
+                times.max
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write the response times of 404 pages. Random paths are never in Drupal's page
+    /// cache, so each one is fully rendered.
+    fn write_not_found_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.not_found.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === 404 METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<4} | {:<6} | {:>7} | {:>9} | {:>7} | {:>7}",
+            "Lang", "Probe", "# reqs", "Average", "Min", "Max"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((language, probe), times) in &self.not_found {
+            writeln!(
+                f,
+                " {:<4} | {:<6} | {:>7} | {:>9.2} | {:>7} | {:>7}",
+                language,
+                probe,
+                times.count,
+                times.average(),
+                times.min,
+                times.max
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write the pages loaded by the crawler, kept apart from the other users' pages.
+    fn write_crawler_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.crawler.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === CRAWLER METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<4} | {:<7} | {:>7} | {:>6} | {:>10} | {:>9} | {:>7}",
+            "Lang", "Source", "Pages", "Hits %", "Disallowed", "Average", "Max"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((language, source), crawler) in &self.crawler {
+            writeln!(
+                f,
+                " {:<4} | {:<7} | {:>7} | {:>6.2} | {:>10} | {:>9.2} | {:>7}",
+                language,
+                source,
+                crawler.pages,
+                percentage(crawler.cache_hits, crawler.pages),
+                crawler.disallowed,
+                crawler.response_times.average(),
+                crawler.response_times.max
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write how often feeds were polled, and how many conditional polls were answered
+    /// with 304 Not Modified.
+    fn write_feed_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.feeds.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === FEED METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<4} | {:<5} | {:>7} | {:>11} | {:>5} | {:>5} | {:>9} | {:>7}",
+            "Lang", "Feed", "Polls", "Conditional", "304 %", "Items", "Average", "Max"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((language, feed), feeds) in &self.feeds {
+            writeln!(
+                f,
+                " {:<4} | {:<5} | {:>7} | {:>11} | {:>5.2} | {:>5.2} | {:>9.2} | {:>7}",
+                language,
+                feed,
+                feeds.polls,
+                feeds.conditional,
+                percentage(feeds.not_modified, feeds.conditional),
+                feeds.items as f64 / (feeds.polls - feeds.not_modified).max(1) as f64,
+                feeds.response_times.average(),
+                feeds.response_times.max
+            )?;
+        }
+        writeln!(
+            f,
+            " 304 % is of conditional polls, items are averages per full feed."
+        )?;
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write the response times of image style derivatives, keeping the derivatives Drupal
+    /// generated apart from those it had already generated.
+    fn write_image_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.images.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === IMAGE STYLE METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<20} | {:<10} | {:>7} | {:>9} | {:>7} | {:>7}",
+            "Style", "Derivative", "# reqs", "Average", "Min", "Max"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((style, derivative), times) in &self.images {
+            writeln!(
+                f,
+                " {:<20} | {:<10} | {:>7} | {:>9.2} | {:>7} | {:>7}",
+                style,
+                derivative,
+                times.count,
+                times.average(),
+                times.min,
+                times.max
+            )?;
+        }
+        if self.image_flushes > 0 {
+            writeln!(
+                f,
+                " Image styles were flushed {} times.",
+                self.image_flushes
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write how many pages of each type failed each assertion.
+    fn write_assertion_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.assertion_failures.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === ASSERTION METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(f, " {:<15} | {:>8} | Assertion", "Page type", "Failures")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for ((page_type, failure), count) in &self.assertion_failures {
+            writeln!(f, " {:<15} | {:>8} | {}", page_type, count, failure)?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+
+    /// Write how many contact form submissions were throttled by flood control.
+    fn write_contact_metrics(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.contact.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "\n === CONTACT FORM METRICS ===")?;
+        writeln!(f, "{}", SEPARATOR)?;
+        writeln!(
+            f,
+            " {:<4} | {:>11} | {:>9} | {:>11}",
+            "Lang", "Submissions", "Throttled", "Throttled %"
+        )?;
+        writeln!(f, "{}", SEPARATOR)?;
+        for (language, contact) in &self.contact {
+            writeln!(
+                f,
+                " {:<4} | {:>11} | {:>9} | {:>11.2}",
+                language,
+                contact.submissions,
+                contact.throttled,
+                percentage(contact.throttled, contact.submissions)
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)
+    }
+}
+
+impl fmt::Display for UmamiMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(warm_up) = &self.warm_up {
+            writeln!(f, "\n === WARM-UP METRICS ===")?;
+            writeln!(f, "{}", SEPARATOR)?;
+            writeln!(f, " Pages: {}", warm_up.pages)?;
+            writeln!(f, " Static assets: {}", warm_up.static_assets)?;
+            writeln!(f, " Failures: {}", warm_up.failures)?;
+            writeln!(f, " Duration: {:.2}s", warm_up.elapsed as f64 / 1000.0)?;
+            writeln!(f, "{}", SEPARATOR)?;
+            warm_up
+                .metrics
+                .write_cache_metrics(f, "WARM-UP CACHE METRICS")?;
+        }
+
+        self.write_cache_metrics(f, "CACHE METRICS")?;
+        self.write_assertion_metrics(f)?;
+        self.write_invalidation_metrics(f)?;
+        self.write_search_metrics(f)?;
+        self.write_contact_metrics(f)?;
+        self.write_node_path_metrics(f)?;
+        self.write_not_found_metrics(f)?;
+        self.write_crawler_metrics(f)?;
+        self.write_feed_metrics(f)?;
+        self.write_image_metrics(f)
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+use goose::prelude::*;
+use lazy_static::lazy_static;
+use rand::rngs::StdRng;
+use rand::{Error, RngCore, SeedableRng};
+
+use crate::config;
+
+lazy_static! {
+    /// The seed every generator is derived from, read from the configuration the first
+    /// time a generator is needed.
+    static ref SEED: u64 = config::get_configuration().seed.unwrap_or_default();
+    /// The random number generator of each GooseUser, indexed by weighted_users_index. A
+    /// user only ever locks its own generator, the map is only written to when a user
+    /// needs a generator for the first time.
+    static ref USER_RNGS: RwLock<HashMap<usize, Mutex<UserState>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// The random number generator of a single GooseUser, and how many times the user started
+/// each task.
+struct UserState {
+    rng: StdRng,
+    invocations: HashMap<&'static str, u64>,
+}
+
+/// A random number generator belonging to a single GooseUser. A new generator is derived
+/// from the configured seed every time the user starts a task, so the task makes the same
+/// choices every time the load test is run with the same seed, whatever order Goose runs
+/// the user's tasks in.
+pub struct UserRng {
+    index: usize,
+}
+
+/// Returns the random number generator for the given user. Use it anywhere a task would
+/// otherwise use `rand::thread_rng()`.
+pub fn user_rng(user: &GooseUser) -> UserRng {
+    UserRng {
+        index: user.weighted_users_index,
+    }
+}
+
+/// Give the user a new generator for the named task, derived from the seed, the user, the
+/// task and how many times the user started the task before. Called by `seeded_task!`
+/// before every task runs.
+pub fn start_task(user: &GooseUser, task: &'static str) {
+    let index = user.weighted_users_index;
+    with_state(index, |state| {
+        let invocation = state.invocations.entry(task).or_default();
+        state.rng = StdRng::seed_from_u64(task_seed(*SEED, index, task, *invocation));
+        *invocation += 1;
+    });
+}
+
+/// Derive a well-distributed seed for a user from the global seed, using the SplitMix64
+/// finalizer so neighbouring users don't get correlated sequences.
+pub fn derive_seed(seed: u64, index: usize) -> u64 {
+    let mut z = seed.wrapping_add((index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Derive the seed of a user's generator for the n-th invocation of a task, counting from
+/// zero, from the global seed.
+pub fn task_seed(seed: u64, index: usize, task: &str, invocation: u64) -> u64 {
+    derive_seed(derive_seed(seed, index) ^ hash(task), invocation as usize)
+}
+
+/// Hash a task name with FNV-1a, which unlike the standard library's hasher gives the same
+/// result with every build.
+fn hash(name: &str) -> u64 {
+    name.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// Run the closure with the user's state, creating it on first use. The user's lock is
+/// only held for the duration of a single call so the generator is never held across an
+/// await point.
+fn with_state<T>(index: usize, f: impl FnOnce(&mut UserState) -> T) -> T {
+    if !USER_RNGS.read().unwrap().contains_key(&index) {
+        USER_RNGS.write().unwrap().entry(index).or_insert_with(|| {
+            Mutex::new(UserState {
+                rng: StdRng::seed_from_u64(derive_seed(*SEED, index)),
+                invocations: HashMap::new(),
+            })
+        });
+    }
+    let users = USER_RNGS.read().unwrap();
+    let mut state = users[&index].lock().unwrap();
+    f(&mut state)
+}
+
+impl UserRng {
+    fn with_rng<T>(&mut self, f: impl FnOnce(&mut StdRng) -> T) -> T {
+        with_state(self.index, |state| f(&mut state.rng))
+    }
+}
+
+impl RngCore for UserRng {
+    fn next_u32(&mut self) -> u32 {
+        self.with_rng(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.with_rng(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.with_rng(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.with_rng(|rng| rng.try_fill_bytes(dest))
+    }
+}
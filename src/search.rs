@@ -0,0 +1,236 @@
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::common::{get_nodes, ContentType};
+
+/// How often each type of query is generated, unless configured with --search-mix.
+pub const DEFAULT_MIX: &[(QueryType, usize)] = &[
+    (QueryType::Word, 4),
+    (QueryType::Phrase, 2),
+    (QueryType::Typo, 1),
+    (QueryType::StopWords, 1),
+    (QueryType::Accents, 1),
+    (QueryType::NoResults, 1),
+];
+
+/// Words commonly added to searches, that don't appear in the titles being searched for.
+const STOP_WORDS_EN: &[&str] = &["the", "how to make", "recipes with", "what is", "best"];
+const STOP_WORDS_ES: &[&str] = &["el", "la", "cómo hacer", "recetas con", "qué es"];
+
+/// The types of search queries users make.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum QueryType {
+    /// A single word from a node title.
+    Word,
+    /// Two or three consecutive words from a node title.
+    Phrase,
+    /// A word from a node title with a typo.
+    Typo,
+    /// A word from a node title, with stop words added.
+    StopWords,
+    /// A word from a Spanish node title, with its accents removed, ie "tailandes" for
+    /// "tailandés".
+    Accents,
+    /// Random letters that won't match anything.
+    NoResults,
+}
+
+impl fmt::Display for QueryType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            QueryType::Word => "word",
+            QueryType::Phrase => "phrase",
+            QueryType::Typo => "typo",
+            QueryType::StopWords => "stop_words",
+            QueryType::Accents => "accents",
+            QueryType::NoResults => "no_results",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for QueryType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "word" => Ok(QueryType::Word),
+            "phrase" => Ok(QueryType::Phrase),
+            "typo" => Ok(QueryType::Typo),
+            "stop_words" => Ok(QueryType::StopWords),
+            "accents" => Ok(QueryType::Accents),
+            "no_results" => Ok(QueryType::NoResults),
+            _ => Err(format!("unknown query type: {}", s)),
+        }
+    }
+}
+
+/// Parse a comma separated list of query types and how often to generate them, ie
+/// "word=4,typo=1". Query types that aren't listed aren't generated.
+pub fn parse_mix(mix: &str) -> Result<Vec<(QueryType, usize)>, String> {
+    let mix = mix
+        .split(',')
+        .map(|entry| {
+            let mut parts = entry.splitn(2, '=');
+            let query_type = parts.next().unwrap_or_default().trim().parse()?;
+            let weight = parts
+                .next()
+                .and_then(|weight| weight.trim().parse().ok())
+                .ok_or_else(|| format!("invalid weight for {}", query_type))?;
+            Ok((query_type, weight))
+        })
+        .collect::<Result<Vec<(QueryType, usize)>, String>>()?;
+    if mix.iter().all(|(_, weight)| *weight == 0) {
+        return Err("search mix has no weights".to_string());
+    }
+    Ok(mix)
+}
+
+/// A generated search query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    pub query_type: QueryType,
+    pub keys: String,
+}
+
+/// Randomly select a node title, favoring articles and recipes.
+pub fn random_title<R: Rng + ?Sized>(rng: &mut R, english: bool) -> String {
+    let content_types = [
+        ContentType::Article,
+        ContentType::Article,
+        ContentType::Article,
+        ContentType::BasicPage,
+        ContentType::Recipe,
+        ContentType::Recipe,
+        ContentType::Recipe,
+    ];
+    let content_type = content_types.choose(rng).unwrap();
+    let nodes = get_nodes(content_type);
+    let node = nodes.choose(rng).unwrap();
+    if english {
+        node.title_en.to_string()
+    } else {
+        node.title_es.to_string()
+    }
+}
+
+/// Randomly select a word from a node title.
+fn random_word<R: Rng + ?Sized>(rng: &mut R, english: bool) -> String {
+    let title = random_title(rng, english);
+    let words: Vec<&str> = title.split_whitespace().collect();
+    words.choose(rng).unwrap().to_string()
+}
+
+/// Remove Spanish accents from a word.
+pub fn remove_accents(word: &str) -> String {
+    word.chars()
+        .map(|c| match c {
+            'á' => 'a',
+            'é' => 'e',
+            'í' => 'i',
+            'ó' => 'o',
+            'ú' | 'ü' => 'u',
+            'Á' => 'A',
+            'É' => 'E',
+            'Í' => 'I',
+            'Ó' => 'O',
+            'Ú' => 'U',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Make a typo in a word, by swapping, dropping or doubling a letter.
+fn make_typo<R: Rng + ?Sized>(rng: &mut R, word: &str) -> String {
+    let mut letters: Vec<char> = word.chars().collect();
+    if letters.len() < 2 {
+        return word.to_string();
+    }
+    let index = rng.gen_range(0, letters.len() - 1);
+    match rng.gen_range(0, 3) {
+        0 => letters.swap(index, index + 1),
+        1 => {
+            letters.remove(index);
+        }
+        _ => letters.insert(index, letters[index]),
+    }
+    letters.into_iter().collect()
+}
+
+/// Generate a query of the given type.
+pub fn generate<R: Rng + ?Sized>(rng: &mut R, english: bool, query_type: QueryType) -> String {
+    match query_type {
+        QueryType::Word => random_word(rng, english),
+        QueryType::Phrase => {
+            let title = random_title(rng, english);
+            let words: Vec<&str> = title.split_whitespace().collect();
+            let length = rng.gen_range(2, 4).min(words.len());
+            let start = rng.gen_range(0, words.len() - length + 1);
+            words[start..start + length].join(" ")
+        }
+        QueryType::Typo => {
+            // Typos in short words are more likely to match other words.
+            let mut word = random_word(rng, english);
+            for _ in 0..5 {
+                if word.chars().count() >= 4 {
+                    break;
+                }
+                word = random_word(rng, english);
+            }
+            make_typo(rng, &word)
+        }
+        QueryType::StopWords => {
+            let stop_words = if english {
+                STOP_WORDS_EN
+            } else {
+                STOP_WORDS_ES
+            };
+            format!(
+                "{} {}",
+                stop_words.choose(rng).unwrap(),
+                random_word(rng, english)
+            )
+        }
+        // English titles have no accents, so search for a word as typed.
+        QueryType::Accents if english => random_word(rng, english),
+        QueryType::Accents => {
+            // Look for a word with an accent, so it's searched for without one.
+            for _ in 0..10 {
+                let word = random_word(rng, false);
+                let unaccented = remove_accents(&word);
+                if unaccented != word {
+                    return unaccented;
+                }
+            }
+            remove_accents(&random_word(rng, false))
+        }
+        QueryType::NoResults => (0..2)
+            .map(|_| {
+                (0..8)
+                    .map(|_| (b'a' + rng.gen_range(0, 26)) as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join(" "),
+    }
+}
+
+/// Generate a random query, choosing its type from the mix, or from the default mix if
+/// the mix is empty.
+pub fn generate_query<R: Rng + ?Sized>(
+    rng: &mut R,
+    english: bool,
+    mix: &[(QueryType, usize)],
+) -> Query {
+    let mix = if mix.is_empty() { DEFAULT_MIX } else { mix };
+    let query_type = mix
+        .choose_weighted(rng, |(_, weight)| *weight)
+        .map(|(query_type, _)| *query_type)
+        .unwrap_or(QueryType::Word);
+    Query {
+        query_type,
+        keys: generate(rng, english, query_type),
+    }
+}
@@ -1,21 +1,27 @@
 use goose::prelude::*;
 
 use crate::common;
+use crate::common::PageType;
+use crate::feeds;
+use crate::images;
+use crate::jsonapi;
+use crate::random;
 
 use rand::seq::SliceRandom;
 
 /// Load the front page in Spanish and all static assets found on the page.
 pub async fn front_page_es(user: &GooseUser) -> GooseTaskResult {
-    let goose = user.get("/es").await?;
-    common::validate_and_load_static_assets(user, goose, "Inicio").await?;
+    let goose = common::get_page(user, "/es").await?;
+    common::validate_and_load_static_assets(user, goose, PageType::Front, "Inicio").await?;
 
     Ok(())
 }
 
 /// Load article listing in Spanish and all static assets found on the page.
 pub async fn recipe_listing_es(user: &GooseUser) -> GooseTaskResult {
-    let goose = user.get("/es/recipes/").await?;
-    common::validate_and_load_static_assets(user, goose, "Recetas").await?;
+    let goose = common::get_page(user, "/es/recipes/").await?;
+    common::validate_and_load_static_assets(user, goose, PageType::RecipeListing, "Recetas")
+        .await?;
 
     Ok(())
 }
@@ -23,17 +29,24 @@ pub async fn recipe_listing_es(user: &GooseUser) -> GooseTaskResult {
 /// Load a random recipe in Spanish and all static assets found on the page.
 pub async fn recipe_es(user: &GooseUser) -> GooseTaskResult {
     let nodes = common::get_nodes(&common::ContentType::Recipe);
-    let recipe = nodes.choose(&mut rand::thread_rng());
-    let goose = user.get(recipe.unwrap().url_es).await?;
-    common::validate_and_load_static_assets(user, goose, recipe.unwrap().title_es).await?;
+    let recipe = nodes.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, recipe.unwrap().url_es).await?;
+    common::validate_and_load_static_assets(
+        user,
+        goose,
+        PageType::Recipe,
+        recipe.unwrap().title_es,
+    )
+    .await?;
 
     Ok(())
 }
 
 /// Load article listing in Spanish and all static assets found on the page.
 pub async fn article_listing_es(user: &GooseUser) -> GooseTaskResult {
-    let goose = user.get("/es/articles/").await?;
-    common::validate_and_load_static_assets(user, goose, "Artículos").await?;
+    let goose = common::get_page(user, "/es/articles/").await?;
+    common::validate_and_load_static_assets(user, goose, PageType::ArticleListing, "Artículos")
+        .await?;
 
     Ok(())
 }
@@ -41,9 +54,15 @@ pub async fn article_listing_es(user: &GooseUser) -> GooseTaskResult {
 /// Load a random article in Spanish and all static assets found on the page.
 pub async fn article_es(user: &GooseUser) -> GooseTaskResult {
     let nodes = common::get_nodes(&common::ContentType::Article);
-    let article = nodes.choose(&mut rand::thread_rng());
-    let goose = user.get(article.unwrap().url_es).await?;
-    common::validate_and_load_static_assets(user, goose, article.unwrap().title_es).await?;
+    let article = nodes.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, article.unwrap().url_es).await?;
+    common::validate_and_load_static_assets(
+        user,
+        goose,
+        PageType::Article,
+        article.unwrap().title_es,
+    )
+    .await?;
 
     Ok(())
 }
@@ -51,9 +70,15 @@ pub async fn article_es(user: &GooseUser) -> GooseTaskResult {
 /// Load a basic page in Spanish and all static assets found on the page.
 pub async fn basic_page_es(user: &GooseUser) -> GooseTaskResult {
     let nodes = common::get_nodes(&common::ContentType::BasicPage);
-    let page = nodes.choose(&mut rand::thread_rng());
-    let goose = user.get(page.unwrap().url_es).await?;
-    common::validate_and_load_static_assets(user, goose, page.unwrap().title_es).await?;
+    let page = nodes.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, page.unwrap().url_es).await?;
+    common::validate_and_load_static_assets(
+        user,
+        goose,
+        PageType::BasicPage,
+        page.unwrap().title_es,
+    )
+    .await?;
 
     Ok(())
 }
@@ -72,12 +97,83 @@ pub async fn search_es(user: &GooseUser) -> GooseTaskResult {
     Ok(())
 }
 
+/// Load a random node by nid in Spanish and all static assets found on the page.
+pub async fn page_by_nid_es(user: &GooseUser) -> GooseTaskResult {
+    common::node_by_nid(user, false).await?;
+
+    Ok(())
+}
+
 /// Load category listing by a random term in Spanish and all static assets found on the page.
 pub async fn term_listing_es(user: &GooseUser) -> GooseTaskResult {
     let terms = common::get_terms();
-    let term = terms.choose(&mut rand::thread_rng());
-    let goose = user.get(term.unwrap().url_es).await?;
-    common::validate_and_load_static_assets(user, goose, term.unwrap().title_es).await?;
+    let term = terms.choose(&mut random::user_rng(user));
+    let goose = common::get_page(user, term.unwrap().url_es).await?;
+    common::validate_and_load_static_assets(user, goose, PageType::Term, term.unwrap().title_es)
+        .await?;
+
+    Ok(())
+}
+
+/// Load a random listing in Spanish and follow its pager to deeper pages.
+pub async fn listing_pager_es(user: &GooseUser) -> GooseTaskResult {
+    common::listing_pager(user, false).await?;
+
+    Ok(())
+}
+
+/// Load a random node in Spanish, then switch to English with the language switcher.
+pub async fn switch_language_es(user: &GooseUser) -> GooseTaskResult {
+    common::switch_language(user, false).await?;
+
+    Ok(())
+}
+
+/// Load a random Spanish alias with non-ASCII characters, with upper and lower-case escapes.
+pub async fn alias_escapes_es(user: &GooseUser) -> GooseTaskResult {
+    common::alias_escapes(user).await?;
+
+    Ok(())
+}
+
+/// Request a nonexistent path in Spanish, as scanners do, and validate the 404 page.
+pub async fn scanner_es(user: &GooseUser) -> GooseTaskResult {
+    common::scanner(user, false).await?;
+
+    Ok(())
+}
+
+/// Request a random JSON:API collection in Spanish, as a decoupled frontend does.
+pub async fn jsonapi_collection_es(user: &GooseUser) -> GooseTaskResult {
+    jsonapi::collection(user, false).await?;
+
+    Ok(())
+}
+
+/// Look up a random node in Spanish through JSON:API, filtering by its title.
+pub async fn jsonapi_filter_es(user: &GooseUser) -> GooseTaskResult {
+    jsonapi::filter_by_title(user, false).await?;
+
+    Ok(())
+}
+
+/// Create, change and delete a recipe or article in Spanish through JSON:API.
+pub async fn jsonapi_write_es(user: &GooseUser) -> GooseTaskResult {
+    jsonapi::write(user, false).await?;
+
+    Ok(())
+}
+
+/// Poll an RSS feed in Spanish, as a feed reader does.
+pub async fn feeds_es(user: &GooseUser) -> GooseTaskResult {
+    feeds::poll(user, false).await?;
+
+    Ok(())
+}
+
+/// Request the image style derivatives on a random Spanish recipe or article.
+pub async fn images_es(user: &GooseUser) -> GooseTaskResult {
+    images::derivatives(user, false).await?;
 
     Ok(())
 }
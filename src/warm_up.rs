@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use goose::prelude::*;
+use log::{info, warn};
+
+use crate::common::{self, ContentType, PageType};
+use crate::metrics::{self, WarmUpMetrics};
+
+/// A page loaded during the warm-up, with the title expected on the page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WarmUpPage {
+    pub path: String,
+    pub page_type: PageType,
+    pub title: String,
+}
+
+impl WarmUpPage {
+    fn new(path: &str, page_type: PageType, title: &str) -> WarmUpPage {
+        WarmUpPage {
+            path: path.to_string(),
+            page_type,
+            title: title.to_string(),
+        }
+    }
+}
+
+/// Returns every page in the catalog: the front pages, listings, nodes and terms in both
+/// languages, and each node by nid.
+pub fn get_pages() -> Vec<WarmUpPage> {
+    let mut pages = vec![
+        WarmUpPage::new("/", PageType::Front, "Home"),
+        WarmUpPage::new("/es", PageType::Front, "Inicio"),
+        WarmUpPage::new("/en/recipes/", PageType::RecipeListing, "Recipes"),
+        WarmUpPage::new("/es/recipes/", PageType::RecipeListing, "Recetas"),
+        WarmUpPage::new("/en/articles/", PageType::ArticleListing, "Articles"),
+        WarmUpPage::new("/es/articles/", PageType::ArticleListing, "Artículos"),
+    ];
+
+    for content_type in &[
+        ContentType::Article,
+        ContentType::BasicPage,
+        ContentType::Recipe,
+    ] {
+        let page_type = PageType::from(content_type);
+        for node in common::get_nodes(content_type) {
+            pages.push(WarmUpPage::new(node.url_en, page_type, node.title_en));
+            pages.push(WarmUpPage::new(node.url_es, page_type, node.title_es));
+            pages.push(WarmUpPage::new(
+                &format!("/node/{}", node.nid),
+                page_type,
+                node.title_en,
+            ));
+        }
+    }
+
+    for term in common::get_terms() {
+        pages.push(WarmUpPage::new(term.url_en, PageType::Term, term.title_en));
+        pages.push(WarmUpPage::new(term.url_es, PageType::Term, term.title_es));
+    }
+
+    pages
+}
+
+/// Load and validate a page, returning its HTML or a description of the problem.
+async fn load_page(user: &GooseUser, page: &WarmUpPage) -> Result<String, String> {
+    let goose = user
+        .get(&common::iri_to_uri(&page.path))
+        .await
+        .map_err(|e| format!("failed to load page: {:?}", e))?;
+    let response = goose
+        .response
+        .map_err(|e| format!("no response from server: {}", e))?;
+    let headers = response.headers().clone();
+    common::record_cache_status(&goose.request, &headers, &page.page_type);
+    let html = response
+        .text()
+        .await
+        .map_err(|e| format!("failed to parse page: {}", e))?;
+    common::validate_page(&html, &headers, &page.page_type, &page.title)?;
+
+    Ok(html)
+}
+
+/// Load every page in the catalog once, along with each static asset found on them,
+/// before any users start. Registered with test_start, so Goose doesn't include these
+/// requests in its metrics. The Umami metrics collected are set aside as the warm-up
+/// metrics, so the measured run starts from zero.
+pub async fn warm_up(user: &GooseUser) -> GooseTaskResult {
+    let started = Instant::now();
+    let mut warm_up = WarmUpMetrics::default();
+    let mut static_assets = HashSet::new();
+
+    for page in get_pages() {
+        warm_up.pages += 1;
+        let html = match load_page(user, &page).await {
+            Ok(html) => html,
+            Err(e) => {
+                warn!("warm-up: {}: {}", page.path, e);
+                warm_up.failures += 1;
+                continue;
+            }
+        };
+
+        // Most static assets are shared by all pages, so only load each one once.
+        for asset in common::get_static_elements(&html) {
+            if !static_assets.insert(asset.clone()) {
+                continue;
+            }
+            let loaded = match user.get_named(&asset, "static asset").await {
+                Ok(goose) => match goose.response {
+                    Ok(response) => response.status().is_success(),
+                    Err(_) => false,
+                },
+                Err(_) => false,
+            };
+            if !loaded {
+                warn!("warm-up: {}: failed to load static asset", asset);
+                warm_up.failures += 1;
+            }
+        }
+    }
+
+    warm_up.static_assets = static_assets.len();
+    warm_up.elapsed = started.elapsed().as_millis() as u64;
+    info!(
+        "warm-up loaded {} pages and {} static assets in {}ms with {} failures",
+        warm_up.pages, warm_up.static_assets, warm_up.elapsed, warm_up.failures
+    );
+    metrics::end_warm_up(warm_up);
+
+    Ok(())
+}
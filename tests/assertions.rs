@@ -0,0 +1,183 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+
+use umami_loadtest::assertions::{
+    evaluate, get_assertions, matches_selector, parse_assertions, Assertion, AssertionFailure,
+};
+use umami_loadtest::common::{validate_page, PageType};
+use umami_loadtest::metrics;
+
+const ARTICLE_EN: &str = include_str!("fixtures/article_en.html");
+const RECIPE_ES: &str = include_str!("fixtures/recipe_es.html");
+const CONTACT_EN: &str = include_str!("fixtures/contact_en.html");
+const SEARCH_RESULTS_EN: &str = include_str!("fixtures/search_results_en.html");
+
+#[test]
+fn selectors() {
+    assert!(matches_selector(
+        ARTICLE_EN,
+        "#block-umami-languageswitcher"
+    ));
+    assert!(matches_selector(ARTICLE_EN, "article .field--name-body"));
+    assert!(matches_selector(
+        ARTICLE_EN,
+        "a.language-link[hreflang=\"es\"]"
+    ));
+    assert!(!matches_selector(ARTICLE_EN, "input[autocomplete]"));
+    assert!(matches_selector(
+        RECIPE_ES,
+        "article .field--name-field-ingredients"
+    ));
+    assert!(!matches_selector(
+        ARTICLE_EN,
+        "article .field--name-field-ingredients"
+    ));
+    assert!(matches_selector(
+        CONTACT_EN,
+        "form#contact-message-feedback-form"
+    ));
+    assert!(matches_selector(
+        CONTACT_EN,
+        "input[name=\"copy\"][type=\"checkbox\"]"
+    ));
+    assert!(!matches_selector(CONTACT_EN, "form#search-form"));
+    assert!(matches_selector(
+        SEARCH_RESULTS_EN,
+        "ol.search-results h3.search-result__title"
+    ));
+    // The body follows the language switcher, but isn't inside it.
+    assert!(!matches_selector(
+        ARTICLE_EN,
+        "#block-umami-languageswitcher .field--name-body"
+    ));
+}
+
+#[test]
+fn language_switcher_is_only_required_where_rendered() {
+    let switcher = Assertion::RequiredSelector("#block-umami-languageswitcher".to_string());
+    assert!(get_assertions(&PageType::Article).contains(&switcher));
+    assert!(get_assertions(&PageType::Contact).contains(&switcher));
+    assert!(!get_assertions(&PageType::Search).contains(&switcher));
+    assert!(!get_assertions(&PageType::NotFound).contains(&switcher));
+    assert!(!get_assertions(&PageType::ViewsAjax).contains(&switcher));
+}
+
+#[test]
+fn default_assertions_pass_on_fixtures() {
+    let headers = HeaderMap::new();
+    let pages = [
+        (ARTICLE_EN, PageType::Article),
+        (RECIPE_ES, PageType::Recipe),
+        (CONTACT_EN, PageType::Contact),
+        (SEARCH_RESULTS_EN, PageType::Search),
+    ];
+    for (html, page_type) in &pages {
+        assert_eq!(evaluate(&get_assertions(page_type), html, &headers), Ok(()));
+    }
+}
+
+#[test]
+fn named_failures() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-drupal-cache", HeaderValue::from_static("HIT"));
+
+    assert_eq!(
+        evaluate(&get_assertions(&PageType::Recipe), ARTICLE_EN, &headers),
+        Err(AssertionFailure::MissingSelector(
+            "article .field--name-field-ingredients".to_string()
+        ))
+    );
+    assert_eq!(
+        evaluate(
+            &[Assertion::ForbiddenString("carrots".to_string())],
+            ARTICLE_EN,
+            &headers
+        ),
+        Err(AssertionFailure::ForbiddenString("carrots".to_string()))
+    );
+    assert_eq!(
+        evaluate(
+            &[Assertion::RequiredHeader("content-language".to_string())],
+            ARTICLE_EN,
+            &headers
+        ),
+        Err(AssertionFailure::MissingHeader(
+            "content-language".to_string()
+        ))
+    );
+    assert_eq!(
+        evaluate(
+            &[Assertion::ForbiddenHeader("X-Drupal-Cache".to_string())],
+            ARTICLE_EN,
+            &headers
+        ),
+        Err(AssertionFailure::ForbiddenHeader(
+            "X-Drupal-Cache".to_string()
+        ))
+    );
+    assert_eq!(
+        evaluate(&[Assertion::MaxBodySize(100)], ARTICLE_EN, &headers),
+        Err(AssertionFailure::BodyTooLarge {
+            size: ARTICLE_EN.len(),
+            max: 100
+        })
+    );
+}
+
+#[test]
+fn failures_are_counted_per_page_type() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-drupal-cache", HeaderValue::from_static("HIT"));
+
+    let failure =
+        AssertionFailure::MissingSelector("article .field--name-field-ingredients".to_string());
+    for _ in 0..2 {
+        assert_eq!(
+            validate_page(
+                ARTICLE_EN,
+                &headers,
+                &PageType::Recipe,
+                "Let's hear it for carrots"
+            ),
+            Err(failure.to_string())
+        );
+    }
+    let key = (PageType::Recipe.to_string(), failure.to_string());
+    assert_eq!(metrics::get_metrics().assertion_failures[&key], 2);
+}
+
+#[test]
+fn assertions_file() {
+    let assertions = parse_assertions(
+        "# Checked on every page.\n\
+         [all]\n\
+         forbidden = Deprecated function\n\
+         \n\
+         [recipe]\n\
+         selector = .field--name-field-recipe-instruction\n\
+         header = Content-Language\n\
+         max_body_size = 200000\n",
+    )
+    .unwrap();
+    assert_eq!(
+        assertions,
+        vec![
+            (
+                None,
+                Assertion::ForbiddenString("Deprecated function".to_string())
+            ),
+            (
+                Some(PageType::Recipe),
+                Assertion::RequiredSelector(".field--name-field-recipe-instruction".to_string())
+            ),
+            (
+                Some(PageType::Recipe),
+                Assertion::RequiredHeader("content-language".to_string())
+            ),
+            (Some(PageType::Recipe), Assertion::MaxBodySize(200000)),
+        ]
+    );
+
+    assert!(parse_assertions("[nonsense]\n").is_err());
+    assert!(parse_assertions("selector\n").is_err());
+    assert!(parse_assertions("max_body_size = big\n").is_err());
+}
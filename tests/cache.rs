@@ -0,0 +1,64 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+
+use umami_loadtest::cache::{CacheResult, CacheStatus};
+
+/// Build a HeaderMap from name/value pairs.
+fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.insert(*name, HeaderValue::from_static(value));
+    }
+    headers
+}
+
+#[test]
+fn drupal_cache_headers() {
+    let status = CacheStatus::from_headers(&headers(&[
+        ("x-drupal-cache", "HIT"),
+        ("x-drupal-dynamic-cache", "MISS"),
+    ]));
+    assert_eq!(status.page_cache, Some(CacheResult::Hit));
+    assert_eq!(status.dynamic_cache, Some(CacheResult::Miss));
+    assert_eq!(status.varnish, None);
+    assert_eq!(status.result(), CacheResult::Hit);
+
+    let status = CacheStatus::from_headers(&headers(&[
+        ("x-drupal-cache", "MISS"),
+        ("x-drupal-dynamic-cache", "MISS"),
+    ]));
+    assert_eq!(status.result(), CacheResult::Miss);
+
+    let status = CacheStatus::from_headers(&headers(&[("x-drupal-dynamic-cache", "UNCACHEABLE")]));
+    assert_eq!(status.page_cache, None);
+    assert_eq!(status.result(), CacheResult::Uncacheable);
+
+    // Responses without cache headers weren't cached by Drupal.
+    assert_eq!(
+        CacheStatus::from_headers(&HeaderMap::new()).result(),
+        CacheResult::Uncacheable
+    );
+}
+
+#[test]
+fn varnish_cache_headers() {
+    // A hit includes the ID of the request that populated the cache.
+    let status = CacheStatus::from_headers(&headers(&[
+        ("x-varnish", "32770 3"),
+        ("age", "120"),
+        ("x-drupal-cache", "MISS"),
+    ]));
+    assert_eq!(status.varnish, Some(CacheResult::Hit));
+    assert_eq!(status.age, Some(120));
+    assert_eq!(status.result(), CacheResult::Hit);
+
+    let status = CacheStatus::from_headers(&headers(&[("x-varnish", "32771"), ("age", "0")]));
+    assert_eq!(status.varnish, Some(CacheResult::Miss));
+    assert_eq!(status.result(), CacheResult::Miss);
+
+    let status = CacheStatus::from_headers(&headers(&[("x-cache", "HIT from varnish")]));
+    assert_eq!(status.varnish, Some(CacheResult::Hit));
+
+    // Without a Varnish header, a non-zero Age means a proxy served the response.
+    let status = CacheStatus::from_headers(&headers(&[("age", "30")]));
+    assert_eq!(status.varnish, Some(CacheResult::Hit));
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use umami_loadtest::cache::{CacheBust, CacheResult};
+use umami_loadtest::{config, metrics};
+
+use common::mock::MockServer;
+use common::run_against_mock;
+
+#[test]
+fn cache_busting_is_configured_per_task_set() {
+    let args = vec![
+        "--cache-bust".to_string(),
+        "no-cache,spanish:query".to_string(),
+        "--users".to_string(),
+        "2".to_string(),
+    ];
+    let (configuration, goose_args) = config::parse_args(args).unwrap();
+    assert_eq!(goose_args, vec!["--users", "2"]);
+    assert_eq!(configuration.cache_bust("english"), CacheBust::NoCache);
+    assert_eq!(configuration.cache_bust("spanish"), CacheBust::Query);
+
+    let args = vec!["--cache-bust=french:query".to_string()];
+    assert!(config::parse_args(args).is_err());
+    // Only the anonymous task sets load pages that can be cache busted.
+    let args = vec!["--cache-bust=scanner:query".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec!["--cache-bust=admin:query".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec!["--cache-bust=always".to_string()];
+    assert!(config::parse_args(args).is_err());
+}
+
+#[test]
+fn unique_query_strings_defeat_the_page_cache() {
+    let server = MockServer::start();
+    let goose_metrics = run_against_mock(
+        &server,
+        &["--cache-bust=query"],
+        2,
+        2,
+        umami_loadtest::register_tasksets,
+    );
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    let metrics = metrics::get_metrics();
+    assert!(!metrics.cache_response_times.is_empty());
+    for (name, result) in metrics.cache_response_times.keys() {
+        assert_ne!(*result, CacheResult::Hit, "{} was served from cache", name);
+    }
+}
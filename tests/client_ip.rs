@@ -0,0 +1,98 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::common::{client_ip, set_client_ip, GOOSE_USER_AGENT};
+use umami_loadtest::english::{anonymous_contact_form_en, front_page_en};
+use umami_loadtest::{config, metrics};
+
+use common::mock::{Fault, MockServer};
+
+#[test]
+fn each_user_submits_the_contact_form_from_its_own_ip() {
+    let args = vec!["--client-ip".to_string(), "X-Forwarded-For".to_string()];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    server.set_fault(Some(Fault::FloodControl));
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "4",
+        "--hatch-rate",
+        "4",
+        "--run-time",
+        "2",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("Contact user")
+                .register_task(task!(set_client_ip).set_on_start())
+                .register_task(task!(anonymous_contact_form_en)),
+        )
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    assert_eq!(server.contact_clients(), 4);
+
+    // Throttled submissions are counted rather than treated as failures.
+    let contact = &metrics::get_metrics().contact["en"];
+    assert!(contact.submissions > 0);
+    assert!(contact.throttled > 0);
+}
+
+#[test]
+fn users_with_a_client_ip_still_identify_as_goose() {
+    let args = vec!["--client-ip".to_string(), "X-Forwarded-For".to_string()];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "2",
+        "--hatch-rate",
+        "2",
+        "--run-time",
+        "1",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("Client IP user")
+                .register_task(task!(set_client_ip).set_on_start())
+                .register_task(task!(front_page_en)),
+        )
+        .register_taskset(taskset!("Goose user").register_task(task!(front_page_en)))
+        .execute()
+        .unwrap();
+
+    // Both the replaced client and Goose's own client send the same User-Agent.
+    let user_agents = server.user_agents();
+    assert_eq!(user_agents.len(), 1, "{:?}", user_agents);
+    assert!(user_agents.contains(GOOSE_USER_AGENT));
+}
+
+#[test]
+fn client_ips() {
+    assert_eq!(client_ip(0), "10.0.0.1");
+    assert_eq!(client_ip(255), "10.0.1.0");
+    assert_ne!(client_ip(1), client_ip(2));
+
+    let args = vec!["--client-ip=Not A Header".to_string()];
+    assert!(config::parse_args(args).is_err());
+}
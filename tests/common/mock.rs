@@ -0,0 +1,1830 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use umami_loadtest::common;
+
+/// How many times each client can submit the contact form before the mock server starts
+/// returning Drupal's flood control message, matching Drupal's default of 5 per hour.
+pub const CONTACT_FORM_LIMIT: usize = 5;
+
+/// How many rows are listed on each page of a Views listing.
+const LISTING_ROWS_PER_PAGE: usize = 4;
+
+/// How many search results are listed on each page, as in Drupal.
+const SEARCH_RESULTS_PER_PAGE: usize = 10;
+
+/// How long the mock server takes to generate an image style derivative.
+const IMAGE_GENERATION: Duration = Duration::from_millis(20);
+
+/// The password the mock server's admin user logs in with.
+pub const ADMIN_PASSWORD: &str = "admin";
+
+/// The session cookie set when the admin user logs in.
+const SESSION_COOKIE: &str = "SESSmock=admin";
+
+/// The Authorization header sent by the admin user with Basic Auth, "admin:admin" encoded
+/// with base64.
+const BASIC_AUTH: &str = "Basic YWRtaW46YWRtaW4=";
+
+/// The CSRF token returned by /session/token, required to write through JSON:API with the
+/// session cookie.
+const CSRF_TOKEN: &str = "mock-csrf-token";
+
+/// A lightweight HTTP server serving canned Umami pages, so the load test can be exercised
+/// without a running Drupal site. Every node and term in the catalog can be loaded in both
+/// languages, along with the listings, the contact and search forms, and static assets,
+/// including image style derivatives.
+///
+/// The server runs in a background thread until the process exits.
+pub struct MockServer {
+    address: SocketAddr,
+    state: Arc<MockState>,
+}
+
+/// Faults that can be injected into the mock server's responses, to confirm the load test
+/// detects broken pages. Faults only affect HTML pages, not static assets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fault {
+    /// Pages are returned with the wrong title.
+    WrongTitle,
+    /// Forms are returned without a form_build_id.
+    MissingFormBuildId,
+    /// Pages are replaced with Drupal's unexpected error page and a 500 status code.
+    ServerError,
+    /// Pages are returned only after the specified delay.
+    SlowResponse(Duration),
+    /// Pages are cut off halfway, before the full Content-Length is sent.
+    TruncatedBody,
+    /// Submitting the search form returns the form again instead of redirecting.
+    NoSearchRedirect,
+    /// Pages include a PHP warning displayed by Drupal, but are otherwise correct.
+    PhpWarning,
+    /// Pages are replaced with Drupal's maintenance page and a 503 status code.
+    MaintenanceMode,
+    /// Pages are replaced with Drupal's error page showing a database exception.
+    DatabaseError,
+    /// Every contact form submission is throttled, as if each client had already reached
+    /// CONTACT_FORM_LIMIT.
+    FloodControl,
+}
+
+/// State shared between all connections to the mock server.
+#[derive(Default)]
+struct MockState {
+    contact_submissions: AtomicUsize,
+    /// Contact form submissions by client IP address, for flood control.
+    contact_clients: Mutex<HashMap<String, usize>>,
+    fault: Mutex<Option<Fault>>,
+    logins: AtomicUsize,
+    /// Pages in the page cache, by path and query string.
+    page_cache: Mutex<HashSet<String>>,
+    cache_clears: AtomicUsize,
+    /// Whether the performance page displays Drupal's message that caches were cleared,
+    /// which it does once after the form is submitted.
+    caches_cleared_message: AtomicBool,
+    node_saves: AtomicUsize,
+    /// Nodes created through JSON:API and not deleted yet, by id.
+    jsonapi_nodes: Mutex<HashMap<String, serde_json::Value>>,
+    jsonapi_creates: AtomicUsize,
+    jsonapi_deletes: AtomicUsize,
+    /// Image style derivatives generated since their style was last flushed, by path
+    /// below the styles directory.
+    derivatives: Mutex<HashSet<String>>,
+    image_generations: AtomicUsize,
+    image_flushes: AtomicUsize,
+    /// The User-Agent headers requests were sent with.
+    user_agents: Mutex<HashSet<String>>,
+}
+
+/// A canned response returned by the mock server.
+struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+    truncated: bool,
+}
+
+/// A request received by the mock server.
+struct MockRequest {
+    method: String,
+    target: String,
+    path: String,
+    query: HashMap<String, String>,
+    form: HashMap<String, String>,
+    body: String,
+    /// Whether the request included the admin user's session cookie.
+    logged_in: bool,
+    authorization: Option<String>,
+    csrf_token: Option<String>,
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+    /// The client's IP address. Like Drupal behind a trusted reverse proxy, this is taken
+    /// from X-Forwarded-For or X-Real-IP if set.
+    client: String,
+    /// The Host header, which absolute URLs are built from.
+    host: String,
+    user_agent: Option<String>,
+}
+
+impl MockServer {
+    /// Start the mock server on a random local port.
+    pub fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock server");
+        let address = listener.local_addr().unwrap();
+        let state = Arc::new(MockState::default());
+
+        let thread_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = thread_state.clone();
+                thread::spawn(move || handle_connection(stream, &state));
+            }
+        });
+
+        MockServer { address, state }
+    }
+
+    /// The base URL of the mock server, suitable for passing to Goose as the host.
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    /// Inject a fault into all subsequent responses, or pass None to stop injecting faults.
+    pub fn set_fault(&self, fault: Option<Fault>) {
+        *self.state.fault.lock().unwrap() = fault;
+    }
+
+    /// How many times the contact form has been successfully submitted.
+    pub fn contact_submissions(&self) -> usize {
+        self.state.contact_submissions.load(Ordering::SeqCst)
+    }
+
+    /// How many different client IP addresses have submitted the contact form.
+    pub fn contact_clients(&self) -> usize {
+        self.state.contact_clients.lock().unwrap().len()
+    }
+
+    /// How many times the admin user has logged in.
+    pub fn logins(&self) -> usize {
+        self.state.logins.load(Ordering::SeqCst)
+    }
+
+    /// How many times the admin user has cleared all caches.
+    pub fn cache_clears(&self) -> usize {
+        self.state.cache_clears.load(Ordering::SeqCst)
+    }
+
+    /// How many times the admin user has saved a node.
+    pub fn node_saves(&self) -> usize {
+        self.state.node_saves.load(Ordering::SeqCst)
+    }
+
+    /// How many nodes have been created through JSON:API.
+    pub fn jsonapi_creates(&self) -> usize {
+        self.state.jsonapi_creates.load(Ordering::SeqCst)
+    }
+
+    /// How many nodes created through JSON:API have been deleted.
+    pub fn jsonapi_deletes(&self) -> usize {
+        self.state.jsonapi_deletes.load(Ordering::SeqCst)
+    }
+
+    /// How many image style derivatives have been generated.
+    pub fn image_generations(&self) -> usize {
+        self.state.image_generations.load(Ordering::SeqCst)
+    }
+
+    /// How many times the admin user has flushed an image style.
+    pub fn image_flushes(&self) -> usize {
+        self.state.image_flushes.load(Ordering::SeqCst)
+    }
+
+    /// The different User-Agent headers requests have been sent with.
+    pub fn user_agents(&self) -> HashSet<String> {
+        self.state.user_agents.lock().unwrap().clone()
+    }
+}
+
+/// Read a single request from the stream, and write the canned response.
+fn handle_connection(mut stream: TcpStream, state: &MockState) {
+    let request = match read_request(&mut stream) {
+        Some(request) => request,
+        None => return,
+    };
+    if let Some(user_agent) = &request.user_agent {
+        state.user_agents.lock().unwrap().insert(user_agent.clone());
+    }
+    let fault = *state.fault.lock().unwrap();
+    let response = cache(&request, state, route(&request, state));
+    let response = match fault {
+        Some(fault) => inject_fault(&request, response, fault),
+        None => response,
+    };
+    let _ = write_response(&mut stream, &response);
+}
+
+/// Parse the request line, headers and (optional) form body of an HTTP/1.1 request.
+fn read_request(stream: &mut TcpStream) -> Option<MockRequest> {
+    let peer = stream.peer_addr().ok()?.ip().to_string();
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?.to_string();
+
+    let mut content_length = 0;
+    let mut logged_in = false;
+    let mut authorization = None;
+    let mut csrf_token = None;
+    let mut if_none_match = None;
+    let mut if_modified_since = None;
+    let mut client = None;
+    let mut host = String::new();
+    let mut user_agent = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(index) = line.find(':') {
+            if line[..index].eq_ignore_ascii_case("content-length") {
+                content_length = line[index + 1..].trim().parse().unwrap_or(0);
+            } else if line[..index].eq_ignore_ascii_case("host") {
+                host = line[index + 1..].trim().to_string();
+            } else if line[..index].eq_ignore_ascii_case("cookie") {
+                logged_in = line[index + 1..].contains(SESSION_COOKIE);
+            } else if line[..index].eq_ignore_ascii_case("authorization") {
+                authorization = Some(line[index + 1..].trim().to_string());
+            } else if line[..index].eq_ignore_ascii_case("x-csrf-token") {
+                csrf_token = Some(line[index + 1..].trim().to_string());
+            } else if line[..index].eq_ignore_ascii_case("if-none-match") {
+                if_none_match = Some(line[index + 1..].trim().to_string());
+            } else if line[..index].eq_ignore_ascii_case("if-modified-since") {
+                if_modified_since = Some(line[index + 1..].trim().to_string());
+            } else if line[..index].eq_ignore_ascii_case("user-agent") {
+                user_agent = Some(line[index + 1..].trim().to_string());
+            } else if line[..index].eq_ignore_ascii_case("x-forwarded-for")
+                || line[..index].eq_ignore_ascii_case("x-real-ip")
+            {
+                // X-Forwarded-For lists the client first, followed by any proxies.
+                client = line[index + 1..]
+                    .split(',')
+                    .next()
+                    .map(|ip| ip.trim().to_string());
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    let body = String::from_utf8_lossy(&body).to_string();
+
+    let (path, query) = match target.find('?') {
+        Some(index) => (&target[..index], &target[index + 1..]),
+        None => (target.as_str(), ""),
+    };
+
+    Some(MockRequest {
+        method,
+        target: target.clone(),
+        path: common::percent_decode(path),
+        query: parse_urlencoded(query),
+        form: parse_urlencoded(&body),
+        body,
+        logged_in,
+        authorization,
+        csrf_token,
+        if_none_match,
+        if_modified_since,
+        client: client.unwrap_or(peer),
+        host,
+        user_agent,
+    })
+}
+
+/// Write the response, closing the connection afterwards.
+fn write_response(stream: &mut TcpStream, response: &MockResponse) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        303 => "See Other",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        422 => "Unprocessable Entity",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes())?;
+    if response.truncated {
+        stream.write_all(&response.body.as_bytes()[..response.body.len() / 2])?;
+    } else {
+        stream.write_all(response.body.as_bytes())?;
+    }
+    stream.flush()
+}
+
+/// Return a robots.txt like Drupal's, with a short crawl delay so tests stay quick.
+fn robots_txt() -> MockResponse {
+    MockResponse {
+        status: 200,
+        headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+        body: "# robots.txt\n\
+               User-agent: *\n\
+               Crawl-delay: 0.05\n\
+               Allow: /core/*.css$\n\
+               Disallow: /core/\n\
+               Disallow: /admin/\n\
+               Disallow: /en/contact\n\
+               Disallow: /es/contact\n"
+            .to_string(),
+        truncated: false,
+    }
+}
+
+/// Return a sitemap listing the front pages, contact forms and every node in both
+/// languages, as the Simple XML Sitemap module does.
+fn sitemap(request: &MockRequest) -> MockResponse {
+    let mut paths = vec!["/en", "/es", "/en/contact", "/es/contact"];
+    for content_type in &[
+        common::ContentType::Article,
+        common::ContentType::BasicPage,
+        common::ContentType::Recipe,
+    ] {
+        for node in common::get_nodes(content_type) {
+            paths.push(node.url_en);
+            paths.push(node.url_es);
+        }
+    }
+    let mut body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+"#
+    .to_string();
+    for path in paths {
+        body.push_str(&format!(
+            "  <url><loc>http://{}{}</loc></url>\n",
+            request.host,
+            common::iri_to_uri(path)
+        ));
+    }
+    body.push_str("</urlset>\n");
+    MockResponse {
+        status: 200,
+        headers: vec![("Content-Type".to_string(), "application/xml".to_string())],
+        body,
+        truncated: false,
+    }
+}
+
+/// Escape text for HTML the way Drupal does, including single quotes.
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#039;")
+}
+
+/// Parse an application/x-www-form-urlencoded string into key/value pairs.
+fn parse_urlencoded(encoded: &str) -> HashMap<String, String> {
+    encoded
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or_default().replace('+', " ");
+            let value = parts.next().unwrap_or_default().replace('+', " ");
+            (common::percent_decode(&key), common::percent_decode(&value))
+        })
+        .collect()
+}
+
+/// Return the canned response for the requested path.
+fn route(request: &MockRequest, state: &MockState) -> MockResponse {
+    let path = request.path.trim_end_matches('/');
+    let english = !(path == "/es" || path.starts_with("/es/"));
+
+    // Drupal's .htaccess forbids hidden files and directories, such as /.env.
+    if path
+        .split('/')
+        .any(|part| part.starts_with('.') && part != ".well-known")
+    {
+        return MockResponse {
+            status: 403,
+            headers: vec![("Content-Type".to_string(), "text/html".to_string())],
+            body: "<h1>Forbidden</h1>".to_string(),
+            truncated: false,
+        };
+    }
+
+    // JSON:API resources, in Spanish with the /es prefix.
+    let resource_path = if english { path } else { &path[3..] };
+    if resource_path.starts_with("/jsonapi/") {
+        return jsonapi(request, state, english, resource_path);
+    }
+
+    // Image style derivatives are generated the first time they're requested.
+    if let Some(derivative) = path.strip_prefix("/sites/default/files/styles/") {
+        return image_style(request, state, derivative);
+    }
+
+    // Static assets.
+    if path.starts_with("/core/") || path.starts_with("/sites/") {
+        return MockResponse {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+            body: "/* static asset */".to_string(),
+            truncated: false,
+        };
+    }
+
+    match path {
+        "" | "/en" => return page(true, "Home", ""),
+        "/es" => return page(false, "Inicio", ""),
+        "/en/recipes" | "/es/recipes" => {
+            let title = if english { "Recipes" } else { "Recetas" };
+            let view = ("recipes", "");
+            return listing(request, english, title, &common::ContentType::Recipe, view);
+        }
+        "/en/articles" | "/es/articles" => {
+            let title = if english { "Articles" } else { "Artículos" };
+            let view = ("articles", "");
+            return listing(request, english, title, &common::ContentType::Article, view);
+        }
+        "/en/views/ajax" | "/es/views/ajax" => {
+            if let Some(response) = views_ajax(request, english) {
+                return response;
+            }
+        }
+        "/en/contact" | "/es/contact" => return contact_form(request, state, english),
+        "/en/search/node" | "/es/search/node" => return search(request, english),
+        "/robots.txt" => return robots_txt(),
+        "/sitemap.xml" => return sitemap(request),
+        "/rss.xml" | "/en/rss.xml" => return feed(request, state, true, "Umami Food Magazine"),
+        "/es/rss.xml" => return feed(request, state, false, "Umami Food Magazine"),
+        "/user/login" => return log_in(request, state),
+        "/session/token" => {
+            return MockResponse {
+                status: 200,
+                headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+                body: CSRF_TOKEN.to_string(),
+                truncated: false,
+            }
+        }
+        "/user/1" => return page(true, "admin", ""),
+        "/admin/config/development/performance" => return performance(request, state),
+        "/admin/config/media/image-styles" if request.logged_in => {
+            return page(true, "Image styles", "")
+        }
+        _ => (),
+    }
+    if let Some(style) = path
+        .strip_prefix("/admin/config/media/image-styles/manage/")
+        .and_then(|style| style.strip_suffix("/flush"))
+    {
+        return flush_image_style(request, state, style);
+    }
+
+    for content_type in &[
+        common::ContentType::Article,
+        common::ContentType::BasicPage,
+        common::ContentType::Recipe,
+    ] {
+        // Recipes and articles start with their image, basic pages have none.
+        let content = |node: &common::Node| match content_type {
+            common::ContentType::Recipe => RECIPE.replacen("\n", &node_image(node), 1),
+            common::ContentType::Article => ARTICLE.replacen("\n", &node_image(node), 1),
+            common::ContentType::BasicPage => ARTICLE.to_string(),
+        };
+        for node in common::get_nodes(content_type) {
+            if path == format!("/node/{}/edit", node.nid) {
+                return edit_node(request, state, content_type, &node);
+            } else if path == node.url_en || path == format!("/node/{}", node.nid) {
+                return translated_page(true, &node, &content(&node));
+            } else if path == node.url_es {
+                return translated_page(false, &node, &content(&node));
+            } else if path == format!("/es/node/{}", node.nid) {
+                // Unlike English nodes, Spanish nodes redirect to their alias, as Drupal
+                // does with the Redirect module.
+                return redirect(node.url_es);
+            }
+        }
+    }
+
+    // Recipe categories list every recipe, and link to their feed, which is numbered
+    // after the term's place in the catalog.
+    for (index, term) in common::get_terms().iter().enumerate() {
+        let language = if english { "en" } else { "es" };
+        let title = if english {
+            term.title_en
+        } else {
+            term.title_es
+        };
+        let feed_path = format!("/{}/taxonomy/term/{}/feed", language, index + 1);
+        if path == feed_path {
+            return feed(request, state, english, title);
+        } else if path == term.url_en || path == term.url_es {
+            let tid = (index + 1).to_string();
+            let view = ("taxonomy_term", tid.as_str());
+            let mut response = listing(request, english, title, &common::ContentType::Recipe, view);
+            response.body = response.body.replace(
+                "  </head>",
+                &format!(
+                    r#"    <link rel="alternate" type="application/rss+xml" title="{}" href="http://{}{}" />
+  </head>"#,
+                    html_escape(title),
+                    request.host,
+                    feed_path
+                ),
+            );
+            return response;
+        }
+    }
+
+    let title = if english {
+        "Page not found"
+    } else {
+        "Página no encontrada"
+    };
+    let mut response = page(english, title, "");
+    response.status = 404;
+    response
+}
+
+/// Add Drupal's cache headers to HTML pages. The first request for a page is a MISS, and
+/// later requests are served from the page cache. Pages with forms can't be cached.
+fn cache(request: &MockRequest, state: &MockState, mut response: MockResponse) -> MockResponse {
+    let html = response
+        .headers
+        .iter()
+        .any(|(name, value)| name == "Content-Type" && value.starts_with("text/html"));
+    if request.method != "GET" || response.status != 200 || !html {
+        return response;
+    }
+
+    // Drupal's page cache is only used for anonymous users.
+    if request.logged_in {
+        response
+            .headers
+            .push(("X-Drupal-Dynamic-Cache".to_string(), "MISS".to_string()));
+        return response;
+    }
+
+    if response.body.contains(r#"name="form_build_id""#) {
+        response.headers.push((
+            "X-Drupal-Dynamic-Cache".to_string(),
+            "UNCACHEABLE".to_string(),
+        ));
+        return response;
+    }
+
+    let page_cache = if state
+        .page_cache
+        .lock()
+        .unwrap()
+        .insert(request.target.clone())
+    {
+        "MISS"
+    } else {
+        "HIT"
+    };
+    response
+        .headers
+        .push(("X-Drupal-Cache".to_string(), page_cache.to_string()));
+    response
+        .headers
+        .push(("X-Drupal-Dynamic-Cache".to_string(), "MISS".to_string()));
+    response
+}
+
+/// Render a page of a Views listing of every node of the content type, with a pager.
+/// drupalSettings has the view's name and arguments, which Views' AJAX pager sends to
+/// /views/ajax to load deeper pages.
+fn listing(
+    request: &MockRequest,
+    english: bool,
+    title: &str,
+    content_type: &common::ContentType,
+    view: (&str, &str),
+) -> MockResponse {
+    let (view_name, view_args) = view;
+    let language = if english { "en" } else { "es" };
+    let settings = serde_json::json!({
+        "views": {
+            "ajax_path": format!("/{}/views/ajax", language),
+            "ajaxViews": {
+                format!("views_dom_id:{}", VIEW_DOM_ID): {
+                    "view_name": view_name,
+                    "view_display_id": "page_1",
+                    "view_args": view_args,
+                    "view_path": request.path,
+                    "view_base_path": view_name,
+                    "view_dom_id": VIEW_DOM_ID,
+                    "pager_element": 0,
+                },
+            },
+        },
+    });
+    let mut response = page(
+        english,
+        title,
+        &listing_view(request, english, content_type),
+    );
+    response.body = response.body.replace(
+        "  </body>",
+        &format!(
+            r#"    <script type="application/json" data-drupal-selector="drupal-settings-json">{}</script>
+  </body>"#,
+            settings
+        ),
+    );
+    response
+}
+
+/// The DOM ID of every view, which Drupal generates randomly for each rendered view.
+const VIEW_DOM_ID: &str = "mock";
+
+/// Render the listing of a view for the requested page.
+fn listing_view(
+    request: &MockRequest,
+    english: bool,
+    content_type: &common::ContentType,
+) -> String {
+    let page_number: usize = request
+        .query
+        .get("page")
+        .and_then(|page| page.parse().ok())
+        .unwrap_or(0);
+    let nodes = common::get_nodes(content_type);
+    let rows: String = nodes
+        .iter()
+        .skip(page_number * LISTING_ROWS_PER_PAGE)
+        .take(LISTING_ROWS_PER_PAGE)
+        .map(|node| {
+            let (url, node_title) = if english {
+                (node.url_en, node.title_en)
+            } else {
+                (node.url_es, node.title_es)
+            };
+            format!(
+                r#"<div class="views-row"><article><h2><a href="{}">{}</a></h2></article></div>"#,
+                url,
+                html_escape(node_title)
+            )
+        })
+        .collect();
+    let pager = if (page_number + 1) * LISTING_ROWS_PER_PAGE < nodes.len() {
+        format!(
+            r#"<nav class="pager" role="navigation"><ul class="pager__items js-pager__items"><li class="pager__item pager__item--next"><a href="?page={}" title="Go to next page" rel="next"><span>Next page</span></a></li></ul></nav>"#,
+            page_number + 1
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        r#"<div class="view js-view-dom-id-{}"><div class="view-content"><div class="views-view-grid">{}</div></div>{}</div>"#,
+        VIEW_DOM_ID, rows, pager
+    )
+}
+
+/// Render a view's listing alone as a Drupal AJAX response, as Views does for its AJAX
+/// pager. Returns None unless the request names a view with its arguments, which Drupal
+/// answers with a 404.
+fn views_ajax(request: &MockRequest, english: bool) -> Option<MockResponse> {
+    let argument = |name: &str| request.query.get(name).map(String::as_str);
+    if argument("_wrapper_format") != Some("drupal_ajax")
+        || argument("view_display_id") != Some("page_1")
+        || argument("view_dom_id") != Some(VIEW_DOM_ID)
+    {
+        return None;
+    }
+    let terms = common::get_terms().len();
+    let content_type = match (argument("view_name")?, argument("view_args")?) {
+        ("recipes", "") => common::ContentType::Recipe,
+        ("articles", "") => common::ContentType::Article,
+        ("taxonomy_term", tid) if (1..=terms).any(|index| tid == index.to_string()) => {
+            common::ContentType::Recipe
+        }
+        _ => return None,
+    };
+
+    let commands = serde_json::json!([{
+        "command": "insert",
+        "method": "replaceWith",
+        "selector": format!(".js-view-dom-id-{}", VIEW_DOM_ID),
+        "data": listing_view(request, english, &content_type),
+        "settings": null,
+    }]);
+    Some(MockResponse {
+        status: 200,
+        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+        body: commands.to_string(),
+        truncated: false,
+    })
+}
+
+/// The content of an article or basic page.
+const ARTICLE: &str = r#"<article role="article" class="node node--view-mode-full">
+        <div class="field field--name-body field--type-text-with-summary">Lorem ipsum.</div>
+      </article>"#;
+
+/// The content of a recipe.
+const RECIPE: &str = r#"<article role="article" class="node node--type-recipe node--view-mode-full">
+        <div class="field field--name-field-ingredients field--type-string">Lorem ipsum.</div>
+      </article>"#;
+
+/// Returns the itok token of an image style derivative. Drupal derives it from the style,
+/// the image and a private key, the mock server just hashes the style and image.
+fn image_token(style: &str, image: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    (style, image).hash(&mut hasher);
+    let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let hash = hasher.finish();
+    (0..8)
+        .map(|index| alphabet[(hash >> (index * 6)) as usize & 63] as char)
+        .collect()
+}
+
+/// Returns the path of an image style derivative of a public image, with its itok token.
+fn image_style_url(style: &str, image: &str) -> String {
+    format!(
+        "/sites/default/files/styles/{}/public/{}?itok={}",
+        style,
+        image,
+        image_token(style, image)
+    )
+}
+
+/// Render a node's image as Umami does, with a wide derivative for large screens and a
+/// smaller one as fallback. Returns the markup between lines of the node's content.
+fn node_image(node: &common::Node) -> String {
+    let image = format!(
+        "2020-11/{}-umami.jpg",
+        node.url_en.rsplit('/').next().unwrap_or_default()
+    );
+    format!(
+        r#"
+        <picture>
+          <source srcset="{} 1x" media="all and (min-width: 851px)" type="image/jpeg"/>
+          <img src="{}" alt="" typeof="foaf:Image" />
+        </picture>
+"#,
+        image_style_url("scale_crop_7_3_wide", &image),
+        image_style_url("large_3_2_768x512", &image)
+    )
+}
+
+/// Serve an image style derivative, refusing it without the right itok token as Drupal
+/// does. The first request for a derivative generates it, which takes IMAGE_GENERATION
+/// and answers with Drupal's headers, later requests are delivered right away.
+fn image_style(request: &MockRequest, state: &MockState, derivative: &str) -> MockResponse {
+    let mut parts = derivative.splitn(3, '/');
+    let (style, image) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(style), Some("public"), Some(image)) => (style, image),
+        _ => {
+            let mut response = page(true, "Page not found", "");
+            response.status = 404;
+            return response;
+        }
+    };
+    if request.query.get("itok") != Some(&image_token(style, image)) {
+        return access_denied();
+    }
+
+    let content_type = if image.ends_with(".png") {
+        "image/png"
+    } else {
+        "image/jpeg"
+    };
+    let mut headers = vec![("Content-Type".to_string(), content_type.to_string())];
+    if state
+        .derivatives
+        .lock()
+        .unwrap()
+        .insert(derivative.to_string())
+    {
+        thread::sleep(IMAGE_GENERATION);
+        state.image_generations.fetch_add(1, Ordering::SeqCst);
+        // Drupal bootstraps to generate a derivative, the web server delivers it after.
+        headers.push((
+            "X-Generator".to_string(),
+            "Drupal 9 (https://www.drupal.org)".to_string(),
+        ));
+        headers.push(("Cache-Control".to_string(), "private".to_string()));
+    }
+    MockResponse {
+        status: 200,
+        headers,
+        body: format!("{} {}", style, image),
+        truncated: false,
+    }
+}
+
+/// Wrap content in the Umami page template, including local static assets.
+fn page(english: bool, title: &str, content: &str) -> MockResponse {
+    let language = if english { "en" } else { "es" };
+    let body = format!(
+        r#"<!DOCTYPE html>
+<html lang="{language}" dir="ltr">
+  <head>
+    <meta charset="utf-8" />
+    <title>{title} | Umami Food Magazine</title>
+    <link rel="stylesheet" media="all" href="/sites/default/files/css/css_umami.css" />
+  </head>
+  <body>
+    <div class="language-switcher-language-url block block-language" id="block-umami-languageswitcher" role="navigation">
+      <ul class="links"><li hreflang="en"><a href="/en" class="language-link" hreflang="en">English</a></li><li hreflang="es"><a href="/es" class="language-link" hreflang="es">Español</a></li></ul>
+    </div>
+    <a href="/{language}" rel="home"><img src="/core/profiles/demo_umami/themes/umami/logo.svg" alt="Home" /></a>
+    <main role="main">
+      <h1 class="page-title">{title}</h1>
+      {content}
+    </main>
+    <script src="/core/assets/vendor/jquery/jquery.min.js?v=3.5.1"></script>
+  </body>
+</html>
+"#,
+        language = language,
+        title = html_escape(title),
+        content = content,
+    );
+
+    MockResponse {
+        status: 200,
+        headers: vec![(
+            "Content-Type".to_string(),
+            "text/html; charset=UTF-8".to_string(),
+        )],
+        body,
+        truncated: false,
+    }
+}
+
+/// Render a node, with the language switcher and hreflang alternate links pointing to
+/// its translations and a canonical link to its alias, as Drupal does.
+fn translated_page(english: bool, node: &common::Node, content: &str) -> MockResponse {
+    let title = if english {
+        node.title_en
+    } else {
+        node.title_es
+    };
+    let mut response = page(english, title, content);
+    response.body = response
+        .body
+        .replace(
+            r#"<a href="/en" class="language-link""#,
+            &format!(r#"<a href="{}" class="language-link""#, node.url_en),
+        )
+        .replace(
+            r#"<a href="/es" class="language-link""#,
+            &format!(r#"<a href="{}" class="language-link""#, node.url_es),
+        )
+        .replace(
+            "  </head>",
+            &format!(
+                r#"    <link rel="canonical" href="{}" />
+    <link rel="alternate" hreflang="en" href="{}" />
+    <link rel="alternate" hreflang="es" href="{}" />
+  </head>"#,
+                if english { node.url_en } else { node.url_es },
+                node.url_en,
+                node.url_es
+            ),
+        );
+    response
+}
+
+/// Returns the terms of a vocabulary, ie "tags", with their index in the catalog.
+fn vocabulary_terms(vocabulary: &str) -> Vec<(usize, common::Term<'static>)> {
+    let prefix = format!("/en/{}/", vocabulary.replace('_', "-"));
+    common::get_terms()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, term)| term.url_en.starts_with(&prefix))
+        .collect()
+}
+
+/// Returns the JSON:API resource identifier of a term, from its index in the catalog.
+fn term_identifier(vocabulary: &str, index: usize) -> serde_json::Value {
+    serde_json::json!({
+        "type": format!("taxonomy_term--{}", vocabulary),
+        "id": format!("00000000-0000-4000-9000-{:012}", index),
+    })
+}
+
+/// Returns the JSON:API resources of every node of a content type in the language. Each
+/// node is tagged with a term, and each recipe is in a category.
+fn node_resources(english: bool, content_type: &common::ContentType) -> Vec<serde_json::Value> {
+    let langcode = if english { "en" } else { "es" };
+    let (resource_type, recipe) = match content_type {
+        common::ContentType::Article => ("node--article", false),
+        common::ContentType::BasicPage => ("node--page", false),
+        common::ContentType::Recipe => ("node--recipe", true),
+    };
+    let tags = vocabulary_terms("tags");
+    let categories = vocabulary_terms("recipe_category");
+    common::get_nodes(content_type)
+        .iter()
+        .map(|node| {
+            let (url, title) = if english {
+                (node.url_en, node.title_en)
+            } else {
+                (node.url_es, node.title_es)
+            };
+            let nid = node.nid as usize;
+            let mut attributes = serde_json::json!({
+                "drupal_internal__nid": nid,
+                "langcode": langcode,
+                "title": title,
+                "created": format!("2020-10-{:02}T12:00:00+00:00", nid),
+                "path": { "alias": &url[3..], "langcode": langcode },
+            });
+            let mut relationships = serde_json::json!({
+                "field_tags": { "data": [term_identifier("tags", tags[nid % tags.len()].0)] },
+            });
+            if recipe {
+                attributes["field_difficulty"] = "easy".into();
+                attributes["field_preparation_time"] = (nid * 5).into();
+                relationships["field_recipe_category"] = serde_json::json!({
+                    "data": [term_identifier(
+                        "recipe_category",
+                        categories[nid % categories.len()].0,
+                    )],
+                });
+            } else {
+                attributes["body"] = serde_json::json!({ "value": format!("<p>{}</p>", title) });
+            }
+            serde_json::json!({
+                "type": resource_type,
+                "id": format!("00000000-0000-4000-8000-{:012}", nid),
+                "attributes": attributes,
+                "relationships": relationships,
+            })
+        })
+        .collect()
+}
+
+/// Returns the JSON:API resources of every term in a vocabulary in the language.
+fn term_resources(english: bool, vocabulary: &str) -> Vec<serde_json::Value> {
+    let langcode = if english { "en" } else { "es" };
+    vocabulary_terms(vocabulary)
+        .iter()
+        .map(|(index, term)| {
+            let (url, name) = if english {
+                (term.url_en, term.title_en)
+            } else {
+                (term.url_es, term.title_es)
+            };
+            let mut resource = term_identifier(vocabulary, *index);
+            resource["attributes"] = serde_json::json!({
+                "drupal_internal__tid": index + 1,
+                "langcode": langcode,
+                "name": name,
+                "description": null,
+                "weight": 0,
+                "path": { "alias": &url[3..], "langcode": langcode },
+            });
+            resource
+        })
+        .collect()
+}
+
+/// Return a JSON:API document with a single error object.
+fn jsonapi_error(status: u16, title: &str, detail: &str) -> MockResponse {
+    let errors = serde_json::json!({ "errors": [{
+        "status": status.to_string(),
+        "title": title,
+        "detail": detail,
+    }]});
+    MockResponse {
+        status,
+        headers: vec![(
+            "Content-Type".to_string(),
+            "application/vnd.api+json".to_string(),
+        )],
+        body: errors.to_string(),
+        truncated: false,
+    }
+}
+
+/// Create, update or delete a recipe or article through JSON:API. As in Drupal, writes
+/// must be authenticated with Basic Auth, or with the session cookie and the CSRF token
+/// from /session/token.
+fn jsonapi_write(
+    request: &MockRequest,
+    state: &MockState,
+    english: bool,
+    path: &str,
+) -> MockResponse {
+    match &request.authorization {
+        Some(authorization) if authorization != BASIC_AUTH => {
+            return jsonapi_error(401, "Unauthorized", "Invalid username or password.");
+        }
+        Some(_) => (),
+        None if !request.logged_in => {
+            return jsonapi_error(
+                403,
+                "Forbidden",
+                "The current user is not permitted to modify this resource.",
+            );
+        }
+        None => match &request.csrf_token {
+            Some(token) if token == CSRF_TOKEN => (),
+            Some(_) => {
+                return jsonapi_error(403, "Forbidden", "X-CSRF-Token request header is invalid")
+            }
+            None => {
+                return jsonapi_error(403, "Forbidden", "X-CSRF-Token request header is missing")
+            }
+        },
+    }
+
+    // Paths are /jsonapi/node/{bundle}, or /jsonapi/node/{bundle}/{id} for a single node.
+    let parts: Vec<&str> = path.split('/').collect();
+    let resource_type = match parts.get(3) {
+        Some(&"recipe") if parts[2] == "node" => "node--recipe",
+        Some(&"article") if parts[2] == "node" => "node--article",
+        _ => {
+            let detail = format!("The requested resource {} was not found.", path);
+            return jsonapi_error(404, "Not Found", &detail);
+        }
+    };
+    let document: serde_json::Value = if request.method == "DELETE" {
+        serde_json::Value::Null
+    } else {
+        match serde_json::from_str(&request.body) {
+            Ok(document) => document,
+            Err(e) => return jsonapi_error(400, "Bad Request", &format!("Syntax error: {}", e)),
+        }
+    };
+    let data = &document["data"];
+    if request.method != "DELETE" && data["type"] != resource_type {
+        let detail = format!(
+            "The provided type ({}) does not match the destination resource types ({}).",
+            data["type"].as_str().unwrap_or_default(),
+            resource_type
+        );
+        return jsonapi_error(409, "Conflict", &detail);
+    }
+
+    let mut nodes = state.jsonapi_nodes.lock().unwrap();
+    let (status, resource) = match (request.method.as_str(), parts.get(4)) {
+        ("POST", None) => {
+            if data["attributes"]["title"]
+                .as_str()
+                .unwrap_or_default()
+                .is_empty()
+            {
+                return jsonapi_error(
+                    422,
+                    "Unprocessable Entity",
+                    "title: This value should not be null.",
+                );
+            }
+            let created = state.jsonapi_creates.fetch_add(1, Ordering::SeqCst) + 1;
+            let id = format!("00000000-0000-4000-a000-{:012}", created);
+            let mut resource = data.clone();
+            resource["id"] = id.as_str().into();
+            resource["attributes"]["langcode"] = if english { "en" } else { "es" }.into();
+            nodes.insert(id, resource.clone());
+            (201, resource)
+        }
+        ("PATCH", Some(id)) | ("DELETE", Some(id)) => {
+            let resource = match nodes.get_mut(*id) {
+                Some(resource) if resource["type"] == resource_type => resource,
+                _ => {
+                    let detail = format!("The requested resource {} was not found.", path);
+                    return jsonapi_error(404, "Not Found", &detail);
+                }
+            };
+            if request.method == "DELETE" {
+                nodes.remove(*id);
+                state.jsonapi_deletes.fetch_add(1, Ordering::SeqCst);
+                return MockResponse {
+                    status: 204,
+                    headers: Vec::new(),
+                    body: String::new(),
+                    truncated: false,
+                };
+            }
+            if data["id"] != *id {
+                return jsonapi_error(
+                    422,
+                    "Unprocessable Entity",
+                    "The resource identifier in the body does not match the one in the URL.",
+                );
+            }
+            if let Some(attributes) = data["attributes"].as_object() {
+                for (field, value) in attributes {
+                    resource["attributes"][field] = value.clone();
+                }
+            }
+            (200, resource.clone())
+        }
+        _ => {
+            let detail = format!("No route found for \"{} {}\"", request.method, path);
+            return jsonapi_error(405, "Method Not Allowed", &detail);
+        }
+    };
+
+    let document = serde_json::json!({
+        "jsonapi": { "version": "1.0" },
+        "data": resource,
+    });
+    MockResponse {
+        status,
+        headers: vec![(
+            "Content-Type".to_string(),
+            "application/vnd.api+json".to_string(),
+        )],
+        body: document.to_string(),
+        truncated: false,
+    }
+}
+
+/// Return a JSON:API collection of recipes, articles or terms in the language, applying
+/// the filter, sort, sparse fieldset, include and pager parameters as Drupal does. Other
+/// methods are passed on to jsonapi_write().
+fn jsonapi(request: &MockRequest, state: &MockState, english: bool, path: &str) -> MockResponse {
+    if request.method != "GET" {
+        return jsonapi_write(request, state, english, path);
+    }
+
+    let (mut resources, title_field) = match path {
+        "/jsonapi/node/recipe" => (
+            node_resources(english, &common::ContentType::Recipe),
+            "title",
+        ),
+        "/jsonapi/node/article" => (
+            node_resources(english, &common::ContentType::Article),
+            "title",
+        ),
+        "/jsonapi/taxonomy_term/tags" => (term_resources(english, "tags"), "name"),
+        "/jsonapi/taxonomy_term/recipe_category" => {
+            (term_resources(english, "recipe_category"), "name")
+        }
+        _ => {
+            let detail = format!("The requested resource {} was not found.", path);
+            return jsonapi_error(404, "Not Found", &detail);
+        }
+    };
+
+    let query = &request.query;
+    if let Some(title) = query.get(&format!("filter[{}]", title_field)) {
+        resources.retain(|resource| resource["attributes"][title_field] == title.as_str());
+    }
+    match query.get("sort").map(String::as_str) {
+        Some("title") | Some("name") => resources.sort_by_key(|resource| {
+            resource["attributes"][title_field]
+                .as_str()
+                .map(str::to_string)
+        }),
+        Some("-title") | Some("-name") => resources.sort_by_key(|resource| {
+            std::cmp::Reverse(
+                resource["attributes"][title_field]
+                    .as_str()
+                    .map(str::to_string),
+            )
+        }),
+        Some("-created") => resources.reverse(),
+        _ => (),
+    }
+
+    let total = resources.len();
+    let offset = query
+        .get("page[offset]")
+        .and_then(|offset| offset.parse().ok())
+        .unwrap_or(0);
+    let limit = query
+        .get("page[limit]")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(50);
+    let mut data: Vec<serde_json::Value> = resources.into_iter().skip(offset).take(limit).collect();
+
+    // Include the terms related to the resources on the page, each only once.
+    let mut included = Vec::new();
+    let includes = query.get("include").map(String::as_str).unwrap_or_default();
+    for relationship in includes.split(',').filter(|name| !name.is_empty()) {
+        let vocabulary = match relationship {
+            "field_tags" => "tags",
+            "field_recipe_category" => "recipe_category",
+            _ => continue,
+        };
+        for term in term_resources(english, vocabulary) {
+            let related = data.iter().any(|resource| {
+                resource["relationships"][relationship]["data"]
+                    .as_array()
+                    .map(|linkage| {
+                        linkage
+                            .iter()
+                            .any(|identifier| identifier["id"] == term["id"])
+                    })
+                    .unwrap_or(false)
+            });
+            if related && !included.contains(&term) {
+                included.push(term);
+            }
+        }
+    }
+
+    // Sparse fieldsets only return the requested attributes and relationships.
+    let resource_type = data
+        .first()
+        .and_then(|resource| resource["type"].as_str())
+        .map(str::to_string);
+    if let Some(fields) =
+        resource_type.and_then(|resource_type| query.get(&format!("fields[{}]", resource_type)))
+    {
+        let fields: Vec<&str> = fields.split(',').collect();
+        for resource in &mut data {
+            for member in &["attributes", "relationships"] {
+                if let Some(object) = resource[*member].as_object_mut() {
+                    object.retain(|field, _| fields.contains(&field.as_str()));
+                }
+            }
+        }
+    }
+
+    let mut document = serde_json::json!({
+        "jsonapi": { "version": "1.0" },
+        "data": data,
+        "links": {},
+    });
+    if !includes.is_empty() {
+        document["included"] = included.into();
+    }
+    if offset + limit < total {
+        let mut parameters: Vec<String> = query
+            .iter()
+            .filter(|(key, _)| key.as_str() != "page[offset]")
+            .map(|(key, value)| {
+                format!(
+                    "{}={}",
+                    common::percent_encode(key),
+                    common::percent_encode(value)
+                )
+            })
+            .collect();
+        parameters.push(format!("page%5Boffset%5D={}", offset + limit));
+        document["links"]["next"] = serde_json::json!({
+            "href": format!(
+                "http://{}{}?{}",
+                request.host,
+                common::iri_to_uri(&request.path),
+                parameters.join("&")
+            ),
+        });
+    }
+
+    MockResponse {
+        status: 200,
+        headers: vec![(
+            "Content-Type".to_string(),
+            "application/vnd.api+json".to_string(),
+        )],
+        body: document.to_string(),
+        truncated: false,
+    }
+}
+
+/// Return an RSS feed of articles and recipes in the language, as Drupal's Views do. The
+/// ETag changes whenever a node is saved, and conditional requests for an unchanged feed
+/// are answered with 304 Not Modified.
+fn feed(request: &MockRequest, state: &MockState, english: bool, title: &str) -> MockResponse {
+    let etag = format!(
+        "\"{}\"",
+        1604312931 + state.node_saves.load(Ordering::SeqCst)
+    );
+    let last_modified = "Mon, 02 Nov 2020 10:28:51 GMT";
+    // As in HTTP, If-None-Match takes precedence over If-Modified-Since.
+    let not_modified = match &request.if_none_match {
+        Some(if_none_match) => *if_none_match == etag,
+        None => request.if_modified_since.as_deref() == Some(last_modified),
+    };
+    let headers = vec![
+        (
+            "Content-Type".to_string(),
+            "application/rss+xml; charset=utf-8".to_string(),
+        ),
+        ("ETag".to_string(), etag),
+        ("Last-Modified".to_string(), last_modified.to_string()),
+    ];
+    if not_modified {
+        return MockResponse {
+            status: 304,
+            headers,
+            body: String::new(),
+            truncated: false,
+        };
+    }
+
+    let language = if english { "en" } else { "es" };
+    let base_url = format!("http://{}", request.host);
+    let items: String = common::get_nodes(&common::ContentType::Article)
+        .iter()
+        .chain(common::get_nodes(&common::ContentType::Recipe).iter())
+        .take(10)
+        .map(|node| {
+            let (url, node_title) = if english {
+                (node.url_en, node.title_en)
+            } else {
+                (node.url_es, node.title_es)
+            };
+            format!(
+                r#"
+    <item>
+      <title>{title}</title>
+      <link>{base_url}{url}</link>
+      <description>&lt;p&gt;{description}&lt;/p&gt;</description>
+      <pubDate>Mon, 02 Nov 2020 10:28:51 +0000</pubDate>
+      <dc:creator>admin</dc:creator>
+      <guid isPermaLink="false">{nid} at {base_url}/</guid>
+    </item>"#,
+                title = html_escape(node_title),
+                base_url = base_url,
+                url = common::iri_to_uri(url),
+                description = html_escape(&html_escape(node_title)),
+                nid = node.nid,
+            )
+        })
+        .collect();
+    let body = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0" xml:base="{base_url}/{language}" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:atom="http://www.w3.org/2005/Atom">
+  <channel>
+    <title>{title}</title>
+    <link>{base_url}/{language}</link>
+    <description></description>
+    <language>{language}</language>
+    <atom:link href="{base_url}{path}" rel="self" type="application/rss+xml" />{items}
+  </channel>
+</rss>
+"#,
+        base_url = base_url,
+        language = language,
+        title = html_escape(title),
+        path = request.target,
+        items = items,
+    );
+
+    MockResponse {
+        status: 200,
+        headers,
+        body,
+        truncated: false,
+    }
+}
+
+/// Redirect to another page, as Drupal does after a form is successfully submitted.
+fn redirect(location: &str) -> MockResponse {
+    MockResponse {
+        status: 303,
+        headers: vec![("Location".to_string(), location.to_string())],
+        body: String::new(),
+        truncated: false,
+    }
+}
+
+/// Render a form with a unique form_build_id.
+fn form(english: bool, title: &str, form_id: &str, fields: &str) -> MockResponse {
+    let form_build_id = format!("form-{}", rand::random::<u64>());
+    page(
+        english,
+        title,
+        &format!(
+            r#"<form action="" method="post" id="{form_id_attribute}" accept-charset="UTF-8">
+        {fields}
+        <input autocomplete="off" type="hidden" name="form_build_id" value="{form_build_id}" />
+        <input type="hidden" name="form_id" value="{form_id}" />
+      </form>"#,
+            form_id = form_id,
+            form_id_attribute = form_id.replace('_', "-"),
+            fields = fields,
+            form_build_id = form_build_id,
+        ),
+    )
+}
+
+/// The contact form redirects to the front page when submitted with all fields, until
+/// flood control kicks in after CONTACT_FORM_LIMIT submissions from the same client.
+fn contact_form(request: &MockRequest, state: &MockState, english: bool) -> MockResponse {
+    let title = if english {
+        "Website feedback"
+    } else {
+        "Comentarios sobre el sitio web"
+    };
+    let fields = r#"<input type="text" name="name" value="" />
+        <input type="email" name="mail" value="" />
+        <input type="text" name="subject[0][value]" value="" />
+        <textarea name="message[0][value]"></textarea>"#;
+
+    if request.method != "POST" || !request.form.contains_key("form_build_id") {
+        return form(english, title, "contact_message_feedback_form", fields);
+    }
+
+    // Like Drupal, display the form again if a field is missing or the email is invalid.
+    let missing = ["name", "mail", "subject[0][value]", "message[0][value]"]
+        .iter()
+        .any(|field| {
+            request
+                .form
+                .get(*field)
+                .is_none_or(|value| value.trim().is_empty())
+        });
+    if missing || !request.form["mail"].contains('@') {
+        let mut response = form(english, title, "contact_message_feedback_form", fields);
+        response.body = response.body.replace(
+            "<main role=\"main\">",
+            "<main role=\"main\"><div role=\"alert\">Error message</div>",
+        );
+        return response;
+    }
+
+    state.contact_submissions.fetch_add(1, Ordering::SeqCst);
+    let submissions = {
+        let mut clients = state.contact_clients.lock().unwrap();
+        let submissions = clients.entry(request.client.clone()).or_default();
+        *submissions += 1;
+        *submissions
+    };
+    let flooded = *state.fault.lock().unwrap() == Some(Fault::FloodControl);
+    if flooded || submissions > CONTACT_FORM_LIMIT {
+        let message = if english {
+            "You cannot send more than 5 messages in 1 hour. Try again later."
+        } else {
+            "No le está permitido enviar más de 5 mensajes en 1 hora. Inténtelo más tarde."
+        };
+        let mut response = form(english, title, "contact_message_feedback_form", fields);
+        response.body = response.body.replace(
+            "<main role=\"main\">",
+            &format!("<main role=\"main\">{}", message),
+        );
+        return response;
+    }
+
+    redirect(if english { "/en" } else { "/es" })
+}
+
+/// The search form redirects to the results page when submitted, which contains the
+/// search keys.
+fn search(request: &MockRequest, english: bool) -> MockResponse {
+    let title = if english { "Search" } else { "Buscar" };
+    let language = if english { "en" } else { "es" };
+
+    if request.method == "POST" {
+        let keys = request.form.get("keys").cloned().unwrap_or_default();
+        return redirect(&format!(
+            "/{}/search/node?keys={}",
+            language,
+            common::percent_encode(&keys)
+        ));
+    }
+
+    let keys = request.query.get("keys").cloned().unwrap_or_default();
+    let fields = format!(
+        r#"<input type="search" name="keys" value="{}" />"#,
+        html_escape(&keys)
+    );
+    let mut response = form(english, title, "search_form", &fields);
+    if keys.is_empty() {
+        return response;
+    }
+
+    // Like Drupal, ignore case and accents, and only return nodes containing every word.
+    let words: Vec<String> = keys.split_whitespace().map(fold).collect();
+    let mut matches = Vec::new();
+    for content_type in &[
+        common::ContentType::Article,
+        common::ContentType::BasicPage,
+        common::ContentType::Recipe,
+    ] {
+        for node in common::get_nodes(content_type) {
+            let (url, node_title) = if english {
+                (node.url_en, node.title_en)
+            } else {
+                (node.url_es, node.title_es)
+            };
+            let folded = fold(node_title);
+            if words.iter().all(|word| folded.contains(word.as_str())) {
+                matches.push((url.to_string(), node_title.to_string()));
+            }
+        }
+    }
+
+    let page: usize = request
+        .query
+        .get("page")
+        .and_then(|page| page.parse().ok())
+        .unwrap_or(0);
+    let mut results = String::new();
+    for (url, node_title) in matches
+        .iter()
+        .skip(page * SEARCH_RESULTS_PER_PAGE)
+        .take(SEARCH_RESULTS_PER_PAGE)
+    {
+        results.push_str(&format!(
+            r#"<li><h3 class="search-result__title"><a href="{}">{}</a></h3></li>"#,
+            url,
+            html_escape(node_title)
+        ));
+    }
+    let results = if results.is_empty() {
+        if english {
+            "<h2>Your search yielded no results.</h2>".to_string()
+        } else {
+            "<h2>Su búsqueda no produjo resultados</h2>".to_string()
+        }
+    } else {
+        format!(
+            r#"<h2>Search results</h2><ol class="search-results">{}</ol>"#,
+            results
+        )
+    };
+    let pager = if matches.len() > (page + 1) * SEARCH_RESULTS_PER_PAGE {
+        format!(
+            r#"<nav class="pager" role="navigation"><ul class="pager__items js-pager__items"><li class="pager__item pager__item--next"><a href="?keys={}&amp;page={}" title="Go to next page" rel="next">Next ›</a></li></ul></nav>"#,
+            common::percent_encode(&keys),
+            page + 1
+        )
+    } else {
+        String::new()
+    };
+    response.body = response
+        .body
+        .replace("</main>", &format!("{}{}</main>", results, pager));
+    response
+}
+
+/// Lowercase text and remove Spanish accents, for accent insensitive matching.
+fn fold(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' => 'a',
+            'é' => 'e',
+            'í' => 'i',
+            'ó' => 'o',
+            'ú' | 'ü' => 'u',
+            'ñ' => 'n',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Log in as the admin user, setting a session cookie.
+fn log_in(request: &MockRequest, state: &MockState) -> MockResponse {
+    let fields = r#"<input type="text" name="name" value="" />
+        <input type="password" name="pass" />"#;
+    if request.method != "POST" {
+        return form(true, "Log in", "user_login_form", fields);
+    }
+
+    let name = request.form.get("name").map(String::as_str);
+    let pass = request.form.get("pass").map(String::as_str);
+    if name != Some("admin") || pass != Some(ADMIN_PASSWORD) {
+        let mut response = form(true, "Log in", "user_login_form", fields);
+        response.body = response.body.replace(
+            "<main role=\"main\">",
+            "<main role=\"main\">Unrecognized username or password.",
+        );
+        return response;
+    }
+
+    state.logins.fetch_add(1, Ordering::SeqCst);
+    let mut response = redirect("/user/1");
+    response.headers.push((
+        "Set-Cookie".to_string(),
+        format!("{}; path=/; HttpOnly", SESSION_COOKIE),
+    ));
+    response
+}
+
+/// Drupal's access denied page, returned for admin pages when not logged in.
+fn access_denied() -> MockResponse {
+    let mut response = page(true, "Access denied", "");
+    response.status = 403;
+    response
+}
+
+/// Add a form_token to a form, as Drupal does for logged in users.
+fn with_form_token(mut response: MockResponse) -> MockResponse {
+    response.body = response.body.replace(
+        r#"<input type="hidden" name="form_id""#,
+        r#"<input type="hidden" name="form_token" value="mock-token" />
+        <input type="hidden" name="form_id""#,
+    );
+    response
+}
+
+/// The performance page clears the page cache when its form is submitted.
+fn performance(request: &MockRequest, state: &MockState) -> MockResponse {
+    if !request.logged_in {
+        return access_denied();
+    }
+
+    if request.method == "POST"
+        && request.form.get("form_token").map(String::as_str) == Some("mock-token")
+    {
+        state.page_cache.lock().unwrap().clear();
+        state.cache_clears.fetch_add(1, Ordering::SeqCst);
+        state.caches_cleared_message.store(true, Ordering::SeqCst);
+        return redirect("/admin/config/development/performance");
+    }
+
+    let fields =
+        r#"<input type="checkbox" name="page_cache_maximum_age" value="1" checked="checked" />"#;
+    let mut response = with_form_token(form(
+        true,
+        "Performance",
+        "system_performance_settings",
+        fields,
+    ));
+    // Like Drupal, the form redirects back to itself, which then displays a message.
+    if state.caches_cleared_message.swap(false, Ordering::SeqCst) {
+        response.body = response.body.replace(
+            "<main role=\"main\">",
+            r#"<main role="main"><div role="contentinfo" aria-label="Status message" class="messages messages--status">Caches cleared.</div>"#,
+        );
+    }
+    response
+}
+
+/// The image style flush form deletes all derivatives of the style when submitted.
+fn flush_image_style(request: &MockRequest, state: &MockState, style: &str) -> MockResponse {
+    if !request.logged_in {
+        return access_denied();
+    }
+
+    if request.method == "POST"
+        && request.form.get("form_token").map(String::as_str) == Some("mock-token")
+    {
+        let prefix = format!("{}/", style);
+        state
+            .derivatives
+            .lock()
+            .unwrap()
+            .retain(|derivative| !derivative.starts_with(&prefix));
+        state.image_flushes.fetch_add(1, Ordering::SeqCst);
+        return redirect("/admin/config/media/image-styles");
+    }
+
+    with_form_token(form(
+        true,
+        &format!(
+            "Are you sure you want to apply the updated {} image effect to all images?",
+            style
+        ),
+        "image_style_flush_form",
+        "",
+    ))
+}
+
+/// The node edit form removes the node from the page cache when saved, along with the
+/// front page and listings, as Drupal does when the node's cache tags are invalidated.
+fn edit_node(
+    request: &MockRequest,
+    state: &MockState,
+    content_type: &common::ContentType,
+    node: &common::Node,
+) -> MockResponse {
+    if !request.logged_in {
+        return access_denied();
+    }
+
+    let title = request.form.get("title[0][value]").map(String::as_str);
+    if request.method == "POST"
+        && request.form.get("form_token").map(String::as_str) == Some("mock-token")
+        && title == Some(node.title_en)
+    {
+        let nid = format!("/node/{}", node.nid);
+        state.page_cache.lock().unwrap().retain(|target| {
+            let path = common::percent_decode(target.split('?').next().unwrap_or_default());
+            let path = path.trim_end_matches('/');
+            !(path == node.url_en
+                || path == node.url_es
+                || path == nid
+                || path.is_empty()
+                || path == "/en"
+                || path == "/es"
+                || path.ends_with("/recipes")
+                || path.ends_with("/articles"))
+        });
+        state.node_saves.fetch_add(1, Ordering::SeqCst);
+        return redirect(node.url_en);
+    }
+
+    let machine_name = match content_type {
+        common::ContentType::Article => "article",
+        common::ContentType::BasicPage => "page",
+        common::ContentType::Recipe => "recipe",
+    };
+    let fields = format!(
+        r#"<input type="text" name="title[0][value]" value="{}" />
+        <input type="hidden" name="changed" value="1604312931" />
+        <textarea name="body[0][value]">&lt;p&gt;Lorem ipsum.&lt;/p&gt;</textarea>"#,
+        html_escape(node.title_en)
+    );
+    with_form_token(form(
+        true,
+        &format!("Edit {}", node.title_en),
+        &format!("node_{}_edit_form", machine_name),
+        &fields,
+    ))
+}
+
+/// Modify the response to simulate the requested fault.
+fn inject_fault(request: &MockRequest, mut response: MockResponse, fault: Fault) -> MockResponse {
+    let path = request.path.as_str();
+    if path.starts_with("/core/") || path.starts_with("/sites/") {
+        return response;
+    }
+
+    match fault {
+        Fault::WrongTitle => {
+            let start = response.body.find("<title>");
+            let end = response.body.find("</title>");
+            if let (Some(start), Some(end)) = (start, end) {
+                response
+                    .body
+                    .replace_range(start + "<title>".len()..end, "Oops | Umami Food Magazine");
+            }
+        }
+        Fault::MissingFormBuildId => {
+            response.body = response
+                .body
+                .lines()
+                .filter(|line| !line.contains(r#"name="form_build_id""#))
+                .collect::<Vec<&str>>()
+                .join("\n");
+        }
+        Fault::ServerError => {
+            response.status = 500;
+            response.headers = vec![("Content-Type".to_string(), "text/plain".to_string())];
+            response.body =
+                "The website encountered an unexpected error. Please try again later.".to_string();
+        }
+        Fault::PhpWarning => {
+            response.body = response.body.replace(
+                "<main role=\"main\">",
+                r#"<main role="main"><div role="contentinfo" aria-label="Warning message" class="messages messages--warning"><em class="placeholder">Warning</em>: Undefined variable $ingredients in <em class="placeholder">umami_preprocess_node()</em> (line <em class="placeholder">42</em> of <em class="placeholder">core/profiles/demo_umami/themes/umami/umami.theme</em>).</div>"#,
+            );
+        }
+        Fault::MaintenanceMode => {
+            let english = !path.starts_with("/es");
+            let (title, message) = if english {
+                ("Site under maintenance", "Umami Food Magazine is currently under maintenance. We should be back shortly. Thank you for your patience.")
+            } else {
+                ("Sitio en mantenimiento", "Umami Food Magazine está en mantenimiento. Volveremos en breve. Gracias por su paciencia.")
+            };
+            response = page(english, title, message);
+            response.status = 503;
+        }
+        Fault::DatabaseError => {
+            response.status = 500;
+            response.headers = vec![("Content-Type".to_string(), "text/html".to_string())];
+            response.body = r#"The website encountered an unexpected error. Please try again later.<br><em class="placeholder">Drupal\Core\Database\DatabaseExceptionWrapper</em>: SQLSTATE[HY000] [2002] Connection refused in <em class="placeholder">Drupal\Core\Database\Connection-&gt;query()</em>"#.to_string();
+        }
+        Fault::SlowResponse(delay) => thread::sleep(delay),
+        Fault::TruncatedBody => response.truncated = true,
+        // Contact form submissions are throttled by contact_form().
+        Fault::FloodControl => (),
+        Fault::NoSearchRedirect => {
+            if request.method == "POST" && path.ends_with("/search/node") {
+                let english = !path.starts_with("/es/");
+                let title = if english { "Search" } else { "Buscar" };
+                let fields = r#"<input type="search" name="keys" value="" />"#;
+                response = form(english, title, "search_form", fields);
+            }
+        }
+    }
+
+    response
+}
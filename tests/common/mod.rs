@@ -0,0 +1,49 @@
+// Each integration test only uses some of these helpers.
+#![allow(dead_code)]
+
+pub mod mock;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::config;
+
+use mock::MockServer;
+
+/// Configure the load test with the Umami options, then run it against the mock server
+/// with the number of users, all started at once, for the number of seconds. `register`
+/// registers the task sets to run. Metrics aren't reset once the users have started, so
+/// requests made when a user starts are included.
+pub fn run_against_mock<F>(
+    server: &MockServer,
+    umami_args: &[&str],
+    users: usize,
+    seconds: usize,
+    register: F,
+) -> GooseMetrics
+where
+    F: FnOnce(GooseAttack) -> Result<GooseAttack, GooseError>,
+{
+    let args = umami_args.iter().map(|arg| arg.to_string());
+    let (configuration, _) = config::parse_args(args).unwrap();
+    config::set_configuration(configuration);
+
+    let host = server.url();
+    let users = users.to_string();
+    let seconds = seconds.to_string();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        &users,
+        "--hatch-rate",
+        &users,
+        "--run-time",
+        &seconds,
+        "--no-reset-metrics",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_attack = GooseAttack::initialize_with_config(configuration).unwrap();
+    register(goose_attack).unwrap().execute().unwrap()
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::common::get_links;
+use umami_loadtest::crawler::{
+    crawl, crawlable_path, get_sitemap_urls, parse_robots, set_user_agent,
+};
+use umami_loadtest::{config, metrics};
+
+use common::mock::MockServer;
+
+const FRONT_PAGE_EN: &str = include_str!("fixtures/front_page_en.html");
+
+/// Part of Drupal's robots.txt, with a group for one crawler.
+const ROBOTS_TXT: &str = "# robots.txt
+User-agent: *
+Crawl-delay: 10
+# CSS, JS, Images
+Allow: /core/*.css$
+Allow: /core/*.css?
+Disallow: /core/
+Disallow: /admin/
+Disallow: /search/
+Disallow: /user/login
+Disallow: /index.php/user/login
+
+User-agent: BadBot
+User-agent: UmamiCrawler
+Disallow: /es/
+
+Sitemap: https://drupal-9.0.7.ddev.site/sitemap.xml
+";
+
+#[test]
+fn robots_txt_rules() {
+    let robots = parse_robots(ROBOTS_TXT, "Mozilla/5.0 (compatible; Googlebot/2.1)");
+    assert_eq!(robots.crawl_delay, Some(10.0));
+    assert_eq!(
+        robots.sitemaps,
+        vec!["https://drupal-9.0.7.ddev.site/sitemap.xml"]
+    );
+    assert!(robots.allowed("/en/recipes/"));
+    assert!(robots.allowed("/es/recetas/"));
+    assert!(!robots.allowed("/admin/content"));
+    assert!(!robots.allowed("/user/login?destination=/node/1"));
+    assert!(robots.allowed("/en/user/login"));
+    assert!(!robots.allowed("/core/misc/drupal.js"));
+
+    // The longest matching pattern applies, and $ anchors the end of the path.
+    assert!(robots.allowed("/core/themes/stable/css/system.css"));
+    assert!(robots.allowed("/core/themes/stable/css/system.css?v=1"));
+    assert!(!robots.allowed("/core/themes/stable/css/system.css.map"));
+
+    // A group naming the crawler applies instead of the group for all crawlers.
+    let robots = parse_robots(ROBOTS_TXT, "UmamiCrawler/1.0");
+    assert_eq!(robots.crawl_delay, None);
+    assert!(!robots.allowed("/es/recetas/"));
+    assert!(robots.allowed("/admin/content"));
+
+    assert!(parse_robots("", "UmamiCrawler/1.0").allowed("/admin/content"));
+}
+
+#[test]
+fn sitemap_urls() {
+    let sitemap = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://drupal-9.0.7.ddev.site/en</loc></url>
+  <url>
+    <loc>https://drupal-9.0.7.ddev.site/en/search/node?keys=soup&amp;page=1</loc>
+  </url>
+</urlset>"#;
+    assert_eq!(
+        get_sitemap_urls(sitemap),
+        vec![
+            "https://drupal-9.0.7.ddev.site/en",
+            "https://drupal-9.0.7.ddev.site/en/search/node?keys=soup&page=1",
+        ]
+    );
+}
+
+#[test]
+fn crawlable_links() {
+    let page = "https://drupal-9.0.7.ddev.site/en";
+    let links: Vec<String> = get_links(FRONT_PAGE_EN)
+        .iter()
+        .filter_map(|link| crawlable_path(link, page))
+        .collect();
+    assert!(links.contains(&"/en/recipes".to_string()));
+    assert!(links.contains(&"/es".to_string()));
+    assert!(!links.iter().any(|link| link.contains('#')));
+
+    assert_eq!(
+        crawlable_path(
+            "https://drupal-9.0.7.ddev.site/es/tags/champiñones#top",
+            page
+        ),
+        Some("/es/tags/champi%C3%B1ones".to_string())
+    );
+    assert_eq!(crawlable_path("https://www.drupal.org/", page), None);
+    assert_eq!(crawlable_path("//cdn.example.com/jquery.js", page), None);
+    assert_eq!(crawlable_path("mailto:info@example.com", page), None);
+    assert_eq!(crawlable_path("/sites/default/files/image.jpg", page), None);
+}
+
+#[test]
+fn crawler_honors_robots_txt() {
+    let args = vec!["--crawler".to_string()];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    assert!(configuration.crawler);
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "1",
+        "--hatch-rate",
+        "1",
+        "--run-time",
+        "2",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("Crawler")
+                .register_task(task!(set_user_agent).set_on_start())
+                .register_task(task!(crawl)),
+        )
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+
+    // The mock server's sitemap lists the contact forms, which robots.txt disallows.
+    let metrics = metrics::get_metrics();
+    let crawler = &metrics.crawler[&("en".to_string(), "sitemap".to_string())];
+    assert!(crawler.pages > 0);
+    assert!(crawler.disallowed > 0);
+}
+
+#[test]
+fn crawler_user_agent_must_be_valid() {
+    let args = vec!["--crawler-user-agent=".to_string()];
+    assert!(config::parse_args(args).is_err());
+}
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use umami_loadtest::fake;
+
+#[test]
+fn contact_messages() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut mails = HashSet::new();
+    let mut lengths = HashSet::new();
+    for (index, english) in [true, false].iter().enumerate() {
+        for _ in 0..50 {
+            let message = fake::contact_message(&mut rng, *english, &fake::next_id(index));
+            assert!(message.name.contains(' '), "{}", message.name);
+            assert!(message.mail.is_ascii(), "{}", message.mail);
+            assert!(message.mail.ends_with("@example.com"));
+            assert!(!message.subject.is_empty());
+            assert!(!message.subject.contains("{}"), "{}", message.subject);
+            assert!(!message.message.is_empty());
+            // Every submission comes from a different email address.
+            assert!(mails.insert(message.mail));
+            lengths.insert(message.message.len());
+        }
+    }
+    assert!(lengths.len() > 10);
+
+    // Spanish messages are written in Spanish.
+    assert!((0..10).any(|_| {
+        let message = fake::contact_message(&mut rng, false, &fake::next_id(2));
+        message.message.chars().any(|c| "áéíóúñ¿".contains(c))
+    }));
+}
+
+#[test]
+fn mail_ids_are_numbered_per_user() {
+    assert_eq!(fake::next_id(10), "10.1");
+    assert_eq!(fake::next_id(11), "11.1");
+    assert_eq!(fake::next_id(10), "10.2");
+}
+
+#[test]
+fn node_drafts() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut titles = HashSet::new();
+    for english in &[true, false] {
+        for _ in 0..50 {
+            let draft = fake::node_draft(&mut rng, *english);
+            assert!(!draft.title.contains("{}"), "{}", draft.title);
+            assert!(!draft.summary.contains("{}"), "{}", draft.summary);
+            assert!(draft.body.starts_with("<p>") && draft.body.ends_with("</p>"));
+            assert!(["easy", "medium", "hard"].contains(&draft.difficulty));
+            assert!(draft.preparation_time > 0 && draft.preparation_time % 5 == 0);
+            assert!(draft.servings > 0);
+            titles.insert(draft.title);
+        }
+    }
+    assert!(titles.len() > 20);
+
+    // Spanish drafts are written in Spanish.
+    assert!((0..10).any(|_| {
+        let draft = fake::node_draft(&mut rng, false);
+        draft.body.chars().any(|c| "áéíóúñ".contains(c))
+    }));
+}
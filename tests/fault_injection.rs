@@ -0,0 +1,141 @@
+mod common;
+
+use std::time::Duration;
+
+use goose::metrics::GooseMetrics;
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::english::*;
+
+use common::mock::{Fault, MockServer};
+
+/// Run a single task against the mock server while it injects the fault. Returns the
+/// metrics and the contents of the debug log, where set_failure records its messages.
+fn run_with_fault(fault: Fault, task: GooseTask) -> (GooseMetrics, String) {
+    let server = MockServer::start();
+    server.set_fault(Some(fault));
+
+    let host = server.url();
+    let debug_file =
+        std::env::temp_dir().join(format!("umami-fault-{}.log", rand::random::<u64>()));
+    let debug_file_name = debug_file.to_str().unwrap();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "1",
+        "--hatch-rate",
+        "1",
+        "--run-time",
+        "2",
+        "--debug-file",
+        debug_file_name,
+        // Count status codes, and don't reset metrics once the user has started, as a
+        // failure recorded for a request made before the reset underflows its counts.
+        "--status-codes",
+        "--no-reset-metrics",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(taskset!("Faulty user").register_task(task))
+        .execute()
+        .unwrap();
+
+    let debug_log = std::fs::read_to_string(&debug_file).unwrap_or_default();
+    let _ = std::fs::remove_file(&debug_file);
+
+    (goose_metrics, debug_log)
+}
+
+/// Total failed requests across all request names.
+fn fail_count(goose_metrics: &GooseMetrics) -> usize {
+    goose_metrics
+        .requests
+        .values()
+        .map(|request| request.fail_count)
+        .sum()
+}
+
+#[test]
+fn wrong_title_is_detected() {
+    let (goose_metrics, debug_log) = run_with_fault(Fault::WrongTitle, task!(front_page_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(debug_log.contains("title not found: Home"));
+}
+
+#[test]
+fn missing_form_build_id_is_detected() {
+    let (goose_metrics, debug_log) =
+        run_with_fault(Fault::MissingFormBuildId, task!(anonymous_contact_form_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(debug_log.contains("no form_build_id on page"));
+}
+
+#[test]
+fn server_error_is_detected() {
+    let (goose_metrics, debug_log) = run_with_fault(Fault::ServerError, task!(front_page_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(goose_metrics
+        .requests
+        .values()
+        .any(|request| request.status_code_counts.contains_key(&500)));
+    assert!(debug_log.contains("error page"));
+}
+
+#[test]
+fn php_warning_is_detected() {
+    let (goose_metrics, debug_log) = run_with_fault(Fault::PhpWarning, task!(front_page_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(debug_log.contains("php warning: Undefined variable $ingredients"));
+}
+
+#[test]
+fn maintenance_mode_is_detected() {
+    let (goose_metrics, debug_log) = run_with_fault(Fault::MaintenanceMode, task!(front_page_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(debug_log.contains("maintenance page"));
+}
+
+#[test]
+fn database_error_is_detected() {
+    let (goose_metrics, debug_log) = run_with_fault(Fault::DatabaseError, task!(front_page_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(debug_log.contains("database error: SQLSTATE[HY000] [2002] Connection refused"));
+}
+
+#[test]
+fn slow_response_is_measured() {
+    let delay = Duration::from_millis(500);
+    let (goose_metrics, _) = run_with_fault(Fault::SlowResponse(delay), task!(front_page_en));
+
+    assert_eq!(fail_count(&goose_metrics), 0);
+    assert!(goose_metrics
+        .requests
+        .values()
+        .any(|request| request.max_response_time >= delay.as_millis() as usize));
+}
+
+#[test]
+fn truncated_body_is_detected() {
+    let (goose_metrics, debug_log) = run_with_fault(Fault::TruncatedBody, task!(front_page_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(debug_log.contains("failed to parse page"));
+}
+
+#[test]
+fn missing_search_redirect_is_detected() {
+    let (goose_metrics, debug_log) = run_with_fault(Fault::NoSearchRedirect, task!(search_en));
+
+    assert!(fail_count(&goose_metrics) > 0);
+    assert!(debug_log.contains("search didn't redirect"));
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::english::feeds_en;
+use umami_loadtest::feeds::{check_well_formed, get_feed_links, validate_feed};
+use umami_loadtest::spanish::feeds_es;
+use umami_loadtest::{config, metrics};
+
+use common::mock::MockServer;
+
+const RSS_ES: &str = include_str!("fixtures/rss_es.xml");
+
+#[test]
+fn well_formed_xml() {
+    assert_eq!(check_well_formed(RSS_ES), Ok(()));
+    assert_eq!(
+        check_well_formed("<?xml version=\"1.0\"?>\n<!-- feed -->\n<a x='1' y=\"&lt;\"><b/><![CDATA[<&>]]>&#233;&#xE9;</a>\n"),
+        Ok(())
+    );
+
+    let malformed = [
+        ("", "no root element"),
+        ("<a><b></a></b>", "</a> closes <b>"),
+        ("<a>", "<a> isn't closed"),
+        ("<a></a><b></b>", "<b> is a second root element"),
+        ("<a></a>text", "text outside the root element"),
+        (
+            "<a>Fish &amp; chips & peas</a>",
+            "undefined entity or unescaped &: & peas",
+        ),
+        ("<a>&nbsp;</a>", "undefined entity or unescaped &: &nbsp;"),
+        ("<a x=1></a>", "<a> has an unquoted x"),
+        (
+            "<a x=\"1\"y=\"2\"></a>",
+            "<a> has attributes without whitespace between",
+        ),
+        ("<a x=\"<\"></a>", "<a> has a < in x"),
+        ("<!DOCTYPE a><a></a>", "DTDs aren't supported"),
+        ("<a><!-- </a>", "unterminated comment"),
+        ("<1a></1a>", "invalid element name <1a"),
+    ];
+    for (xml, error) in &malformed {
+        assert_eq!(check_well_formed(xml), Err(error.to_string()), "{}", xml);
+    }
+}
+
+#[test]
+fn feeds_are_validated() {
+    assert_eq!(validate_feed(RSS_ES, "es", None), Ok(3));
+    assert_eq!(
+        validate_feed(RSS_ES, "es", Some("Umami Food Magazine")),
+        Ok(3)
+    );
+    assert_eq!(
+        validate_feed(RSS_ES, "es", Some("Postres")),
+        Err("channel title Umami Food Magazine, expected Postres".to_string())
+    );
+    assert_eq!(
+        validate_feed(RSS_ES, "en", None),
+        Err("channel language es, expected en".to_string())
+    );
+
+    // A node with the wrong title, one that isn't in the catalog, and one in English.
+    let feed = RSS_ES.replace(
+        "<title>Quiche mediterráneo profundo</title>",
+        "<title>Quiche</title>",
+    );
+    assert_eq!(
+        validate_feed(&feed, "es", None),
+        Err(
+            "https://umami.example.com/es/recipes/quiche-mediterr%C3%A1neo-profundo \
+             is titled Quiche, expected Quiche mediterráneo profundo"
+                .to_string()
+        )
+    );
+    let feed = RSS_ES.replace("/es/articles/guia-umami", "/es/articles/guia");
+    assert!(validate_feed(&feed, "es", None)
+        .unwrap_err()
+        .ends_with("isn't in the catalog"));
+    let feed = RSS_ES
+        .replace(
+            "/es/articles/guia-umami-de-nuestras-setas-preferidas",
+            "/en/articles/the-umami-guide-to-our-favourite-mushrooms",
+        )
+        .replace(
+            "Guía Umami de nuestras setas preferidas",
+            "The Umami guide to our favorite mushrooms",
+        );
+    assert!(validate_feed(&feed, "es", None)
+        .unwrap_err()
+        .ends_with("isn't in es"));
+
+    assert!(validate_feed(&RSS_ES.replace("</item>\n<item>", "<item>"), "es", None).is_err());
+    assert_eq!(
+        validate_feed("<feed><title>Atom</title></feed>", "es", None),
+        Err("not an RSS 2.0 feed".to_string())
+    );
+}
+
+#[test]
+fn feed_links() {
+    let html = r#"<head>
+    <link rel="alternate" type="application/rss+xml" title="Desserts" href="https://umami.example.com/en/taxonomy/term/2/feed" />
+    <link rel="alternate" hreflang="es" href="https://umami.example.com/es/recipe-category/postres" />
+    <link rel="stylesheet" media="all" href="/sites/default/files/css/css_umami.css" />
+    </head>"#;
+    assert_eq!(
+        get_feed_links(html),
+        vec!["https://umami.example.com/en/taxonomy/term/2/feed".to_string()]
+    );
+    assert!(get_feed_links("<head></head>").is_empty());
+}
+
+#[test]
+fn feed_reader_sends_conditional_requests() {
+    let args = vec!["--feeds".to_string(), "--feed-interval=0".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec!["--feeds".to_string(), "--feed-interval=1".to_string()];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    assert!(configuration.feeds);
+    assert_eq!(configuration.feed_interval, Some(1));
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "2",
+        "--hatch-rate",
+        "2",
+        "--run-time",
+        "2",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("Feed reader")
+                .register_task(task!(feeds_en))
+                .register_task(task!(feeds_es)),
+        )
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    let names: Vec<&String> = goose_metrics.requests.keys().collect();
+    assert!(names.iter().any(|name| name.ends_with("/es/rss.xml")));
+    assert!(names
+        .iter()
+        .any(|name| name.ends_with("/en/taxonomy/term/%/feed")));
+
+    // Feeds are only returned in full the first time each reader polls them.
+    let metrics = metrics::get_metrics();
+    let front = &metrics.feeds[&("en".to_string(), "front".to_string())];
+    assert!(front.conditional > 0);
+    assert_eq!(front.not_modified, front.conditional);
+    assert_eq!(front.items, 10 * (front.polls - front.not_modified));
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+use reqwest::header::{HeaderMap, CACHE_CONTROL};
+
+use umami_loadtest::admin::log_in;
+use umami_loadtest::english::images_en;
+use umami_loadtest::images::{get_derivatives, is_generated, Derivative};
+use umami_loadtest::spanish::images_es;
+use umami_loadtest::{config, metrics};
+
+use common::mock::{MockServer, ADMIN_PASSWORD};
+
+const ARTICLE_EN: &str = include_str!("fixtures/article_en.html");
+const CONTACT_EN: &str = include_str!("fixtures/contact_en.html");
+
+fn derivative(style: &str, url: &str) -> Derivative {
+    Derivative {
+        url: url.to_string(),
+        style: style.to_string(),
+    }
+}
+
+#[test]
+fn derivatives_on_page() {
+    // Unlike static assets, the srcset of the <source> is included.
+    assert_eq!(
+        get_derivatives(ARTICLE_EN),
+        vec![
+            derivative(
+                "scale_crop_7_3_wide",
+                "/sites/default/files/styles/scale_crop_7_3_wide/public/2020-11/carrots-umami.jpg?itok=3aCNb2Ja"
+            ),
+            derivative(
+                "large_3_2_768x512",
+                "/sites/default/files/styles/large_3_2_768x512/public/2020-11/carrots-umami.jpg?itok=RzqYqZ5m"
+            ),
+            derivative(
+                "medium_8_7",
+                "/sites/default/files/styles/medium_8_7/public/2020-11/umami-bundle.png?itok=tlwHaVYn"
+            ),
+        ]
+    );
+    assert_eq!(
+        get_derivatives(CONTACT_EN),
+        vec![derivative(
+            "medium_8_7",
+            "/sites/default/files/styles/medium_8_7/public/2020-11/umami-bundle.png?itok=tlwHaVYn"
+        )]
+    );
+}
+
+#[test]
+fn srcset_candidates() {
+    let html = r#"<img src="/core/misc/logo.svg" srcset="https://umami.example.com/sites/default/files/styles/small/public/a.jpg?itok=abc 1x, /sites/default/files/styles/large/public/a.jpg?h=1&amp;itok=d-_f 2x, /sites/default/files/styles/large/public/b.jpg 3x" />"#;
+    assert_eq!(
+        get_derivatives(html),
+        vec![
+            derivative(
+                "small",
+                "/sites/default/files/styles/small/public/a.jpg?itok=abc"
+            ),
+            derivative(
+                "large",
+                "/sites/default/files/styles/large/public/a.jpg?h=1&itok=d-_f"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn image_flush_requires_image_styles_and_admin_password() {
+    let args = vec!["--image-flush".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec!["--image-styles".to_string(), "--image-flush".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec![
+        "--image-flush".to_string(),
+        format!("--admin-password={}", ADMIN_PASSWORD),
+    ];
+    assert!(config::parse_args(args).is_err());
+}
+
+#[test]
+fn generated_derivatives_from_headers() {
+    let mut headers = HeaderMap::new();
+    assert!(!is_generated(&headers));
+    headers.insert(CACHE_CONTROL, "max-age=1209600".parse().unwrap());
+    assert!(!is_generated(&headers));
+    headers.insert(CACHE_CONTROL, "no-cache, private".parse().unwrap());
+    assert!(is_generated(&headers));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "X-Generator",
+        "Drupal 9 (https://www.drupal.org)".parse().unwrap(),
+    );
+    assert!(is_generated(&headers));
+}
+
+#[test]
+fn image_flush_interval_requires_image_flush() {
+    let args = vec![
+        "--image-styles".to_string(),
+        "--image-flush-interval=10".to_string(),
+    ];
+    assert!(config::parse_args(args).is_err());
+}
+
+#[test]
+fn derivatives_are_generated_after_flush() {
+    let args = vec![
+        "--image-styles".to_string(),
+        "--image-flush".to_string(),
+        format!("--admin-password={}", ADMIN_PASSWORD),
+    ];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    assert!(configuration.image_styles);
+    assert!(configuration.image_flush);
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "1",
+        "--hatch-rate",
+        "1",
+        "--run-time",
+        "2",
+        "--no-reset-metrics",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("Image style user")
+                .register_task(task!(log_in).set_on_start())
+                .register_task(task!(images_en))
+                .register_task(task!(images_es)),
+        )
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    let names: Vec<&String> = goose_metrics.requests.keys().collect();
+    assert!(names.iter().any(|name| name.ends_with("/manage/%/flush")));
+    assert!(names
+        .iter()
+        .any(|name| name.ends_with("image style large_3_2_768x512")));
+
+    // Every derivative is generated again after its style is flushed, then delivered. The
+    // user can be stopped between the two requests. Styles are flushed once per interval.
+    assert!(server.image_flushes() > 0);
+    assert!(server.image_flushes() <= 3);
+    assert!(server.image_generations() > 0);
+    let metrics = metrics::get_metrics();
+    assert!(metrics.image_flushes > 0);
+    for style in &["large_3_2_768x512", "scale_crop_7_3_wide"] {
+        let generated = &metrics.images[&(style.to_string(), "generated".to_string())];
+        let cached = &metrics.images[&(style.to_string(), "cached".to_string())];
+        assert!(
+            generated.min >= 20,
+            "{} generated in {}ms",
+            style,
+            generated.min
+        );
+        assert!(cached.count + 1 >= generated.count);
+    }
+}
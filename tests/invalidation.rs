@@ -0,0 +1,90 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::{config, metrics};
+
+use common::mock::{MockServer, ADMIN_PASSWORD};
+
+#[test]
+fn admin_user_invalidates_caches_under_load() {
+    let args = vec![
+        "--invalidate".to_string(),
+        "both".to_string(),
+        "--invalidate-interval=1".to_string(),
+        format!("--admin-password={}", ADMIN_PASSWORD),
+    ];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    config::set_configuration(configuration);
+
+    // Goose allocates the first and tenth users to the admin task set.
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "10",
+        "--hatch-rate",
+        "10",
+        "--run-time",
+        "4",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_attack = GooseAttack::initialize_with_config(configuration).unwrap();
+    let goose_metrics = umami_loadtest::register_tasksets(goose_attack)
+        .unwrap()
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+
+    // The load test can stop after the mock server invalidated caches, but before the
+    // invalidation was recorded.
+    let metrics = metrics::get_metrics();
+    assert!(!metrics.invalidations.is_empty());
+    assert!(metrics.invalidations.len() <= server.cache_clears() + server.node_saves());
+    assert!(!metrics.recovery.is_empty());
+    // Only one of the admin users logs in and invalidates caches.
+    assert_eq!(server.logins(), 1);
+
+    // A second load test in the same process elects its own admin user.
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "1",
+        "--hatch-rate",
+        "1",
+        "--run-time",
+        "2",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_attack = GooseAttack::initialize_with_config(configuration).unwrap();
+    umami_loadtest::register_tasksets(goose_attack)
+        .unwrap()
+        .execute()
+        .unwrap();
+    assert_eq!(server.logins(), 1);
+    assert!(server.cache_clears() + server.node_saves() > 0);
+}
+
+#[test]
+fn invalidate_requires_admin_password() {
+    let args = vec!["--invalidate=clear".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec!["--invalidate=sometimes".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec![
+        "--invalidate=clear".to_string(),
+        "--invalidate-interval=0".to_string(),
+        format!("--admin-password={}", ADMIN_PASSWORD),
+    ];
+    assert!(config::parse_args(args).is_err());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::config;
+use umami_loadtest::english::{jsonapi_collection_en, jsonapi_filter_en};
+use umami_loadtest::jsonapi::{validate_collection, CollectionQuery, Resource};
+use umami_loadtest::spanish::{jsonapi_collection_es, jsonapi_filter_es};
+
+use common::mock::MockServer;
+
+const RECIPES_ES: &str = include_str!("fixtures/jsonapi_recipes_es.json");
+
+/// The query the recipes fixture was requested with.
+fn recipes_query() -> CollectionQuery {
+    CollectionQuery {
+        fields: vec!["title", "path", "langcode"],
+        include: Some("field_recipe_category"),
+        limit: Some(2),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn query_strings() {
+    assert_eq!(
+        recipes_query().query_string(Resource::Recipe),
+        "fields[node--recipe]=title,path,langcode,field_recipe_category\
+         &include=field_recipe_category&page[limit]=2"
+    );
+    let query = CollectionQuery {
+        title: Some("Acompañamientos".to_string()),
+        sort: Some("-name"),
+        ..Default::default()
+    };
+    assert_eq!(
+        query.query_string(Resource::RecipeCategory),
+        "filter[name]=Acompa%C3%B1amientos&sort=-name"
+    );
+}
+
+#[test]
+fn collections_are_validated() {
+    let collection =
+        validate_collection(RECIPES_ES, Resource::Recipe, "es", &recipes_query()).unwrap();
+    assert_eq!(collection.resources, 2);
+    assert_eq!(
+        collection.next.unwrap(),
+        "https://drupal-9.0.7.ddev.site/es/jsonapi/node/recipe?fields%5Bnode--recipe%5D=\
+         title%2Cpath%2Clangcode%2Cfield_recipe_category&include=field_recipe_category\
+         &page%5Boffset%5D=2&page%5Blimit%5D=2"
+    );
+
+    // Resources must be of the right type, in the right language, and match the filter.
+    let invalid = |resource: Resource, langcode: &str, query: &CollectionQuery| {
+        validate_collection(RECIPES_ES, resource, langcode, query).unwrap_err()
+    };
+    assert!(invalid(Resource::Article, "es", &recipes_query()).contains("type is node--recipe"));
+    assert!(invalid(Resource::Recipe, "en", &recipes_query()).contains("langcode is es"));
+    let mut query = recipes_query();
+    query.title = Some("Quiche mediterráneo profundo".to_string());
+    assert!(invalid(Resource::Recipe, "es", &query).contains("doesn't match the filter"));
+    let mut query = recipes_query();
+    query.fields = vec!["title", "path"];
+    assert!(invalid(Resource::Recipe, "es", &query).contains("langcode is not in the sparse"));
+    let mut query = recipes_query();
+    query.limit = Some(1);
+    assert!(invalid(Resource::Recipe, "es", &query).contains("at most 1"));
+
+    // Titles must match the catalog, and included relationships must be in the document.
+    let renamed = RECIPES_ES.replace("Bizcochos veganos", "Brownies veganos");
+    let e = validate_collection(&renamed, Resource::Recipe, "es", &recipes_query()).unwrap_err();
+    assert!(
+        e.contains("expected Bizcochos veganos de chocolate y nueces"),
+        "{}",
+        e
+    );
+    let without_included = RECIPES_ES.replace("\"included\"", "\"excluded\"");
+    let e = validate_collection(&without_included, Resource::Recipe, "es", &recipes_query())
+        .unwrap_err();
+    assert!(e.contains("field_recipe_category"), "{}", e);
+
+    let error = r#"{"jsonapi":{"version":"1.0"},"errors":[{"title":"Bad Request","status":"400","detail":"Invalid nested filtering."}]}"#;
+    let e = validate_collection(error, Resource::Recipe, "es", &recipes_query()).unwrap_err();
+    assert_eq!(e, "JSON:API error: Invalid nested filtering.");
+    assert!(validate_collection("<html>", Resource::Recipe, "es", &recipes_query()).is_err());
+}
+
+#[test]
+fn terms_are_found_in_the_catalog() {
+    assert_eq!(
+        Resource::RecipeCategory.catalog_title("es", "/recipe-category/acompa%C3%B1amientos"),
+        Some("Acompañamientos".to_string())
+    );
+    assert_eq!(
+        Resource::Recipe.catalog_title("en", "/recipes/deep-mediterranean-quiche"),
+        Some("Deep mediterranean quiche".to_string())
+    );
+    assert_eq!(Resource::Tags.catalog_title("en", "/tags/unknown"), None);
+}
+
+#[test]
+fn jsonapi_client_has_no_failures() {
+    let (configuration, _) = config::parse_args(vec!["--jsonapi".to_string()]).unwrap();
+    assert!(configuration.jsonapi);
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "2",
+        "--hatch-rate",
+        "2",
+        "--run-time",
+        "2",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("JSON:API client")
+                .register_task(task!(jsonapi_collection_en))
+                .register_task(task!(jsonapi_filter_en))
+                .register_task(task!(jsonapi_collection_es))
+                .register_task(task!(jsonapi_filter_es)),
+        )
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    let names: Vec<&String> = goose_metrics.requests.keys().collect();
+    assert!(names
+        .iter()
+        .any(|name| name.ends_with("/es/jsonapi/node/recipe filter")
+            || name.ends_with("/es/jsonapi/node/article filter")));
+    assert!(names.iter().any(|name| name.ends_with(" next")));
+}
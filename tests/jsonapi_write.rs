@@ -0,0 +1,149 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::config;
+use umami_loadtest::english::jsonapi_write_en;
+use umami_loadtest::fake::NodeDraft;
+use umami_loadtest::jsonapi::{
+    self, create_document, log_in, update_document, validate_resource, Auth,
+};
+use umami_loadtest::spanish::jsonapi_write_es;
+
+use common::mock::{MockServer, ADMIN_PASSWORD};
+
+fn draft() -> NodeDraft {
+    NodeDraft {
+        title: "Tarta de zanahoria para dos".to_string(),
+        summary: "Una nueva versión de Tarta de zanahoria.".to_string(),
+        body: "<p>Precaliente el horno a 180 °C.</p>".to_string(),
+        difficulty: "medium",
+        preparation_time: 20,
+        cooking_time: 45,
+        servings: 2,
+    }
+}
+
+#[test]
+fn documents() {
+    let document = create_document(jsonapi::Resource::Recipe, "es", &draft());
+    let data = &document["data"];
+    assert_eq!(data["type"], "node--recipe");
+    assert!(data["id"].is_null());
+    assert_eq!(data["attributes"]["title"], "Tarta de zanahoria para dos");
+    assert_eq!(data["attributes"]["langcode"], "es");
+    assert_eq!(data["attributes"]["status"], false);
+    assert_eq!(data["attributes"]["field_difficulty"], "medium");
+    assert_eq!(data["attributes"]["field_number_of_servings"], 2);
+    assert_eq!(
+        data["attributes"]["field_recipe_instruction"]["format"],
+        "basic_html"
+    );
+    assert!(data["attributes"]["body"].is_null());
+
+    let document = create_document(jsonapi::Resource::Article, "en", &draft());
+    let attributes = &document["data"]["attributes"];
+    assert_eq!(
+        attributes["body"]["value"],
+        "<p>Precaliente el horno a 180 °C.</p>"
+    );
+    assert_eq!(
+        attributes["body"]["summary"],
+        "Una nueva versión de Tarta de zanahoria."
+    );
+    assert!(attributes["field_difficulty"].is_null());
+
+    let document = update_document(jsonapi::Resource::Article, "1234", "Nuevo título");
+    assert_eq!(
+        document.to_string(),
+        r#"{"data":{"attributes":{"title":"Nuevo título"},"id":"1234","type":"node--article"}}"#
+    );
+}
+
+#[test]
+fn written_resources_are_validated() {
+    let created = r#"{"jsonapi":{"version":"1.0"},"data":{"type":"node--recipe","id":"1234","attributes":{"title":"Tarta de zanahoria para dos","langcode":"es"}}}"#;
+    let resource = jsonapi::Resource::Recipe;
+    assert_eq!(
+        validate_resource(created, resource, "Tarta de zanahoria para dos"),
+        Ok("1234".to_string())
+    );
+    let e = validate_resource(created, resource, "Tarta de zanahoria").unwrap_err();
+    assert_eq!(
+        e,
+        "1234: title is Tarta de zanahoria para dos, expected Tarta de zanahoria"
+    );
+    let e = validate_resource(created, jsonapi::Resource::Article, "Tarta").unwrap_err();
+    assert_eq!(e, "1234 is a node--recipe, expected node--article");
+
+    let error = r#"{"jsonapi":{"version":"1.0"},"errors":[{"title":"Unprocessable Entity","status":"422","detail":"title: This value should not be null."},{"title":"Unprocessable Entity","status":"422","detail":"field_difficulty: The value you selected is not a valid choice."}]}"#;
+    let e = validate_resource(error, resource, "Tarta").unwrap_err();
+    assert_eq!(
+        e,
+        "JSON:API error: title: This value should not be null.; \
+         field_difficulty: The value you selected is not a valid choice."
+    );
+    assert!(validate_resource("", resource, "Tarta").is_err());
+}
+
+#[test]
+fn jsonapi_write_requires_admin_password() {
+    let args = vec!["--jsonapi-write=basic".to_string()];
+    assert!(config::parse_args(args).is_err());
+    let args = vec![
+        "--jsonapi-write=oauth".to_string(),
+        format!("--admin-password={}", ADMIN_PASSWORD),
+    ];
+    assert!(config::parse_args(args).is_err());
+}
+
+#[test]
+fn jsonapi_writer_cleans_up_after_itself() {
+    let args = vec![
+        "--jsonapi-write=cookie".to_string(),
+        format!("--admin-password={}", ADMIN_PASSWORD),
+    ];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    assert_eq!(configuration.jsonapi_write, Some(Auth::Cookie));
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "2",
+        "--hatch-rate",
+        "2",
+        "--run-time",
+        "2",
+        // Keep the log in requests made when each user starts.
+        "--no-reset-metrics",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("JSON:API writer")
+                .register_task(task!(log_in).set_on_start())
+                .register_task(task!(jsonapi_write_en))
+                .register_task(task!(jsonapi_write_es)),
+        )
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    let names: Vec<&String> = goose_metrics.requests.keys().collect();
+    assert!(names.iter().any(|name| name.ends_with("/session/token")));
+    assert!(names.iter().any(|name| name.ends_with(" update")));
+
+    // A writer can be stopped between creating and deleting a node, but never leaves
+    // more than one node behind.
+    assert!(server.jsonapi_creates() > 0);
+    assert!(server.jsonapi_deletes() + 2 >= server.jsonapi_creates());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::config;
+use umami_loadtest::english::listing_pager_en;
+use umami_loadtest::spanish::listing_pager_es;
+
+use common::mock::MockServer;
+
+#[test]
+fn listing_pagers_are_followed() {
+    let args = vec!["--pager-depth".to_string(), "3=1".to_string()];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    assert_eq!(configuration.pager_depth, vec![(3, 1)]);
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "2",
+        "--hatch-rate",
+        "2",
+        "--run-time",
+        "2",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("Listing user")
+                .register_task(task!(listing_pager_en))
+                .register_task(task!(listing_pager_es)),
+        )
+        .execute()
+        .unwrap();
+
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    // Deeper pages are loaded both by following links and with the AJAX pager.
+    let names: Vec<&String> = goose_metrics.requests.keys().collect();
+    assert!(names.iter().any(|name| name.ends_with("?page=1")));
+    assert!(names.iter().any(|name| name.ends_with("?page=1 ajax")));
+}
+
+#[test]
+fn pager_depth_must_be_valid() {
+    for depth in &["0=1", "2", "2=0", "two=1"] {
+        let args = vec![format!("--pager-depth={}", depth)];
+        assert!(config::parse_args(args).is_err(), "{}", depth);
+    }
+}
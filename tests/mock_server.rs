@@ -0,0 +1,92 @@
+mod common;
+
+use goose::metrics::GooseMetrics;
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::cache::CacheResult;
+use umami_loadtest::metrics;
+use umami_loadtest::search::QueryType;
+
+use common::mock::MockServer;
+
+/// Run a short load test with all task sets against the mock server.
+fn run_load_test(server: &MockServer) -> GooseMetrics {
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "4",
+        "--hatch-rate",
+        "4",
+        "--run-time",
+        "3",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_attack = GooseAttack::initialize_with_config(configuration).unwrap();
+    umami_loadtest::register_tasksets(goose_attack)
+        .unwrap()
+        .execute()
+        .unwrap()
+}
+
+#[test]
+fn load_test_against_mock_server_has_no_failures() {
+    let server = MockServer::start();
+    let goose_metrics = run_load_test(&server);
+
+    let mut success_count = 0;
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+        success_count += request.success_count;
+    }
+    assert!(success_count > 0);
+
+    // The mock server caches pages after they're first loaded, but not forms.
+    let metrics = metrics::get_metrics();
+    let results: Vec<CacheResult> = metrics
+        .cache_response_times
+        .keys()
+        .map(|(_, result)| *result)
+        .collect();
+    assert!(results.contains(&CacheResult::Hit));
+    assert!(results.contains(&CacheResult::Miss));
+    let contact = &metrics.cache_hits[&("contact".to_string(), "en".to_string())];
+    assert_eq!(contact.hits, 0);
+    assert!(metrics
+        .cache_hits
+        .values()
+        .any(|hits| hits.page_cache_hits > 0));
+
+    // Words, phrases and unaccented words from node titles always find the node, while
+    // nonsense queries never find anything.
+    for ((language, query_type), search) in &metrics.searches {
+        match query_type {
+            QueryType::Word | QueryType::Phrase | QueryType::Accents => {
+                assert_eq!(
+                    search.no_results, 0,
+                    "{} {} search found nothing",
+                    language, query_type
+                );
+                assert_eq!(search.clicks, search.searches);
+            }
+            QueryType::NoResults => {
+                assert_eq!(search.no_results, search.searches);
+                assert_eq!(search.clicks, 0);
+            }
+            _ => (),
+        }
+    }
+
+    // The mock server serves English node paths directly and redirects Spanish ones.
+    for (language, resolution) in metrics.node_paths.keys() {
+        let expected = if language == "en" {
+            "direct"
+        } else {
+            "redirect"
+        };
+        assert_eq!(resolution, expected, "/{} node paths", language);
+    }
+}
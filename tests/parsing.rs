@@ -0,0 +1,386 @@
+use umami_loadtest::common::{
+    compile_title_patterns, decode_entities, get_ajax_content, get_alternate_links,
+    get_canonical_link, get_form_fields, get_form_value, get_language_links, get_listing_rows,
+    get_next_page, get_node_by_path, get_search_results, get_static_elements, get_title,
+    get_views_ajax_url, iri_to_uri, percent_decode, title_matches, url_path, valid_title, PageType,
+    TitleMatch,
+};
+use umami_loadtest::errors::{detect_drupal_error, ErrorCategory};
+
+const FRONT_PAGE_EN: &str = include_str!("fixtures/front_page_en.html");
+const FRONT_PAGE_ES: &str = include_str!("fixtures/front_page_es.html");
+const ARTICLE_EN: &str = include_str!("fixtures/article_en.html");
+const RECIPE_ES: &str = include_str!("fixtures/recipe_es.html");
+const CONTACT_EN: &str = include_str!("fixtures/contact_en.html");
+const CONTACT_ES: &str = include_str!("fixtures/contact_es.html");
+const SEARCH_RESULTS_EN: &str = include_str!("fixtures/search_results_en.html");
+const NODE_EDIT_EN: &str = include_str!("fixtures/node_edit_en.html");
+const RECIPES_AJAX_EN: &str = include_str!("fixtures/recipes_ajax_en.json");
+
+/// Static assets included on every Umami page, before and after the page content.
+const HEAD_ASSETS: &[&str] = &[
+    "/core/assets/vendor/modernizr/modernizr.min.js?v=3.8.0",
+    "/core/misc/modernizr-additional-tests.js?v=3.8.0",
+    "/core/profiles/demo_umami/themes/umami/logo.svg",
+];
+const FOOTER_ASSETS: &[&str] = &[
+    "/sites/default/files/styles/medium_8_7/public/2020-11/umami-bundle.png?itok=tlwHaVYn",
+    "/core/assets/vendor/jquery/jquery.min.js?v=3.5.1",
+    "/sites/default/files/js/js_N2bbRC3HOCcQ2WEj3OJnvQKWSVoY0dhgclcPf7l5Bqo.js",
+    "/sites/default/files/css/css_ZVpUCdu_LCc8DB8q8KH0whLX9J8SRVr6yVAUf-Yql2E.css",
+    "/sites/default/files/css/css_7ylvDExVmFfFdaDaXEMmHWOjLjdZKjkKdKoFHYsdUnY.css",
+];
+
+/// Build the full list of assets expected on a page with the given content assets.
+fn expected_assets(content: &[&str]) -> Vec<String> {
+    HEAD_ASSETS
+        .iter()
+        .chain(content.iter())
+        .chain(FOOTER_ASSETS.iter())
+        .map(|asset| asset.to_string())
+        .collect()
+}
+
+#[test]
+fn front_page_titles() {
+    assert!(valid_title(FRONT_PAGE_EN, "Home"));
+    assert!(!valid_title(FRONT_PAGE_EN, "Inicio"));
+    assert!(valid_title(FRONT_PAGE_ES, "Inicio"));
+    assert!(!valid_title(FRONT_PAGE_ES, "Home"));
+}
+
+#[test]
+fn node_titles() {
+    assert!(valid_title(ARTICLE_EN, "Let's hear it for carrots"));
+    assert!(!valid_title(ARTICLE_EN, "Let&#039;s hear it for carrots"));
+    assert!(valid_title(RECIPE_ES, "Quiche mediterráneo profundo"));
+    assert!(!valid_title(RECIPE_ES, "Deep mediterranean quiche"));
+}
+
+#[test]
+fn titles_are_decoded_and_normalized() {
+    assert_eq!(
+        get_title(ARTICLE_EN),
+        Some("Let's hear it for carrots | Umami Food Magazine".to_string())
+    );
+    assert_eq!(
+        get_title("<title>\n  Fish &amp; chips\n  | Umami</title>"),
+        Some("Fish & chips | Umami".to_string())
+    );
+    assert_eq!(get_title("<html><body>No title</body></html>"), None);
+    assert_eq!(
+        decode_entities("caf&#xE9; &lt;3 &bogus;"),
+        "café <3 &bogus;"
+    );
+}
+
+#[test]
+fn title_match_modes() {
+    let page_title = "Deep mediterranean quiche | Umami Food Magazine";
+    assert!(title_matches(
+        page_title,
+        "Deep mediterranean",
+        TitleMatch::Prefix
+    ));
+    assert!(!title_matches(
+        page_title,
+        "Deep mediterranean",
+        TitleMatch::Exact
+    ));
+    assert!(title_matches(
+        page_title,
+        "Deep mediterranean quiche",
+        TitleMatch::Exact
+    ));
+    assert!(title_matches(
+        page_title,
+        "^Deep .* quiche \\|",
+        TitleMatch::Regex
+    ));
+    assert!(!title_matches(page_title, "^quiche", TitleMatch::Regex));
+    assert!(!title_matches(page_title, "(quiche", TitleMatch::Regex));
+    // --title-match regex compares every title in the catalog as a pattern.
+    assert_eq!(compile_title_patterns(), Ok(()));
+}
+
+#[test]
+fn form_titles() {
+    assert!(valid_title(CONTACT_EN, "Website feedback"));
+    assert!(valid_title(CONTACT_ES, "Comentarios sobre el sitio web"));
+    assert!(valid_title(SEARCH_RESULTS_EN, "Search"));
+}
+
+#[test]
+fn contact_form_values() {
+    assert_eq!(
+        get_form_value(CONTACT_EN, "form_build_id"),
+        Some("form-1p3GkSjCZcGz2l0mE3pRQ6dHn_ZDb9zP2bqWcMh_Cmw".to_string())
+    );
+    assert_eq!(
+        get_form_value(CONTACT_EN, "form_id"),
+        Some("contact_message_feedback_form".to_string())
+    );
+    assert_eq!(
+        get_form_value(CONTACT_ES, "form_build_id"),
+        Some("form-Xh7p9a0cMfDq1RSNPsNhb8yOdlV8VqD5oZf_bHkBVw4".to_string())
+    );
+    assert_eq!(get_form_value(CONTACT_ES, "form_token"), None);
+}
+
+#[test]
+fn search_form_values() {
+    assert_eq!(
+        get_form_value(SEARCH_RESULTS_EN, "form_build_id"),
+        Some("form-KwzVnU0EzSKiBj0IeG0L1v6sBNFM8RRhwxiuHA0P3ie".to_string())
+    );
+    assert_eq!(
+        get_form_value(SEARCH_RESULTS_EN, "form_id"),
+        Some("search_form".to_string())
+    );
+    assert_eq!(get_form_value(FRONT_PAGE_EN, "form_build_id"), None);
+}
+
+#[test]
+fn search_results_and_pager() {
+    assert_eq!(
+        get_search_results(SEARCH_RESULTS_EN),
+        vec![
+            "https://drupal-9.0.7.ddev.site/en/articles/dairy-free-and-delicious-milk-chocolate",
+            "https://drupal-9.0.7.ddev.site/en/recipes/vegan-chocolate-and-nut-brownies",
+        ]
+    );
+    assert_eq!(
+        get_next_page(SEARCH_RESULTS_EN),
+        Some("?keys=chocolate&page=1".to_string())
+    );
+    assert!(get_search_results(CONTACT_EN).is_empty());
+    assert_eq!(get_next_page(CONTACT_EN), None);
+}
+
+#[test]
+fn views_ajax_pager() {
+    let content = get_ajax_content(RECIPES_AJAX_EN).unwrap();
+    assert!(content.starts_with("<div class=\"view view-recipes"));
+    assert_eq!(get_listing_rows(&content), 2);
+    assert_eq!(get_next_page(&content), Some("?page=2".to_string()));
+
+    // The settings command doesn't insert any content.
+    assert_eq!(
+        get_ajax_content(r#"[{"command":"settings","settings":{}}]"#),
+        None
+    );
+    assert_eq!(get_ajax_content(CONTACT_EN), None);
+    assert_eq!(get_listing_rows(CONTACT_EN), 0);
+
+    // The pager loads the listing from /views/ajax, with the view's arguments.
+    let html = r#"<script type="application/json" data-drupal-selector="drupal-settings-json">{"views":{"ajax_path":"\/en\/views\/ajax","ajaxViews":{"views_dom_id:5d6ee5c4":{"view_name":"recipes","view_display_id":"page_1","view_args":"","view_path":"\/recipes","view_base_path":"recipes","view_dom_id":"5d6ee5c4","pager_element":0}}}}</script>"#;
+    assert_eq!(
+        get_views_ajax_url(html),
+        Some(
+            "/en/views/ajax?_wrapper_format=drupal_ajax&view_name=recipes&view_display_id=page_1\
+             &view_args=&view_path=%2Frecipes&view_base_path=recipes&view_dom_id=5d6ee5c4\
+             &pager_element=0"
+                .to_string()
+        )
+    );
+    // Pages without an AJAX-enabled view.
+    assert_eq!(get_views_ajax_url(FRONT_PAGE_EN), None);
+    assert_eq!(get_views_ajax_url(RECIPES_AJAX_EN), None);
+}
+
+#[test]
+fn translation_links() {
+    let pair = |language: &str, url: &str| (language.to_string(), url.to_string());
+    assert_eq!(
+        get_language_links(ARTICLE_EN),
+        vec![
+            pair("en", "/en/articles/lets-hear-it-for-carrots"),
+            pair("es", "/es/articles/un-aplauso-para-las-zanahorias"),
+        ]
+    );
+    assert_eq!(
+        get_alternate_links(ARTICLE_EN),
+        vec![
+            pair(
+                "en",
+                "https://drupal-9.0.7.ddev.site/en/articles/lets-hear-it-for-carrots"
+            ),
+            pair(
+                "es",
+                "https://drupal-9.0.7.ddev.site/es/articles/un-aplauso-para-las-zanahorias"
+            ),
+        ]
+    );
+
+    // Links to Spanish paths are percent-encoded.
+    let (_, link) = &get_alternate_links(RECIPE_ES)[1];
+    assert_eq!(
+        percent_decode(url_path(link)),
+        "/es/recipes/quiche-mediterráneo-profundo"
+    );
+    assert_eq!(get_language_links(RECIPE_ES).len(), 2);
+    assert!(get_alternate_links(CONTACT_EN).is_empty());
+
+    // Nodes have a canonical link to their alias, whichever path they're loaded from.
+    assert_eq!(
+        url_path(&get_canonical_link(ARTICLE_EN).unwrap()),
+        "/en/articles/lets-hear-it-for-carrots"
+    );
+    assert_eq!(
+        percent_decode(url_path(&get_canonical_link(RECIPE_ES).unwrap())),
+        "/es/recipes/quiche-mediterráneo-profundo"
+    );
+}
+
+#[test]
+fn nodes_are_found_by_path() {
+    assert_eq!(
+        get_node_by_path(
+            "https://drupal-9.0.7.ddev.site/en/recipes/vegan-chocolate-and-nut-brownies"
+        ),
+        Some((
+            PageType::Recipe,
+            "Vegan chocolate and nut brownies".to_string()
+        ))
+    );
+    // Links to Spanish pages are percent-encoded.
+    assert_eq!(
+        get_node_by_path("/es/recipes/quiche-mediterr%C3%A1neo-profundo"),
+        Some((PageType::Recipe, "Quiche mediterráneo profundo".to_string()))
+    );
+    assert_eq!(get_node_by_path("/en/search/node"), None);
+}
+
+#[test]
+fn iris_are_normalized() {
+    // Raw UTF-8, lower-case and upper-case escapes are all normalized alike.
+    let uri = "/es/recipes/quiche-mediterr%C3%A1neo-profundo";
+    assert_eq!(iri_to_uri("/es/recipes/quiche-mediterráneo-profundo"), uri);
+    assert_eq!(
+        iri_to_uri("/es/recipes/quiche-mediterr%c3%a1neo-profundo"),
+        uri
+    );
+    assert_eq!(iri_to_uri(uri), uri);
+    assert_eq!(
+        iri_to_uri("/es/tags/champiñones"),
+        "/es/tags/champi%C3%B1ones"
+    );
+
+    // Query strings are left as is, but spaces are encoded.
+    assert_eq!(
+        iri_to_uri("/es/search/node?keys=crème brûlée&page=1"),
+        "/es/search/node?keys=cr%C3%A8me%20br%C3%BBl%C3%A9e&page=1"
+    );
+    assert_eq!(
+        iri_to_uri("/en/search/node?keys=100%"),
+        "/en/search/node?keys=100%25"
+    );
+}
+
+#[test]
+fn node_edit_form_fields() {
+    let fields = get_form_fields(NODE_EDIT_EN, "node_article_edit_form").unwrap();
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    assert_eq!(
+        field("form_token"),
+        Some("9pCj4l0tPSgMk5Fp3-C2nEhHNpZmQyRkHTx6BMAbZoU")
+    );
+    assert_eq!(field("changed"), Some("1604312931"));
+    assert_eq!(field("title[0][value]"), Some("Let's hear it for carrots"));
+    assert_eq!(
+        field("body[0][value]"),
+        Some("<p>Carrots are & always will be tasty.</p>")
+    );
+    // Selects submit the selected option, checkboxes only if checked.
+    assert_eq!(field("body[0][format]"), Some("basic_html"));
+    assert_eq!(field("moderation_state[0][state]"), Some("published"));
+    assert_eq!(field("promote[value]"), Some("1"));
+    assert_eq!(field("sticky[value]"), None);
+    assert_eq!(field("path[0][pathauto]"), None);
+    // Buttons and fields of other forms on the page aren't included.
+    assert_eq!(field("op"), None);
+    assert_eq!(field("keys"), None);
+
+    assert_eq!(get_form_fields(NODE_EDIT_EN, "search_form"), None);
+}
+
+#[test]
+fn front_page_static_assets() {
+    assert_eq!(
+        get_static_elements(FRONT_PAGE_EN),
+        expected_assets(&[
+            "/sites/default/files/styles/scale_crop_7_3_wide/public/2020-11/veggie-pasta-bake-hero-umami.jpg?itok=MSk9Txn6"
+        ])
+    );
+}
+
+#[test]
+fn node_static_assets() {
+    // The srcset on <source> is not (yet) parsed, only the <img> fallback.
+    assert_eq!(
+        get_static_elements(ARTICLE_EN),
+        expected_assets(&[
+            "/sites/default/files/styles/large_3_2_768x512/public/2020-11/carrots-umami.jpg?itok=RzqYqZ5m"
+        ])
+    );
+    assert_eq!(
+        get_static_elements(RECIPE_ES),
+        expected_assets(&[
+            "/sites/default/files/styles/large_3_2_768x512/public/2020-11/mediterranean-quiche-umami.jpg?itok=6HJvWWIk"
+        ])
+    );
+}
+
+#[test]
+fn form_static_assets() {
+    assert_eq!(get_static_elements(CONTACT_EN), expected_assets(&[]));
+    assert_eq!(get_static_elements(SEARCH_RESULTS_EN), expected_assets(&[]));
+}
+
+#[test]
+fn drupal_errors_are_classified() {
+    let category = |html: &str| detect_drupal_error(html).map(|error| error.category);
+
+    assert_eq!(category(ARTICLE_EN), None);
+    assert_eq!(
+        category("<em class=\"placeholder\">Notice</em>: Undefined index: nid in node_view()"),
+        Some(ErrorCategory::PhpNotice)
+    );
+    assert_eq!(
+        category("<em class=\"placeholder\">Deprecated function</em>: Return type of ..."),
+        Some(ErrorCategory::PhpDeprecated)
+    );
+    assert_eq!(
+        category("<b>Fatal error</b>:  Allowed memory size of 134217728 bytes exhausted"),
+        Some(ErrorCategory::PhpError)
+    );
+    assert_eq!(
+        category("<title>Sitio en mantenimiento | Umami Food Magazine</title>"),
+        Some(ErrorCategory::MaintenancePage)
+    );
+    assert_eq!(
+        category("El sitio web encontró un error inesperado. Por favor, inténtelo de nuevo."),
+        Some(ErrorCategory::ErrorPage)
+    );
+    assert_eq!(
+        category("<em class=\"placeholder\">PDOException</em>: SQLSTATE[08004] [1040] Too many connections in <em class=\"placeholder\">Drupal\\Core\\Database\\Connection-&gt;open()</em>"),
+        Some(ErrorCategory::DatabaseError)
+    );
+    assert_eq!(
+        category(
+            "<b>Fatal error</b>:  Uncaught PDOException: SQLSTATE[HY000] [2002] Connection refused"
+        ),
+        Some(ErrorCategory::DatabaseError)
+    );
+    // Content that mentions database errors isn't an error.
+    assert_eq!(
+        category("<p>Too many connections? A PDOException is nothing to fear.</p>"),
+        None
+    );
+}
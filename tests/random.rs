@@ -0,0 +1,47 @@
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use umami_loadtest::random;
+
+/// A user with the given index, as Goose creates them.
+fn user(index: usize) -> GooseUser {
+    let configuration = GooseConfiguration::parse_args_default::<&str>(&[]).unwrap();
+    let base_url = reqwest::Url::parse("http://127.0.0.1/").unwrap();
+    let mut user = GooseUser::single(base_url, &configuration).unwrap();
+    user.weighted_users_index = index;
+    user
+}
+
+/// Start the task as the user, returning the first two random numbers it draws.
+fn draw(user: &GooseUser, task: &'static str) -> (u64, u64) {
+    random::start_task(user, task);
+    (random::user_rng(user).gen(), random::user_rng(user).gen())
+}
+
+/// The first two random numbers drawn with the seed.
+fn expected(seed: u64) -> (u64, u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (rng.gen(), rng.gen())
+}
+
+#[test]
+fn choices_dont_depend_on_task_order() {
+    // Without --seed, generators are derived from seed 0.
+    let first = user(1);
+    let second = user(2);
+    let first_draws = [draw(&first, "a"), draw(&first, "b"), draw(&first, "a")];
+    let second_draws = [draw(&second, "b"), draw(&second, "a"), draw(&second, "a")];
+
+    // Each invocation of a task gets its own generator, whichever task ran before it.
+    assert_eq!(first_draws[0], expected(random::task_seed(0, 1, "a", 0)));
+    assert_eq!(first_draws[1], expected(random::task_seed(0, 1, "b", 0)));
+    assert_eq!(first_draws[2], expected(random::task_seed(0, 1, "a", 1)));
+    assert_eq!(second_draws[0], expected(random::task_seed(0, 2, "b", 0)));
+    assert_eq!(second_draws[1], expected(random::task_seed(0, 2, "a", 0)));
+    assert_eq!(second_draws[2], expected(random::task_seed(0, 2, "a", 1)));
+    assert_ne!(first_draws[0], first_draws[2]);
+    assert_ne!(first_draws[0], second_draws[1]);
+}
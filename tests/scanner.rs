@@ -0,0 +1,68 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::english::scanner_en;
+use umami_loadtest::spanish::scanner_es;
+use umami_loadtest::{config, metrics};
+
+use common::mock::MockServer;
+
+#[test]
+fn scanner_gets_localized_404_pages() {
+    let args = vec![
+        "--scanner".to_string(),
+        "--scanner-paths".to_string(),
+        "/wp-login.php,/.env".to_string(),
+    ];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    assert!(configuration.scanner);
+    assert_eq!(configuration.scanner_paths, vec!["/wp-login.php", "/.env"]);
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "2",
+        "--hatch-rate",
+        "2",
+        "--run-time",
+        "2",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_metrics = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_taskset(
+            taskset!("Scanner")
+                .register_task(task!(scanner_en))
+                .register_task(task!(scanner_es)),
+        )
+        .execute()
+        .unwrap();
+
+    // 404 pages and hidden files blocked with a 403 aren't failures.
+    for (name, request) in &goose_metrics.requests {
+        assert_eq!(request.fail_count, 0, "{} failed", name);
+    }
+    let names: Vec<&String> = goose_metrics.requests.keys().collect();
+    assert!(names.iter().any(|name| name.ends_with("/wp-login.php")));
+    assert!(names.iter().any(|name| name.ends_with("/%random")));
+
+    // 404 pages are measured separately.
+    let metrics = metrics::get_metrics();
+    for language in &["en", "es"] {
+        let listed = &metrics.not_found[&(language.to_string(), "listed".to_string())];
+        assert!(listed.count > 0);
+    }
+}
+
+#[test]
+fn scanner_paths_must_be_absolute() {
+    let args = vec!["--scanner-paths=/wp-login.php,.env".to_string()];
+    assert!(config::parse_args(args).is_err());
+}
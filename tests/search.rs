@@ -0,0 +1,46 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use umami_loadtest::search::{self, QueryType};
+
+/// Generate queries of one type, with a fixed seed.
+fn generate(english: bool, query_type: QueryType) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..50)
+        .map(|_| search::generate(&mut rng, english, query_type))
+        .collect()
+}
+
+#[test]
+fn query_types() {
+    for query in generate(true, QueryType::Phrase) {
+        assert!(query.split_whitespace().count() <= 3, "{}", query);
+    }
+    for query in generate(false, QueryType::Accents) {
+        assert_eq!(query, search::remove_accents(&query));
+    }
+    for query in generate(true, QueryType::StopWords) {
+        assert!(query.split_whitespace().count() >= 2, "{}", query);
+    }
+    for query in generate(false, QueryType::NoResults) {
+        assert_eq!(query.len(), 17, "{}", query);
+    }
+    assert_eq!(search::remove_accents("tailandés"), "tailandes");
+}
+
+#[test]
+fn search_mix() {
+    let mix = search::parse_mix("word=4,no_results=1").unwrap();
+    assert_eq!(mix, vec![(QueryType::Word, 4), (QueryType::NoResults, 1)]);
+    assert!(search::parse_mix("word").is_err());
+    assert!(search::parse_mix("words=1").is_err());
+    assert!(search::parse_mix("word=0").is_err());
+
+    // Only query types in the mix are generated.
+    let mut rng = StdRng::seed_from_u64(42);
+    let mix = search::parse_mix("typo=1").unwrap();
+    for _ in 0..20 {
+        let query = search::generate_query(&mut rng, true, &mix);
+        assert_eq!(query.query_type, QueryType::Typo);
+    }
+}
@@ -0,0 +1,22 @@
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::config;
+
+#[test]
+fn task_set_names_follow_registration() {
+    let args = vec!["--crawler".to_string(), "--feeds".to_string()];
+    let (configuration, _) = config::parse_args(args).unwrap();
+    config::set_configuration(configuration);
+
+    let args = ["--host", "http://127.0.0.1/", "--users", "1"];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_attack = GooseAttack::initialize_with_config(configuration).unwrap();
+    umami_loadtest::register_tasksets(goose_attack).unwrap();
+
+    // Optional task sets are only registered when enabled, so they don't have fixed
+    // indexes.
+    let names: Vec<&str> = (0..5).map(umami_loadtest::task_set_name).collect();
+    assert_eq!(names, vec!["english", "spanish", "crawler", "feeds", ""]);
+}
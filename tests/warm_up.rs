@@ -0,0 +1,63 @@
+mod common;
+
+use goose::prelude::*;
+use goose::GooseConfiguration;
+use gumdrop::Options;
+
+use umami_loadtest::cache::CacheResult;
+use umami_loadtest::common::{get_nodes, get_terms, ContentType};
+use umami_loadtest::warm_up::get_pages;
+use umami_loadtest::{config, metrics};
+
+use common::mock::MockServer;
+
+#[test]
+fn warm_up_loads_every_page_then_resets_metrics() {
+    let (configuration, _) = config::parse_args(vec!["--warm-up".to_string()]).unwrap();
+    config::set_configuration(configuration);
+
+    let server = MockServer::start();
+    let host = server.url();
+    // Goose starts the run time before the warm-up, so it must be long enough for users to
+    // start after it.
+    let args = [
+        "--host",
+        &host,
+        "--users",
+        "2",
+        "--hatch-rate",
+        "2",
+        "--run-time",
+        "5",
+    ];
+    let configuration = GooseConfiguration::parse_args_default(&args).unwrap();
+    let goose_attack = GooseAttack::initialize_with_config(configuration).unwrap();
+    umami_loadtest::register_tasksets(goose_attack)
+        .unwrap()
+        .execute()
+        .unwrap();
+
+    // Every node is loaded by alias in both languages and by nid, every term in both
+    // languages, and the front page and listings in both languages.
+    let nodes = get_nodes(&ContentType::Article).len()
+        + get_nodes(&ContentType::BasicPage).len()
+        + get_nodes(&ContentType::Recipe).len();
+    let pages = get_pages();
+    assert_eq!(pages.len(), nodes * 3 + get_terms().len() * 2 + 6);
+
+    let metrics = metrics::get_metrics();
+    let warm_up = metrics.warm_up.as_ref().expect("no warm-up metrics");
+    assert_eq!(warm_up.pages, pages.len());
+    assert_eq!(warm_up.failures, 0);
+    assert!(warm_up.static_assets > 0);
+
+    // Each page was loaded once during the warm-up, so none were cached.
+    for (name, result) in warm_up.metrics.cache_response_times.keys() {
+        assert_eq!(*result, CacheResult::Miss, "{} was cached", name);
+    }
+    // After the warm-up, pages were served from cache.
+    assert!(metrics
+        .cache_hits
+        .values()
+        .any(|hits| hits.page_cache_hits > 0));
+}